@@ -2,27 +2,41 @@
 // Clean-room implementation inspired by Impala UX by pythops.
 
 use crate::{
-    backend::{iwd::IwdBackend, networkd::NetworkdBackend, traits::EthernetBackend},
+    backend::{
+        BackendKind, Backends, is_service_active,
+        privilege::{Escalation, PrivilegeConfig},
+        traits::{CommandResult, EthernetBackend, WifiBackend},
+    },
     domain::{
         common::{ActiveTab, StartupTabPolicy, Toast, ToastKind, WifiFocus},
-        ethernet::{EthernetIface, EthernetState},
-        wifi::{WifiNetwork, WifiState},
+        enterprise::EnterpriseForm,
+        ethernet::{EthernetChange, EthernetIface, EthernetState},
+        profile::ProfileForm,
+        share::WifiShare,
+        wifi::{KnownSort, SecretKind, SecretRequest, WifiNetwork, WifiSecret, WifiState},
     },
+    error::NettuiError,
+    event::{BackendEvent, Event},
+    jobs::{JobKind, JobManager, JobOutcome},
     keybinds::Keybinds,
 };
 use anyhow::Result;
 use ratatui::widgets::TableState;
-use std::time::{Duration, Instant};
+use std::{collections::HashSet, sync::Arc, time::Duration};
 use tokio::process::Command;
-use tokio::task::JoinHandle;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::time::Instant;
 
 #[derive(Debug, Clone, Copy)]
 pub struct AppConfig {
     pub startup_policy: StartupTabPolicy,
     pub tick_ms: u64,
     pub data_refresh_ms: u64,
+    pub safety_refresh_ms: u64,
     pub job_timeout_scan_ms: u64,
     pub job_timeout_connect_ms: u64,
+    pub job_timeout_ethernet_ms: u64,
+    pub job_timeout_profile_ms: u64,
     pub scan_debounce_ms: u64,
     pub esc_quit: bool,
 }
@@ -33,8 +47,11 @@ impl Default for AppConfig {
             startup_policy: StartupTabPolicy::PreferActive,
             tick_ms: 250,
             data_refresh_ms: 900,
+            safety_refresh_ms: 15_000,
             job_timeout_scan_ms: 12_000,
             job_timeout_connect_ms: 20_000,
+            job_timeout_ethernet_ms: 30_000,
+            job_timeout_profile_ms: 30_000,
             scan_debounce_ms: 700,
             esc_quit: true,
         }
@@ -56,10 +73,17 @@ pub struct App {
     pub show_wifi_details: bool,
     pub show_unavailable_known_networks: bool,
     pub show_hidden_networks: bool,
+    pub known_sort: KnownSort,
     pub hidden_connect_prompt: bool,
     pub hidden_ssid_input: String,
     pub wifi_passphrase_prompt_ssid: Option<String>,
     pub wifi_passphrase_input: String,
+    pub wifi_username_input: String,
+    pub wifi_username_editing: bool,
+    pub wifi_secret_request: Option<SecretRequest>,
+    pub enterprise_form: Option<EnterpriseForm>,
+    pub profile_form: Option<ProfileForm>,
+    pub wifi_share: Option<WifiShare>,
 
     pub ethernet: EthernetState,
     pub ethernet_state: TableState,
@@ -67,23 +91,62 @@ pub struct App {
     pub last_error: Option<String>,
     pub last_action: Option<String>,
     pub toast: Option<Toast>,
-    pub wifi_scan_pending: bool,
-    pub wifi_connect_pending: bool,
+    pub jobs: JobManager<App>,
+    pub wifi_service_down: bool,
+    wifi_query_error: Option<String>,
     last_data_refresh_at: Instant,
-    refresh_requested: bool,
-    wifi_scan_started_at: Option<Instant>,
-    wifi_connect_started_at: Option<Instant>,
+    last_wifi_refresh_at: Instant,
+    wifi_refresh_requested: bool,
+    wifi_refresh_pending: bool,
+    wifi_events_live: bool,
+    ethernet_refresh_requested: bool,
+    ethernet_refresh_pending: bool,
+    // Links outside the Ethernet table (Wi-Fi, bridges, ...) that already asked for a
+    // refresh; their later RTM_NEWLINKs are ignored.
+    ethernet_foreign_links: HashSet<u32>,
+    announce_refresh: bool,
+    ethernet_events_live: bool,
     last_scan_request_at: Option<Instant>,
 
-    wifi_backend: IwdBackend,
-    eth_backend: NetworkdBackend,
-    wifi_scan_task: Option<JoinHandle<Result<()>>>,
-    wifi_connect_task: Option<JoinHandle<Result<()>>>,
-    wifi_connect_context: Option<WifiConnectContext>,
+    backend_kind: BackendKind,
+    wifi_backend: Arc<dyn WifiBackend>,
+    eth_backend: Arc<dyn EthernetBackend>,
+    snapshot_tx: UnboundedSender<Snapshot>,
+    snapshots: UnboundedReceiver<Snapshot>,
+}
+
+enum Snapshot {
+    Wifi {
+        requested: Option<String>,
+        state: Result<WifiState>,
+        details: Option<Box<EthernetIface>>,
+    },
+    Ethernet(Result<Vec<EthernetIface>>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum EthernetAction {
+    RenewDhcp,
+    SetLink(bool),
+    Reconfigure,
+    ForceRenew,
+}
+
+impl EthernetAction {
+    fn label(self, iface: &str) -> String {
+        match self {
+            Self::RenewDhcp => format!("Renewing DHCP on {iface}"),
+            Self::SetLink(true) => format!("Setting {iface} link up"),
+            Self::SetLink(false) => format!("Setting {iface} link down"),
+            Self::Reconfigure => format!("Reconfiguring {iface}"),
+            Self::ForceRenew => format!("DHCP force renew on {iface}"),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 struct WifiConnectContext {
+    iface: String,
     ssid: String,
     disconnect: bool,
     used_passphrase: bool,
@@ -91,22 +154,30 @@ struct WifiConnectContext {
 
 impl App {
     pub async fn new(config: AppConfig) -> Result<Self> {
-        let now = Instant::now();
-        let wifi_backend = IwdBackend::new();
-        let eth_backend = NetworkdBackend::new();
+        Self::with_backends(config, Backends::detect(PrivilegeConfig::load()).await).await
+    }
 
-        let wifi = wifi_backend
-            .query_state()
-            .await
-            .unwrap_or_else(|_| WifiState::empty());
+    pub async fn with_backends(config: AppConfig, backends: Backends) -> Result<Self> {
+        let now = Instant::now();
+        let Backends {
+            kind: backend_kind,
+            wifi: wifi_backend,
+            ethernet: eth_backend,
+        } = backends;
+
+        let (wifi, wifi_service_down) = match wifi_backend.query_state(None).await {
+            Ok(wifi) => (wifi, false),
+            Err(e) => (WifiState::empty(), service_unavailable(&e)),
+        };
         let ethernet = EthernetState {
-            ifaces: eth_backend.list_ifaces().unwrap_or_default(),
+            ifaces: eth_backend.list_ifaces().await.unwrap_or_default(),
+        };
+        let wifi_iface_details = match wifi.selected_iface.as_deref() {
+            Some(iface) => eth_backend.iface_details(iface).await.ok(),
+            None => None,
         };
-        let wifi_iface_details = wifi
-            .ifaces
-            .first()
-            .and_then(|iface| eth_backend.iface_details(iface).ok());
         let active_tab = determine_start_tab(config.startup_policy, &wifi, &ethernet);
+        let (snapshot_tx, snapshots) = mpsc::unbounded_channel();
 
         let mut app = Self {
             running: true,
@@ -122,32 +193,46 @@ impl App {
             show_wifi_details: false,
             show_unavailable_known_networks: false,
             show_hidden_networks: false,
+            known_sort: KnownSort::default(),
             hidden_connect_prompt: false,
             hidden_ssid_input: String::new(),
             wifi_passphrase_prompt_ssid: None,
             wifi_passphrase_input: String::new(),
+            wifi_username_input: String::new(),
+            wifi_username_editing: false,
+            wifi_secret_request: None,
+            enterprise_form: None,
+            profile_form: None,
+            wifi_share: None,
             ethernet,
             ethernet_state: TableState::default(),
             last_error: None,
             last_action: None,
             toast: None,
-            wifi_scan_pending: false,
-            wifi_connect_pending: false,
+            jobs: JobManager::default(),
+            wifi_service_down,
+            wifi_query_error: None,
             last_data_refresh_at: now,
-            refresh_requested: false,
-            wifi_scan_started_at: None,
-            wifi_connect_started_at: None,
+            last_wifi_refresh_at: now,
+            wifi_refresh_requested: false,
+            wifi_refresh_pending: false,
+            wifi_events_live: false,
+            ethernet_refresh_requested: false,
+            ethernet_refresh_pending: false,
+            ethernet_foreign_links: HashSet::new(),
+            announce_refresh: false,
+            ethernet_events_live: false,
             last_scan_request_at: None,
+            backend_kind,
             wifi_backend,
             eth_backend,
-            wifi_scan_task: None,
-            wifi_connect_task: None,
-            wifi_connect_context: None,
+            snapshot_tx,
+            snapshots,
         };
 
         app.init_wifi_states();
         app.init_ethernet_state();
-        if let Some(msg) = detect_conflicting_wifi_services().await {
+        if let Some(msg) = detect_conflicting_wifi_services(app.backend_kind).await {
             app.last_action = Some(msg.clone());
             app.set_toast(ToastKind::Info, msg);
         }
@@ -155,7 +240,8 @@ impl App {
     }
 
     pub async fn tick(&mut self) -> Result<()> {
-        self.poll_background_tasks().await;
+        self.poll_background_tasks();
+        self.apply_snapshots();
         let now = Instant::now();
 
         if let Some(t) = &self.toast
@@ -163,57 +249,239 @@ impl App {
         {
             self.toast = None;
         }
-        if self.refresh_requested
-            || refresh_due(self.last_data_refresh_at, self.config.data_refresh_ms, now)
+
+        let wifi_interval = if self.wifi_events_live {
+            self.config.safety_refresh_ms
+        } else {
+            self.config.data_refresh_ms
+        };
+        let ethernet_interval = if self.ethernet_events_live {
+            self.config.safety_refresh_ms
+        } else {
+            self.config.data_refresh_ms
+        };
+        if !self.wifi_refresh_pending
+            && (self.wifi_refresh_requested
+                || refresh_due(self.last_wifi_refresh_at, wifi_interval, now))
         {
-            self.refresh_all().await;
-            self.refresh_requested = false;
+            self.spawn_wifi_refresh();
+            self.wifi_refresh_requested = false;
+            self.last_wifi_refresh_at = now;
+        }
+        if !self.ethernet_refresh_pending
+            && (self.ethernet_refresh_requested
+                || refresh_due(self.last_data_refresh_at, ethernet_interval, now))
+        {
+            self.spawn_ethernet_refresh();
+            self.ethernet_refresh_requested = false;
             self.last_data_refresh_at = now;
         }
         Ok(())
     }
 
-    async fn refresh_all(&mut self) {
+    pub fn watch_backend_events(&mut self, events: UnboundedSender<Event>) {
+        self.wifi_events_live = self.wifi_backend.watch(events.clone());
+        self.ethernet_events_live = self.eth_backend.watch(events);
+    }
+
+    pub fn handle_backend_event(&mut self, event: BackendEvent) {
+        match event {
+            BackendEvent::WifiChanged => self.wifi_refresh_requested = true,
+            BackendEvent::SecretRequested(request) => self.open_secret_prompt(request),
+            BackendEvent::SecretCanceled => {
+                if self.wifi_secret_request.take().is_some() {
+                    self.close_wifi_passphrase_prompt();
+                    self.set_toast(ToastKind::Info, "Credentials request canceled");
+                }
+            }
+            BackendEvent::EthernetChanged(change) => self.apply_ethernet_change(change),
+        }
+    }
+
+    fn apply_ethernet_change(&mut self, change: EthernetChange) {
+        if !self.ethernet.knows(change.ifindex()) {
+            match change {
+                EthernetChange::Link(link) if self.ethernet_foreign_links.insert(link.ifindex) => {
+                    self.ethernet_refresh_requested = true;
+                }
+                EthernetChange::LinkRemoved(ifindex) => {
+                    self.ethernet_foreign_links.remove(&ifindex);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        let selected_eth = self.selected_eth_iface().map(|i| i.name.clone());
+        if let Some(notice) = self.ethernet.apply(change) {
+            self.last_action = Some(notice.clone());
+            self.set_toast(ToastKind::Info, notice);
+        }
+        self.restore_ethernet_selection(selected_eth);
+    }
+
+    fn open_secret_prompt(&mut self, request: SecretRequest) {
+        if let Some(previous) = self.wifi_secret_request.take() {
+            self.wifi_backend.answer_secret(previous.id, None);
+        }
+        self.hidden_connect_prompt = false;
+        self.set_toast(
+            ToastKind::Info,
+            format!("Credentials required for {}", request.ssid),
+        );
+        self.open_wifi_passphrase_prompt(request.ssid.clone());
+        self.wifi_username_editing = request.kind == SecretKind::UserNameAndPassword;
+        self.wifi_secret_request = Some(request);
+    }
+
+    fn spawn_wifi_refresh(&mut self) {
+        let wifi = Arc::clone(&self.wifi_backend);
+        let eth = Arc::clone(&self.eth_backend);
+        let requested = self.wifi.selected_iface.clone();
+        let tx = self.snapshot_tx.clone();
+        self.wifi_refresh_pending = true;
+        tokio::spawn(async move {
+            let state = wifi.query_state(requested.as_deref()).await;
+            let details = match state.as_ref().ok().and_then(|s| s.selected_iface.clone()) {
+                Some(iface) => eth.iface_details(&iface).await.ok().map(Box::new),
+                None => None,
+            };
+            let _ = tx.send(Snapshot::Wifi {
+                requested,
+                state,
+                details,
+            });
+        });
+    }
+
+    fn spawn_ethernet_refresh(&mut self) {
+        let eth = Arc::clone(&self.eth_backend);
+        let tx = self.snapshot_tx.clone();
+        self.ethernet_refresh_pending = true;
+        tokio::spawn(async move {
+            let _ = tx.send(Snapshot::Ethernet(eth.list_ifaces().await));
+        });
+    }
+
+    fn apply_snapshots(&mut self) {
+        while let Ok(snapshot) = self.snapshots.try_recv() {
+            match snapshot {
+                Snapshot::Wifi {
+                    requested,
+                    state,
+                    details,
+                } => {
+                    self.wifi_refresh_pending = false;
+                    // The adapter was switched while this query ran; the next refresh covers it.
+                    if requested.is_some() && requested != self.wifi.selected_iface {
+                        self.wifi_refresh_requested = true;
+                        continue;
+                    }
+                    self.apply_wifi_snapshot(state, details);
+                }
+                Snapshot::Ethernet(ifaces) => {
+                    self.ethernet_refresh_pending = false;
+                    self.apply_ethernet_snapshot(ifaces);
+                }
+            }
+        }
+
+        if self.announce_refresh
+            && !(self.wifi_refresh_requested || self.wifi_refresh_pending)
+            && !(self.ethernet_refresh_requested || self.ethernet_refresh_pending)
+        {
+            self.announce_refresh = false;
+            self.set_toast(
+                ToastKind::Info,
+                format!(
+                    "Refreshed (Known: {}, New: {}, Ethernet: {})",
+                    self.known_total_len(),
+                    self.new_total_len(),
+                    self.ethernet.ifaces.len()
+                ),
+            );
+        }
+    }
+
+    fn apply_wifi_snapshot(
+        &mut self,
+        state: Result<WifiState>,
+        details: Option<Box<EthernetIface>>,
+    ) {
         let known_ssid = self.selected_known_ssid();
         let new_ssid = self.selected_new_ssid();
-        let selected_eth = self.selected_eth_iface().map(|i| i.name.clone());
+        let adapter = self.selected_adapter_iface();
+
+        match state {
+            Ok(wifi) => {
+                self.mark_wifi_service_back();
+                self.wifi_query_error = None;
+                self.wifi = wifi;
+                self.sort_known_networks();
+                self.restore_wifi_selection(known_ssid, new_ssid, adapter);
+                self.wifi_iface_details = details.map(|d| *d);
+            }
+            Err(e) if service_unavailable(&e) => {
+                self.wifi_query_error = None;
+                if !self.wifi_service_down {
+                    self.wifi_service_down = true;
+                    let msg = format!("{} went away", self.wifi_service_name());
+                    self.last_action = Some(msg.clone());
+                    self.set_toast(ToastKind::Error, msg);
+                }
+                self.clear_wifi_state();
+            }
+            Err(e) => {
+                self.mark_wifi_service_back();
+                let msg = e.to_string();
+                if self.wifi_query_error.as_deref() != Some(msg.as_str()) {
+                    self.set_toast(ToastKind::Error, format!("Wi-Fi refresh failed: {msg}"));
+                    self.wifi_query_error = Some(msg);
+                }
+                self.clear_wifi_state();
+            }
+        }
+
+        self.ensure_valid_wifi_focus();
+        self.last_error = None;
+    }
 
-        if let Ok(wifi) = self.wifi_backend.query_state().await {
-            self.wifi = wifi;
-            self.restore_wifi_selection(known_ssid, new_ssid);
-            self.wifi_iface_details = self
-                .wifi
-                .ifaces
-                .first()
-                .and_then(|iface| self.eth_backend.iface_details(iface).ok());
+    fn mark_wifi_service_back(&mut self) {
+        if self.wifi_service_down {
+            self.wifi_service_down = false;
+            let msg = format!("{} came back", self.wifi_service_name());
+            self.last_action = Some(msg.clone());
+            self.set_toast(ToastKind::Success, msg);
         }
+    }
+
+    fn clear_wifi_state(&mut self) {
+        self.wifi = WifiState {
+            selected_iface: self.wifi.selected_iface.take(),
+            ..WifiState::empty()
+        };
+        self.restore_wifi_selection(None, None, None);
+        self.wifi_iface_details = None;
+    }
 
-        if let Ok(ifaces) = self.eth_backend.list_ifaces() {
+    fn apply_ethernet_snapshot(&mut self, ifaces: Result<Vec<EthernetIface>>) {
+        let selected_eth = self.selected_eth_iface().map(|i| i.name.clone());
+        if let Ok(ifaces) = ifaces {
             self.ethernet = EthernetState { ifaces };
             self.restore_ethernet_selection(selected_eth);
         }
 
-        self.ensure_valid_wifi_focus();
         self.last_error = None;
     }
 
-    pub async fn refresh_current(&mut self) {
-        self.refresh_all().await;
-        self.last_data_refresh_at = Instant::now();
-        self.refresh_requested = false;
-        self.set_toast(
-            ToastKind::Info,
-            format!(
-                "Refreshed (Known: {}, New: {}, Ethernet: {})",
-                self.known_total_len(),
-                self.new_total_len(),
-                self.ethernet.ifaces.len()
-            ),
-        );
+    pub fn refresh_current(&mut self) {
+        self.request_refresh();
+        self.announce_refresh = true;
     }
 
     fn request_refresh(&mut self) {
-        self.refresh_requested = true;
+        self.wifi_refresh_requested = true;
+        self.ethernet_refresh_requested = true;
     }
 
     pub fn quit(&mut self) {
@@ -343,6 +611,25 @@ impl App {
         self.show_wifi_details = !self.show_wifi_details;
     }
 
+    pub fn cycle_known_sort(&mut self) {
+        let known_ssid = self.selected_known_ssid();
+        let new_ssid = self.selected_new_ssid();
+        let adapter = self.selected_adapter_iface();
+        self.known_sort = self.known_sort.next();
+        self.sort_known_networks();
+        self.restore_wifi_selection(known_ssid, new_ssid, adapter);
+        self.set_toast(
+            ToastKind::Info,
+            format!("Known: sorted by {}", self.known_sort.label()),
+        );
+    }
+
+    fn sort_known_networks(&mut self) {
+        self.known_sort.sort(&mut self.wifi.known_networks);
+        self.known_sort
+            .sort(&mut self.wifi.unavailable_known_networks);
+    }
+
     pub fn toggle_known_show_all(&mut self) {
         self.show_unavailable_known_networks = !self.show_unavailable_known_networks;
         let len = self.known_total_len();
@@ -397,19 +684,44 @@ impl App {
     pub fn open_wifi_passphrase_prompt(&mut self, ssid: String) {
         self.wifi_passphrase_prompt_ssid = Some(ssid);
         self.wifi_passphrase_input.clear();
+        self.wifi_username_input.clear();
+        self.wifi_username_editing = false;
     }
 
     pub fn close_wifi_passphrase_prompt(&mut self) {
+        if let Some(request) = self.wifi_secret_request.take() {
+            self.wifi_backend.answer_secret(request.id, None);
+        }
         self.wifi_passphrase_prompt_ssid = None;
         self.wifi_passphrase_input.clear();
+        self.wifi_username_input.clear();
+        self.wifi_username_editing = false;
     }
 
     pub fn passphrase_input_push(&mut self, c: char) {
-        self.wifi_passphrase_input.push(c);
+        if self.wifi_username_editing {
+            self.wifi_username_input.push(c);
+        } else {
+            self.wifi_passphrase_input.push(c);
+        }
     }
 
     pub fn passphrase_input_backspace(&mut self) {
-        self.wifi_passphrase_input.pop();
+        if self.wifi_username_editing {
+            self.wifi_username_input.pop();
+        } else {
+            self.wifi_passphrase_input.pop();
+        }
+    }
+
+    pub fn toggle_prompt_field(&mut self) {
+        if self
+            .wifi_secret_request
+            .as_ref()
+            .is_some_and(|r| r.kind == SecretKind::UserNameAndPassword)
+        {
+            self.wifi_username_editing = !self.wifi_username_editing;
+        }
     }
 
     pub async fn submit_hidden_connect(&mut self) {
@@ -418,20 +730,29 @@ impl App {
             self.set_toast(ToastKind::Error, "SSID cannot be empty");
             return;
         }
-
-        match self.wifi_backend.connect_hidden(&ssid).await {
-            Ok(()) => {
-                self.last_action = Some(format!("Connect hidden requested: {ssid}"));
-                self.set_toast(ToastKind::Info, format!("Connect hidden requested: {ssid}"));
-                self.notify("Wi-Fi", &format!("Connect hidden: {ssid}"));
-                self.close_hidden_connect_prompt();
-                self.request_refresh();
-            }
-            Err(e) => {
-                let msg = friendly_wifi_error("connect hidden network", &e);
-                self.set_toast(ToastKind::Error, msg);
-            }
+        if self.wifi_connect_blocked() {
+            self.set_toast(ToastKind::Info, "Wi-Fi connect already in progress");
+            return;
         }
+        let Some(iface) = self.wifi_iface_or_toast() else {
+            return;
+        };
+
+        self.last_action = Some(format!("Connect hidden requested: {ssid}"));
+        self.set_toast(ToastKind::Info, format!("Connect hidden requested: {ssid}"));
+        self.notify("Wi-Fi", &format!("Connect hidden: {ssid}"));
+        self.close_hidden_connect_prompt();
+
+        let backend = Arc::clone(&self.wifi_backend);
+        let context = WifiConnectContext {
+            iface: iface.clone(),
+            ssid: ssid.clone(),
+            disconnect: false,
+            used_passphrase: false,
+        };
+        self.spawn_wifi_connect(context, async move {
+            backend.connect_hidden(&iface, &ssid).await
+        });
     }
 
     pub async fn submit_wifi_passphrase_connect(&mut self) {
@@ -444,27 +765,133 @@ impl App {
             return;
         }
 
-        if self.wifi_connect_pending {
+        if let Some(request) = self.wifi_secret_request.take() {
+            let username = match request.kind {
+                SecretKind::UserNameAndPassword => {
+                    let username = self.wifi_username_input.trim().to_string();
+                    if username.is_empty() {
+                        self.set_toast(ToastKind::Error, "Username cannot be empty");
+                        self.wifi_secret_request = Some(request);
+                        return;
+                    }
+                    Some(username)
+                }
+                _ => None,
+            };
+            self.wifi_backend.answer_secret(
+                request.id,
+                Some(WifiSecret {
+                    username,
+                    password: passphrase,
+                }),
+            );
+            self.jobs.restart_timer(JobKind::WifiConnect);
+            self.last_action = Some(format!("Connecting to {ssid}..."));
+            self.set_toast(ToastKind::Info, format!("Connecting to {ssid}..."));
+            self.close_wifi_passphrase_prompt();
+            return;
+        }
+
+        if self.wifi_connect_blocked() {
             self.set_toast(ToastKind::Info, "Wi-Fi connect already in progress");
             return;
         }
+        let Some(iface) = self.wifi_iface_or_toast() else {
+            return;
+        };
 
         self.last_action = Some(format!("Connecting to {ssid}..."));
         self.set_toast(ToastKind::Info, format!("Connecting to {ssid}..."));
         self.close_wifi_passphrase_prompt();
 
-        self.wifi_connect_pending = true;
-        self.wifi_connect_started_at = Some(Instant::now());
-        self.wifi_connect_context = Some(WifiConnectContext {
+        let backend = Arc::clone(&self.wifi_backend);
+        let context = WifiConnectContext {
+            iface: iface.clone(),
             ssid: ssid.clone(),
             disconnect: false,
             used_passphrase: true,
-        });
-        self.wifi_connect_task = Some(tokio::spawn(async move {
-            IwdBackend::new()
-                .connect_with_passphrase(&ssid, &passphrase)
+        };
+        self.spawn_wifi_connect(context, async move {
+            backend
+                .connect_with_passphrase(&iface, &ssid, &passphrase)
                 .await
-        }));
+        });
+    }
+
+    pub fn open_enterprise_form(&mut self, ssid: String) {
+        self.set_toast(
+            ToastKind::Info,
+            format!("{ssid} uses 802.1X; enter its credentials"),
+        );
+        self.enterprise_form = Some(EnterpriseForm::new(ssid));
+    }
+
+    pub fn close_enterprise_form(&mut self) {
+        self.enterprise_form = None;
+    }
+
+    pub fn submit_enterprise_form(&mut self) {
+        let Some(form) = self.enterprise_form.clone() else {
+            return;
+        };
+        if let Err(e) = form.config.validate() {
+            self.set_toast(ToastKind::Error, e.to_string());
+            return;
+        }
+        if self.wifi_connect_blocked() {
+            self.set_toast(ToastKind::Info, "Wi-Fi connect already in progress");
+            return;
+        }
+        let Some(iface) = self.wifi_iface_or_toast() else {
+            return;
+        };
+
+        let ssid = form.ssid;
+        self.close_enterprise_form();
+        self.last_action = Some(format!("Provisioning {ssid}..."));
+        self.set_toast(ToastKind::Info, format!("Provisioning {ssid}..."));
+
+        let backend = Arc::clone(&self.wifi_backend);
+        let config = form.config;
+        let context = WifiConnectContext {
+            iface: iface.clone(),
+            ssid: ssid.clone(),
+            disconnect: false,
+            used_passphrase: false,
+        };
+        self.jobs.spawn(
+            JobKind::WifiConnect,
+            format!("Connecting to {ssid}"),
+            Duration::from_millis(self.config.job_timeout_connect_ms),
+            async move {
+                let out = backend.provision_enterprise(&ssid, &config).await?;
+                if out.dry_run {
+                    return Ok(Some(out));
+                }
+                backend.connect(&iface, &ssid).await?;
+                Ok(None)
+            },
+            move |app: &mut App, outcome| app.finish_enterprise_connect(context, outcome),
+        );
+    }
+
+    fn finish_enterprise_connect(
+        &mut self,
+        ctx: WifiConnectContext,
+        outcome: JobOutcome<Option<CommandResult>>,
+    ) {
+        let outcome = match outcome {
+            JobOutcome::Finished(Ok(Some(out))) => {
+                let msg = format!("Dry run: {}", out.dry_run_summary());
+                self.last_action = Some(msg.clone());
+                self.set_toast(ToastKind::Info, msg);
+                return;
+            }
+            JobOutcome::Finished(result) => JobOutcome::Finished(result.map(|_| ())),
+            JobOutcome::TimedOut => JobOutcome::TimedOut,
+            JobOutcome::Canceled => JobOutcome::Canceled,
+        };
+        self.finish_wifi_connect(ctx, outcome);
     }
 
     pub fn notify(&self, title: &str, body: &str) {
@@ -483,7 +910,7 @@ impl App {
 
     pub async fn wifi_scan(&mut self) -> Result<()> {
         let now = Instant::now();
-        if self.wifi_scan_pending {
+        if self.jobs.is_running(JobKind::WifiScan) {
             self.set_toast(ToastKind::Info, "Wi-Fi scan already running");
             return Ok(());
         }
@@ -496,19 +923,27 @@ impl App {
             );
             return Ok(());
         }
+        let Some(iface) = self.wifi_iface_or_toast() else {
+            return Ok(());
+        };
 
-        self.wifi_scan_pending = true;
-        self.wifi_scan_started_at = Some(now);
         self.last_scan_request_at = Some(now);
         self.request_refresh();
         self.last_action = Some("Wi-Fi scan requested".to_string());
         self.set_toast(ToastKind::Info, "Wi-Fi scan requested...");
-        self.wifi_scan_task = Some(tokio::spawn(async { IwdBackend::new().scan().await }));
+        let backend = Arc::clone(&self.wifi_backend);
+        self.jobs.spawn(
+            JobKind::WifiScan,
+            format!("Scanning on {iface}"),
+            Duration::from_millis(self.config.job_timeout_scan_ms),
+            async move { backend.scan(&iface).await },
+            App::finish_wifi_scan,
+        );
         Ok(())
     }
 
     pub async fn wifi_connect_or_disconnect(&mut self) -> Result<()> {
-        if self.wifi_connect_pending {
+        if self.wifi_connect_blocked() {
             self.set_toast(
                 ToastKind::Info,
                 "Wi-Fi connect/disconnect already in progress",
@@ -529,17 +964,17 @@ impl App {
             return Ok(());
         }
 
+        let Some(iface) = self.wifi_iface_or_toast() else {
+            return Ok(());
+        };
         let ssid = net.ssid.clone();
         let disconnect = net.connected;
+        if !disconnect && self.wifi_focus == WifiFocus::NewNetworks && net.security == "8021x" {
+            self.open_enterprise_form(ssid);
+            return Ok(());
+        }
 
-        self.wifi_connect_pending = true;
-        self.wifi_connect_started_at = Some(Instant::now());
         self.request_refresh();
-        self.wifi_connect_context = Some(WifiConnectContext {
-            ssid: ssid.clone(),
-            disconnect,
-            used_passphrase: false,
-        });
         self.last_action = Some(if disconnect {
             "Disconnecting Wi-Fi...".to_string()
         } else {
@@ -554,250 +989,612 @@ impl App {
             },
         );
 
-        self.wifi_connect_task = Some(tokio::spawn(async move {
+        let backend = Arc::clone(&self.wifi_backend);
+        let context = WifiConnectContext {
+            iface: iface.clone(),
+            ssid: ssid.clone(),
+            disconnect,
+            used_passphrase: false,
+        };
+        self.spawn_wifi_connect(context, async move {
             if disconnect {
-                IwdBackend::new().disconnect().await
+                backend.disconnect(&iface).await
             } else {
-                IwdBackend::new().connect(&ssid).await
+                backend.connect(&iface, &ssid).await
             }
-        }));
+        });
 
         Ok(())
     }
 
-    pub async fn wifi_forget_selected(&mut self) -> Result<()> {
+    pub fn wifi_forget_selected(&mut self) {
         if self.wifi_focus != WifiFocus::KnownNetworks {
             self.set_toast(ToastKind::Info, "Forget is available in Known Networks");
-            return Ok(());
+            return;
         }
 
         let Some(net) = self.selected_known_network().cloned() else {
             self.set_toast(ToastKind::Error, "No known network selected");
-            return Ok(());
+            return;
         };
 
-        match self.wifi_backend.forget_known(&net.ssid).await {
-            Ok(()) => {
-                self.last_action = Some(format!("Forgot network {}", net.ssid));
-                self.set_toast(ToastKind::Success, format!("Forgot network {}", net.ssid));
-                self.notify("Wi-Fi", &format!("Forgot network {}", net.ssid));
-                self.request_refresh();
-            }
-            Err(e) => {
-                let msg = friendly_wifi_error("forget known network", &e);
-                self.set_toast(ToastKind::Error, msg);
-            }
+        let Some(iface) = self.wifi_iface_or_toast() else {
+            return;
+        };
+        if let Some(job) = self.jobs.get(JobKind::WifiProfile) {
+            let msg = format!("Wait for \"{}\" to finish", job.label);
+            self.set_toast(ToastKind::Info, msg);
+            return;
         }
-        Ok(())
+
+        let backend = Arc::clone(&self.wifi_backend);
+        let ssid = net.ssid.clone();
+        self.jobs.spawn(
+            JobKind::WifiProfile,
+            format!("Forgetting {}", net.ssid),
+            Duration::from_millis(self.config.job_timeout_profile_ms),
+            async move { backend.forget_known(&iface, &ssid).await },
+            move |app: &mut App, outcome| match outcome {
+                JobOutcome::Finished(Ok(())) => {
+                    app.last_action = Some(format!("Forgot network {}", net.ssid));
+                    app.set_toast(ToastKind::Success, format!("Forgot network {}", net.ssid));
+                    app.notify("Wi-Fi", &format!("Forgot network {}", net.ssid));
+                    app.request_refresh();
+                }
+                JobOutcome::Finished(Err(e)) => {
+                    let msg = friendly_error("forget known network", &e);
+                    app.set_toast(ToastKind::Error, msg);
+                }
+                JobOutcome::TimedOut => {
+                    let msg = format!("Forgetting {} timed out", net.ssid);
+                    app.set_toast(ToastKind::Error, msg);
+                }
+                JobOutcome::Canceled => {}
+            },
+        );
     }
 
-    pub async fn wifi_toggle_autoconnect_selected(&mut self) -> Result<()> {
+    pub fn wifi_toggle_autoconnect_selected(&mut self) {
         if self.wifi_focus != WifiFocus::KnownNetworks {
             self.set_toast(
                 ToastKind::Info,
                 "Autoconnect toggle is available in Known Networks",
             );
-            return Ok(());
+            return;
         }
 
         let Some(net) = self.selected_known_network().cloned() else {
             self.set_toast(ToastKind::Error, "No known network selected");
-            return Ok(());
+            return;
+        };
+
+        let Some(iface) = self.wifi_iface_or_toast() else {
+            return;
+        };
+        if let Some(job) = self.jobs.get(JobKind::WifiProfile) {
+            let msg = format!("Wait for \"{}\" to finish", job.label);
+            self.set_toast(ToastKind::Info, msg);
+            return;
+        }
+
+        let backend = Arc::clone(&self.wifi_backend);
+        let ssid = net.ssid.clone();
+        self.jobs.spawn(
+            JobKind::WifiProfile,
+            format!("Toggling autoconnect for {}", net.ssid),
+            Duration::from_millis(self.config.job_timeout_profile_ms),
+            async move { backend.toggle_autoconnect(&iface, &ssid).await },
+            move |app: &mut App, outcome| match outcome {
+                JobOutcome::Finished(Ok(enabled)) => {
+                    let state = if enabled { "enabled" } else { "disabled" };
+                    app.last_action = Some(format!("Autoconnect {} for {}", state, net.ssid));
+                    app.set_toast(
+                        ToastKind::Success,
+                        format!("Autoconnect {} for {}", state, net.ssid),
+                    );
+                    app.notify("Wi-Fi", &format!("Autoconnect {}: {}", state, net.ssid));
+                    app.request_refresh();
+                }
+                JobOutcome::Finished(Err(e)) => {
+                    let msg = friendly_error("toggle autoconnect", &e);
+                    app.set_toast(ToastKind::Error, msg);
+                }
+                JobOutcome::TimedOut => {
+                    let msg = format!("Toggling autoconnect for {} timed out", net.ssid);
+                    app.set_toast(ToastKind::Error, msg);
+                }
+                JobOutcome::Canceled => {}
+            },
+        );
+    }
+
+    pub fn wifi_edit_selected_profile(&mut self) {
+        if self.wifi_focus != WifiFocus::KnownNetworks {
+            self.set_toast(
+                ToastKind::Info,
+                "Profile editing is available in Known Networks",
+            );
+            return;
+        }
+        let Some(net) = self.selected_known_network().cloned() else {
+            self.set_toast(ToastKind::Error, "No known network selected");
+            return;
+        };
+        if let Some(job) = self.jobs.get(JobKind::WifiProfile) {
+            let msg = format!("Wait for \"{}\" to finish", job.label);
+            self.set_toast(ToastKind::Info, msg);
+            return;
+        }
+
+        let backend = Arc::clone(&self.wifi_backend);
+        let (ssid, security) = (net.ssid.clone(), net.security.clone());
+        self.jobs.spawn(
+            JobKind::WifiProfile,
+            format!("Loading profile for {}", net.ssid),
+            Duration::from_millis(self.config.job_timeout_profile_ms),
+            async move { backend.read_known_profile(&ssid, &security).await },
+            move |app: &mut App, outcome| match outcome {
+                JobOutcome::Finished(Ok(profile)) => {
+                    app.profile_form = Some(ProfileForm::new(net.ssid, net.security, profile));
+                }
+                JobOutcome::Finished(Err(e)) => {
+                    let msg = friendly_error("read network profile", &e);
+                    app.set_toast(ToastKind::Error, msg);
+                }
+                JobOutcome::TimedOut => {
+                    let msg = format!("Loading profile for {} timed out", net.ssid);
+                    app.set_toast(ToastKind::Error, msg);
+                }
+                JobOutcome::Canceled => {}
+            },
+        );
+    }
+
+    pub fn close_profile_form(&mut self) {
+        self.profile_form = None;
+    }
+
+    pub fn submit_profile_form(&mut self) {
+        let Some(form) = self.profile_form.clone() else {
+            return;
         };
+        if let Err(e) = form.profile.validate() {
+            self.set_toast(ToastKind::Error, e.to_string());
+            return;
+        }
+        if let Some(job) = self.jobs.get(JobKind::WifiProfile) {
+            let msg = format!("Wait for \"{}\" to finish", job.label);
+            self.set_toast(ToastKind::Info, msg);
+            return;
+        }
 
-        match self.wifi_backend.toggle_autoconnect(&net.ssid).await {
-            Ok(enabled) => {
-                let state = if enabled { "enabled" } else { "disabled" };
-                self.last_action = Some(format!("Autoconnect {} for {}", state, net.ssid));
+        self.close_profile_form();
+        let label = format!("Saving profile for {}", form.ssid);
+        self.set_toast(ToastKind::Info, format!("{label}..."));
+        let backend = Arc::clone(&self.wifi_backend);
+        let (ssid, security, profile) = (
+            form.ssid.clone(),
+            form.security.clone(),
+            form.profile.clone(),
+        );
+        self.jobs.spawn(
+            JobKind::WifiProfile,
+            label,
+            Duration::from_millis(self.config.job_timeout_profile_ms),
+            async move {
+                backend
+                    .write_known_profile(&ssid, &security, &profile)
+                    .await
+            },
+            move |app: &mut App, outcome| app.finish_profile_save(form, outcome),
+        );
+    }
+
+    fn finish_profile_save(&mut self, form: ProfileForm, outcome: JobOutcome<CommandResult>) {
+        match outcome {
+            JobOutcome::Finished(Ok(out)) if out.dry_run => {
+                let msg = format!("Dry run: {}", out.dry_run_summary());
+                self.set_toast(ToastKind::Info, msg);
+            }
+            JobOutcome::Finished(Ok(_)) => {
+                self.last_action = Some(format!("Saved profile for {}", form.ssid));
                 self.set_toast(
                     ToastKind::Success,
-                    format!("Autoconnect {} for {}", state, net.ssid),
+                    format!("Saved profile for {}", form.ssid),
                 );
-                self.notify("Wi-Fi", &format!("Autoconnect {}: {}", state, net.ssid));
                 self.request_refresh();
             }
-            Err(e) => {
-                let msg = friendly_wifi_error("toggle autoconnect", &e);
+            JobOutcome::Finished(Err(e)) => {
+                let msg = friendly_error("save network profile", &e);
                 self.set_toast(ToastKind::Error, msg);
+                self.profile_form = Some(form);
             }
+            JobOutcome::TimedOut => {
+                let msg = format!("Saving profile for {} timed out", form.ssid);
+                self.set_toast(ToastKind::Error, msg);
+                self.profile_form = Some(form);
+            }
+            JobOutcome::Canceled => {}
         }
-        Ok(())
     }
 
-    pub async fn ethernet_renew_dhcp(&mut self) -> Result<()> {
-        let iface = self
-            .selected_eth_iface()
-            .map(|i| i.name.clone())
-            .ok_or_else(|| std::io::Error::other("no ethernet interface selected"))?;
-
-        let before = snapshot_eth(self.selected_eth_iface());
-        let out = self.eth_backend.renew_dhcp(&iface).await?;
-        self.refresh_all().await;
-        let after = snapshot_eth(self.selected_eth_iface());
-
-        let mut msg = format!("{iface}: DHCP renew requested");
-        if out.used_sudo {
-            msg.push_str(" (elevated)");
+    pub fn wifi_share_network(&mut self) {
+        let target = match self.wifi_focus {
+            WifiFocus::KnownNetworks => self.selected_known_network(),
+            _ => None,
         }
-        if !out.stdout.is_empty() {
-            msg.push_str(&format!("\nstdout: {}", out.stdout));
+        .or_else(|| self.wifi.known_networks.iter().find(|n| n.connected))
+        .cloned();
+        let Some(net) = target else {
+            self.set_toast(
+                ToastKind::Error,
+                "Select a known network or connect to one first",
+            );
+            return;
+        };
+        if net.security.eq_ignore_ascii_case("8021x") {
+            self.set_toast(
+                ToastKind::Info,
+                "802.1X networks cannot be shared as a QR code",
+            );
+            return;
         }
-        if !out.stderr.is_empty() {
-            msg.push_str(&format!("\nstderr: {}", out.stderr));
+        if net.security.eq_ignore_ascii_case("open") {
+            self.show_wifi_share(&net, None);
+            return;
         }
-        if before == after {
-            msg.push_str("\nNo visible change detected.");
+        if let Some(job) = self.jobs.get(JobKind::WifiProfile) {
+            let msg = format!("Wait for \"{}\" to finish", job.label);
+            self.set_toast(ToastKind::Info, msg);
+            return;
         }
-        msg.push_str(&format!("\nBefore: {before}\nAfter:  {after}"));
 
-        self.last_action = Some(format!("Renewed DHCP on {iface}"));
-        self.set_toast(ToastKind::Success, msg);
-        self.notify("Ethernet", &format!("DHCP renew requested on {iface}"));
-        Ok(())
+        let backend = Arc::clone(&self.wifi_backend);
+        let (ssid, security) = (net.ssid.clone(), net.security.clone());
+        self.jobs.spawn(
+            JobKind::WifiProfile,
+            format!("Reading passphrase for {}", net.ssid),
+            Duration::from_millis(self.config.job_timeout_profile_ms),
+            async move { backend.read_passphrase(&ssid, &security).await },
+            move |app: &mut App, outcome| match outcome {
+                JobOutcome::Finished(Ok(passphrase)) => app.show_wifi_share(&net, passphrase),
+                JobOutcome::Finished(Err(e)) => {
+                    let msg = friendly_error("read passphrase", &e);
+                    app.set_toast(ToastKind::Error, msg);
+                }
+                JobOutcome::TimedOut => {
+                    let msg = format!("Reading passphrase for {} timed out", net.ssid);
+                    app.set_toast(ToastKind::Error, msg);
+                }
+                JobOutcome::Canceled => {}
+            },
+        );
+    }
+
+    fn show_wifi_share(&mut self, net: &WifiNetwork, passphrase: Option<String>) {
+        let hidden = net.hidden == Some(true);
+        match WifiShare::new(&net.ssid, &net.security, hidden, passphrase) {
+            Ok(share) => self.wifi_share = Some(share),
+            Err(e) => self.set_toast(ToastKind::Error, e.to_string()),
+        }
+    }
+
+    pub fn close_wifi_share(&mut self) {
+        self.wifi_share = None;
     }
 
-    pub async fn ethernet_toggle_link(&mut self) -> Result<()> {
+    pub fn ethernet_renew_dhcp(&mut self) -> Result<()> {
+        self.spawn_ethernet_action(EthernetAction::RenewDhcp)
+    }
+
+    pub fn ethernet_toggle_link(&mut self) -> Result<()> {
+        let up = self
+            .selected_eth_iface()
+            .is_some_and(|i| !(i.operstate == "up" || i.carrier == Some(true)));
+        self.spawn_ethernet_action(EthernetAction::SetLink(up))
+    }
+
+    pub fn ethernet_reconfigure(&mut self) -> Result<()> {
+        self.spawn_ethernet_action(EthernetAction::Reconfigure)
+    }
+
+    pub fn ethernet_force_renew(&mut self) -> Result<()> {
+        self.spawn_ethernet_action(EthernetAction::ForceRenew)
+    }
+
+    fn spawn_ethernet_action(&mut self, action: EthernetAction) -> Result<()> {
         let iface = self
             .selected_eth_iface()
-            .cloned()
+            .map(|i| i.name.clone())
             .ok_or_else(|| std::io::Error::other("no ethernet interface selected"))?;
-        let target_up = !(iface.operstate == "up" || iface.carrier == Some(true));
-        let state_word = if target_up { "up" } else { "down" };
+        if let Some(job) = self.jobs.get(JobKind::Ethernet) {
+            let msg = format!("Wait for \"{}\" to finish", job.label);
+            self.set_toast(ToastKind::Info, msg);
+            return Ok(());
+        }
+
+        let before = snapshot_eth(self.selected_eth_iface());
+        let backend = Arc::clone(&self.eth_backend);
+        let name = iface.clone();
+        self.jobs.spawn(
+            JobKind::Ethernet,
+            action.label(&iface),
+            Duration::from_millis(self.config.job_timeout_ethernet_ms),
+            async move {
+                let out = match action {
+                    EthernetAction::RenewDhcp => backend.renew_dhcp(&name).await,
+                    EthernetAction::SetLink(up) => backend.set_link_admin_state(&name, up).await,
+                    EthernetAction::Reconfigure => backend.reconfigure(&name).await,
+                    EthernetAction::ForceRenew => backend.force_renew(&name).await,
+                }?;
+                let after = backend.iface_details(&name).await.ok();
+                Ok((out, after))
+            },
+            move |app: &mut App, outcome| {
+                app.finish_ethernet_action(&iface, action, &before, outcome)
+            },
+        );
+        Ok(())
+    }
+
+    fn finish_ethernet_action(
+        &mut self,
+        iface: &str,
+        action: EthernetAction,
+        before: &str,
+        outcome: JobOutcome<(CommandResult, Option<EthernetIface>)>,
+    ) {
+        self.request_refresh();
+        let (out, after) = match outcome {
+            JobOutcome::Finished(Ok(done)) => done,
+            JobOutcome::Finished(Err(e)) => {
+                self.last_error = Some(friendly_error(&action.label(iface), &e));
+                return;
+            }
+            JobOutcome::TimedOut => {
+                let msg = format!("{} timed out", action.label(iface));
+                self.set_toast(ToastKind::Error, msg);
+                return;
+            }
+            JobOutcome::Canceled => return,
+        };
+        if out.dry_run {
+            self.set_toast(
+                ToastKind::Info,
+                format!("Dry run: {}", out.dry_run_summary()),
+            );
+            return;
+        }
+
+        let after = snapshot_eth(after.as_ref());
+        match action {
+            EthernetAction::RenewDhcp => {
+                let mut msg = format!("{iface}: DHCP renew requested");
+                if out.escalation != Escalation::None {
+                    msg.push_str(&format!(" (via {})", out.escalation.label()));
+                }
+                if !out.stdout.is_empty() {
+                    msg.push_str(&format!("\nstdout: {}", out.stdout));
+                }
+                if !out.stderr.is_empty() {
+                    msg.push_str(&format!("\nstderr: {}", out.stderr));
+                }
+                push_eth_diff(&mut msg, before, &after);
 
-        let out = self
-            .eth_backend
-            .set_link_admin_state(&iface.name, target_up)
-            .await?;
-        self.refresh_all().await;
+                self.last_action = Some(format!("Renewed DHCP on {iface}"));
+                self.set_toast(ToastKind::Success, msg);
+                self.notify("Ethernet", &format!("DHCP renew requested on {iface}"));
+            }
+            EthernetAction::SetLink(up) => {
+                let state_word = if up { "up" } else { "down" };
+                let mut msg = format!("{iface}: link set {state_word}");
+                if out.escalation != Escalation::None {
+                    msg.push_str(&format!(" (via {})", out.escalation.label()));
+                }
+                if !out.stderr.is_empty() {
+                    msg.push_str(&format!("\nstderr: {}", out.stderr));
+                }
+                push_eth_diff(&mut msg, before, &after);
 
-        let mut msg = format!("{}: link set {}", iface.name, state_word);
-        if out.used_sudo {
-            msg.push_str(" (elevated)");
+                self.last_action = Some(format!("{iface} link {state_word}"));
+                self.set_toast(ToastKind::Success, msg);
+                self.notify("Ethernet", &format!("{iface} link {state_word}"));
+            }
+            EthernetAction::Reconfigure => self.report_ethernet_action(iface, "reconfigure", out),
+            EthernetAction::ForceRenew => {
+                self.report_ethernet_action(iface, "DHCP force renew", out)
+            }
+        }
+    }
+
+    fn report_ethernet_action(&mut self, iface: &str, action: &str, out: CommandResult) {
+        let mut msg = format!("{iface}: {action} requested");
+        if out.escalation != Escalation::None {
+            msg.push_str(&format!(" (via {})", out.escalation.label()));
         }
         if !out.stderr.is_empty() {
             msg.push_str(&format!("\nstderr: {}", out.stderr));
         }
-        self.last_action = Some(format!("{} link {}", iface.name, state_word));
+        self.last_action = Some(format!("{iface}: {action} requested"));
         self.set_toast(ToastKind::Success, msg);
-        self.notify("Ethernet", &format!("{} link {}", iface.name, state_word));
-        Ok(())
+        self.notify("Ethernet", &format!("{action} requested on {iface}"));
+    }
+
+    pub fn wifi_use_selected_adapter(&mut self) {
+        let Some(iface) = self.selected_adapter_iface() else {
+            self.set_toast(ToastKind::Error, "No adapter selected");
+            return;
+        };
+        if self.wifi.selected_iface.as_deref() == Some(iface.as_str()) {
+            self.set_toast(ToastKind::Info, format!("Already using {iface}"));
+            return;
+        }
+        if self.wifi_scanning_active() || self.wifi_connect_blocked() {
+            self.set_toast(
+                ToastKind::Info,
+                "Wait for the running Wi-Fi operation to finish",
+            );
+            return;
+        }
+
+        self.wifi.selected_iface = Some(iface.clone());
+        self.last_action = Some(format!("Using adapter {iface}"));
+        self.set_toast(ToastKind::Info, format!("Using adapter {iface}"));
+        self.request_refresh();
+    }
+
+    pub fn wifi_service_name(&self) -> &'static str {
+        self.backend_kind.wifi_service()
     }
 
     pub fn wifi_scanning_active(&self) -> bool {
-        self.wifi_scan_pending
+        self.jobs.is_running(JobKind::WifiScan)
     }
 
     pub fn wifi_connect_active(&self) -> bool {
-        self.wifi_connect_pending
+        self.jobs.is_running(JobKind::WifiConnect)
     }
 
-    async fn poll_background_tasks(&mut self) {
-        let now = Instant::now();
-        if self.wifi_scan_pending
-            && self.wifi_scan_started_at.is_some_and(|started| {
-                now.duration_since(started) > Duration::from_millis(self.config.job_timeout_scan_ms)
-            })
-        {
-            if let Some(handle) = self.wifi_scan_task.take() {
-                handle.abort();
-            }
-            self.wifi_scan_pending = false;
-            self.wifi_scan_started_at = None;
-            self.set_toast(ToastKind::Error, "Wi-Fi scan timed out");
+    fn wifi_connect_blocked(&self) -> bool {
+        self.wifi_connect_active() || self.jobs.is_running(JobKind::WifiAbort)
+    }
+
+    pub fn cancel_wifi_job(&mut self) {
+        let done = match self.jobs.cancel(JobKind::WifiConnect) {
+            Some(done) => Some(done),
+            None => self.jobs.cancel(JobKind::WifiScan),
+        };
+        match done {
+            Some(done) => done(self),
+            None => self.set_toast(ToastKind::Info, "No Wi-Fi operation to cancel"),
         }
+    }
 
-        if self.wifi_connect_pending
-            && self.wifi_connect_started_at.is_some_and(|started| {
-                now.duration_since(started)
-                    > Duration::from_millis(self.config.job_timeout_connect_ms)
-            })
-        {
-            if let Some(handle) = self.wifi_connect_task.take() {
-                handle.abort();
+    fn poll_background_tasks(&mut self) {
+        let awaiting_secret = self.wifi_secret_request.is_some();
+        let completions = self.jobs.poll(Instant::now(), |job| {
+            awaiting_secret && job.kind == JobKind::WifiConnect
+        });
+        for done in completions {
+            done(self);
+        }
+    }
+
+    fn spawn_wifi_connect(
+        &mut self,
+        ctx: WifiConnectContext,
+        job: impl Future<Output = Result<()>> + Send + 'static,
+    ) {
+        let label = if ctx.disconnect {
+            format!("Disconnecting from {}", ctx.ssid)
+        } else {
+            format!("Connecting to {}", ctx.ssid)
+        };
+        self.jobs.spawn(
+            JobKind::WifiConnect,
+            label,
+            Duration::from_millis(self.config.job_timeout_connect_ms),
+            job,
+            move |app: &mut App, outcome| app.finish_wifi_connect(ctx, outcome),
+        );
+    }
+
+    fn finish_wifi_scan(&mut self, outcome: JobOutcome<()>) {
+        match outcome {
+            JobOutcome::Finished(Ok(())) => {
+                self.last_action = Some("Wi-Fi scan completed".to_string());
+                self.set_toast(ToastKind::Success, "Wi-Fi scan completed");
+                self.notify("Wi-Fi", "Scan completed");
+                self.request_refresh();
             }
-            self.wifi_connect_pending = false;
-            self.wifi_connect_started_at = None;
-            self.wifi_connect_context = None;
-            self.set_toast(ToastKind::Error, "Wi-Fi connect/disconnect timed out");
-        }
-
-        if let Some(handle) = self.wifi_scan_task.take() {
-            if handle.is_finished() {
-                self.wifi_scan_pending = false;
-                self.wifi_scan_started_at = None;
-                match handle.await {
-                    Ok(Ok(())) => {
-                        self.last_action = Some("Wi-Fi scan completed".to_string());
-                        self.set_toast(ToastKind::Success, "Wi-Fi scan completed");
-                        self.notify("Wi-Fi", "Scan completed");
-                        self.request_refresh();
-                    }
-                    Ok(Err(e)) => {
-                        let msg = friendly_wifi_error("scan", &e);
-                        self.set_toast(ToastKind::Error, msg);
-                    }
-                    Err(e) => {
-                        self.set_toast(ToastKind::Error, format!("scan task failed: {e}"));
-                    }
-                }
-            } else {
-                self.wifi_scan_task = Some(handle);
+            JobOutcome::Finished(Err(e)) => {
+                let msg = friendly_error("scan", &e);
+                self.set_toast(ToastKind::Error, msg);
             }
+            JobOutcome::TimedOut => self.set_toast(ToastKind::Error, "Wi-Fi scan timed out"),
+            JobOutcome::Canceled => self.set_toast(ToastKind::Info, "Wi-Fi scan canceled"),
         }
+    }
 
-        if let Some(handle) = self.wifi_connect_task.take() {
-            if handle.is_finished() {
-                self.wifi_connect_pending = false;
-                self.wifi_connect_started_at = None;
-                let ctx = self.wifi_connect_context.take();
-                match handle.await {
-                    Ok(Ok(())) => {
-                        if let Some(ctx) = ctx {
-                            if ctx.disconnect {
-                                self.last_action = Some("Disconnected Wi-Fi".to_string());
-                                self.set_toast(
-                                    ToastKind::Success,
-                                    format!("Disconnected from {}", ctx.ssid),
-                                );
-                                self.notify("Wi-Fi", &format!("Disconnected from {}", ctx.ssid));
-                            } else {
-                                self.last_action = Some(format!("Connected to {}", ctx.ssid));
-                                self.set_toast(
-                                    ToastKind::Success,
-                                    format!("Connected to {}", ctx.ssid),
-                                );
-                                self.notify("Wi-Fi", &format!("Connected to {}", ctx.ssid));
-                            }
-                            self.request_refresh();
-                        }
-                    }
-                    Ok(Err(e)) => {
-                        let no_agent = is_no_agent_error(&e);
-                        if let Some(ctx) = ctx
-                            && no_agent
-                            && !ctx.disconnect
-                            && !ctx.used_passphrase
-                        {
-                            self.open_wifi_passphrase_prompt(ctx.ssid.clone());
-                            self.set_toast(
-                                ToastKind::Info,
-                                format!("Passphrase required for {}", ctx.ssid),
-                            );
-                        } else {
-                            let msg = friendly_wifi_error("connect/disconnect", &e);
-                            self.set_toast(ToastKind::Error, msg);
-                        }
-                    }
-                    Err(e) => {
-                        self.set_toast(ToastKind::Error, format!("connect task failed: {e}"));
+    fn finish_wifi_connect(&mut self, ctx: WifiConnectContext, outcome: JobOutcome<()>) {
+        match outcome {
+            JobOutcome::Finished(Ok(())) => {
+                if ctx.disconnect {
+                    self.last_action = Some("Disconnected Wi-Fi".to_string());
+                    self.set_toast(
+                        ToastKind::Success,
+                        format!("Disconnected from {}", ctx.ssid),
+                    );
+                    self.notify("Wi-Fi", &format!("Disconnected from {}", ctx.ssid));
+                } else {
+                    self.last_action = Some(format!("Connected to {}", ctx.ssid));
+                    self.set_toast(ToastKind::Success, format!("Connected to {}", ctx.ssid));
+                    self.notify("Wi-Fi", &format!("Connected to {}", ctx.ssid));
+                }
+                self.request_refresh();
+            }
+            JobOutcome::Finished(Err(e)) => {
+                if !ctx.disconnect
+                    && NettuiError::of(&e).is_some_and(|err| err.prompts_again(ctx.used_passphrase))
+                {
+                    self.open_wifi_passphrase_prompt(ctx.ssid.clone());
+                    if ctx.used_passphrase {
+                        self.set_toast(ToastKind::Error, friendly_error("connect", &e));
+                    } else {
+                        self.set_toast(
+                            ToastKind::Info,
+                            format!("Passphrase required for {}", ctx.ssid),
+                        );
                     }
+                } else {
+                    let msg = friendly_error("connect/disconnect", &e);
+                    self.set_toast(ToastKind::Error, msg);
                 }
-            } else {
-                self.wifi_connect_task = Some(handle);
             }
+            JobOutcome::TimedOut => {
+                self.set_toast(ToastKind::Error, "Wi-Fi connect/disconnect timed out")
+            }
+            JobOutcome::Canceled => self.abort_wifi_connect(ctx),
         }
     }
 
+    fn abort_wifi_connect(&mut self, ctx: WifiConnectContext) {
+        if self.wifi_secret_request.is_some() {
+            self.close_wifi_passphrase_prompt();
+        }
+        self.request_refresh();
+        if ctx.disconnect {
+            self.last_action = Some("Disconnect canceled".to_string());
+            self.set_toast(ToastKind::Info, "Disconnect canceled");
+            return;
+        }
+
+        self.last_action = Some(format!("Canceled connecting to {}", ctx.ssid));
+        self.set_toast(
+            ToastKind::Info,
+            format!("Canceled connecting to {}", ctx.ssid),
+        );
+        let backend = Arc::clone(&self.wifi_backend);
+        let iface = ctx.iface;
+        self.jobs.spawn(
+            JobKind::WifiAbort,
+            format!("Aborting connection to {}", ctx.ssid),
+            Duration::from_millis(self.config.job_timeout_connect_ms),
+            async move { backend.cancel_connect(&iface).await },
+            |app: &mut App, outcome| match outcome {
+                JobOutcome::Finished(Ok(())) => app.request_refresh(),
+                JobOutcome::Finished(Err(e)) => {
+                    let msg = friendly_error("cancel connect", &e);
+                    app.set_toast(ToastKind::Error, msg);
+                }
+                JobOutcome::TimedOut => {
+                    app.set_toast(ToastKind::Error, "Aborting Wi-Fi connect timed out")
+                }
+                JobOutcome::Canceled => {}
+            },
+        );
+    }
+
     fn known_total_len(&self) -> usize {
         self.wifi.known_networks.len()
             + if self.show_unavailable_known_networks {
@@ -817,7 +1614,20 @@ impl App {
     }
 
     fn device_total_len(&self) -> usize {
-        usize::from(self.wifi.device.is_some())
+        self.wifi.adapters.len()
+    }
+
+    fn selected_adapter_iface(&self) -> Option<String> {
+        let idx = self.wifi_adapter_state.selected()?;
+        self.wifi.adapters.get(idx).map(|a| a.iface.clone())
+    }
+
+    fn wifi_iface_or_toast(&mut self) -> Option<String> {
+        let iface = self.wifi.selected_iface.clone();
+        if iface.is_none() {
+            self.set_toast(ToastKind::Error, "No Wi-Fi adapter found");
+        }
+        iface
     }
 
     fn selected_known_network(&self) -> Option<&WifiNetwork> {
@@ -845,6 +1655,7 @@ impl App {
     }
 
     fn init_wifi_states(&mut self) {
+        self.sort_known_networks();
         let known_len = self.known_total_len();
         let new_len = self.new_total_len();
         let device_len = self.device_total_len();
@@ -866,7 +1677,12 @@ impl App {
         self.selected_new_network().map(|n| n.ssid.clone())
     }
 
-    fn restore_wifi_selection(&mut self, known_ssid: Option<String>, new_ssid: Option<String>) {
+    fn restore_wifi_selection(
+        &mut self,
+        known_ssid: Option<String>,
+        new_ssid: Option<String>,
+        adapter: Option<String>,
+    ) {
         if let Some(ssid) = known_ssid {
             if let Some(idx) = self.wifi.known_networks.iter().position(|n| n.ssid == ssid) {
                 self.wifi_known_state.select(Some(idx));
@@ -917,8 +1733,14 @@ impl App {
             select_first_if_any(&mut self.wifi_new_state, len);
         }
 
-        let len = self.device_total_len();
-        select_first_if_any(&mut self.wifi_adapter_state, len);
+        if let Some(iface) = adapter
+            && let Some(idx) = self.wifi.adapters.iter().position(|a| a.iface == iface)
+        {
+            self.wifi_adapter_state.select(Some(idx));
+        } else {
+            let len = self.device_total_len();
+            select_first_if_any(&mut self.wifi_adapter_state, len);
+        }
     }
 
     fn restore_ethernet_selection(&mut self, selected_iface: Option<String>) {
@@ -976,7 +1798,11 @@ pub fn determine_start_tab(
     }
 }
 
-async fn detect_conflicting_wifi_services() -> Option<String> {
+async fn detect_conflicting_wifi_services(kind: BackendKind) -> Option<String> {
+    if kind != BackendKind::Iwd {
+        return None;
+    }
+
     let mut active = Vec::new();
     if is_service_active("NetworkManager.service").await {
         active.push("NetworkManager");
@@ -994,19 +1820,6 @@ async fn detect_conflicting_wifi_services() -> Option<String> {
     }
 }
 
-async fn is_service_active(unit: &str) -> bool {
-    match Command::new("systemctl")
-        .arg("is-active")
-        .arg("--quiet")
-        .arg(unit)
-        .status()
-        .await
-    {
-        Ok(status) => status.success(),
-        Err(_) => false,
-    }
-}
-
 fn refresh_due(last_refresh: Instant, interval_ms: u64, now: Instant) -> bool {
     now.duration_since(last_refresh) >= Duration::from_millis(interval_ms)
 }
@@ -1029,32 +1842,18 @@ fn scan_debounce_remaining(
     }
 }
 
-fn friendly_wifi_error(action: &str, err: &anyhow::Error) -> String {
-    let msg = err.to_string();
-    let lower = msg.to_lowercase();
-    if lower.contains("no agent registered") {
-        return format!(
-            "{} needs Wi-Fi credentials. Use connect again and enter passphrase.",
-            action
-        );
-    }
-    if lower.contains("accessdenied")
-        || lower.contains("permission denied")
-        || lower.contains("not authorized")
-        || lower.contains("operation not permitted")
-    {
-        return format!(
-            "{} requires elevated permissions. Run with proper privileges and retry.",
-            action
-        );
-    }
-    msg
+fn service_unavailable(err: &anyhow::Error) -> bool {
+    matches!(
+        NettuiError::of(err),
+        Some(NettuiError::ServiceUnavailable(_))
+    )
 }
 
-fn is_no_agent_error(err: &anyhow::Error) -> bool {
-    err.to_string()
-        .to_lowercase()
-        .contains("no agent registered")
+fn friendly_error(action: &str, err: &anyhow::Error) -> String {
+    match NettuiError::of(err) {
+        Some(typed) => typed.user_message(action),
+        None => err.to_string(),
+    }
 }
 
 fn snapshot_eth(iface: Option<&EthernetIface>) -> String {
@@ -1077,6 +1876,13 @@ fn snapshot_eth(iface: Option<&EthernetIface>) -> String {
     )
 }
 
+fn push_eth_diff(msg: &mut String, before: &str, after: &str) {
+    if before == after {
+        msg.push_str("\nNo visible change detected.");
+    }
+    msg.push_str(&format!("\nBefore: {before}\nAfter:  {after}"));
+}
+
 fn select_first_if_any(state: &mut TableState, len: usize) {
     if len == 0 {
         state.select(None);
@@ -1131,19 +1937,26 @@ mod tests {
             unavailable_known_networks: vec![],
             new_networks: vec![],
             hidden_networks: vec![],
-            device: None,
+            selected_iface: None,
+            adapters: vec![],
         };
         let ethernet = EthernetState {
             ifaces: vec![EthernetIface {
                 name: "enp1s0".to_string(),
+                ifindex: 2,
                 operstate: "up".to_string(),
                 carrier: Some(true),
                 mac: None,
                 speed_mbps: None,
+                mtu: Some(1500),
+                flags: vec![],
                 ipv4: vec!["192.168.1.2/24".to_string()],
                 ipv6: vec![],
                 gateway_v4: None,
+                gateway_v6: None,
+                routes: vec![],
                 dns: vec![],
+                managed: None,
             }],
         };
 
@@ -1162,7 +1975,8 @@ mod tests {
             unavailable_known_networks: vec![],
             new_networks: vec![],
             hidden_networks: vec![],
-            device: None,
+            selected_iface: None,
+            adapters: vec![],
         };
         let ethernet = EthernetState { ifaces: vec![] };
 
@@ -0,0 +1,701 @@
+use crate::{
+    backend::{
+        privilege::Escalation,
+        traits::{CommandResult, EthernetBackend, WifiBackend},
+    },
+    domain::{
+        enterprise::EnterpriseConfig,
+        ethernet::{EthernetChange, EthernetIface, EthernetRoute, ManagedLinkState},
+        profile::KnownProfile,
+        wifi::{WifiDeviceInfo, WifiNetwork, WifiState, pick_iface, signal_label},
+    },
+    error::NettuiError,
+    event::{BackendEvent, Event},
+};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::{
+    fs,
+    path::Path,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, SystemTime},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+const SCAN_DELAY: Duration = Duration::from_millis(800);
+const CONNECT_DELAY: Duration = Duration::from_millis(600);
+const LINK_DELAY: Duration = Duration::from_millis(400);
+const TIMEOUT_DELAY: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Fixture {
+    #[serde(default)]
+    wifi: WifiFixture,
+    #[serde(default)]
+    ethernet: Vec<EthernetFixture>,
+    #[serde(default)]
+    events: Vec<EventFixture>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct WifiFixture {
+    #[serde(default)]
+    adapters: Vec<String>,
+    connected: Option<String>,
+    #[serde(default)]
+    networks: Vec<NetworkFixture>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct NetworkFixture {
+    ssid: String,
+    #[serde(default = "open_security")]
+    security: String,
+    #[serde(default)]
+    signal: i16,
+    #[serde(default)]
+    known: bool,
+    #[serde(default = "yes")]
+    autoconnect: bool,
+    #[serde(default)]
+    hidden: bool,
+    #[serde(default = "yes")]
+    in_range: bool,
+    passphrase: Option<String>,
+    #[serde(default)]
+    outcome: ConnectOutcome,
+    last_connected_mins_ago: Option<u64>,
+    #[serde(skip)]
+    last_connected: Option<SystemTime>,
+    #[serde(skip)]
+    profile: Option<KnownProfile>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ConnectOutcome {
+    #[default]
+    Success,
+    WrongPassword,
+    Timeout,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct EthernetFixture {
+    name: String,
+    #[serde(default)]
+    carrier: bool,
+    #[serde(default)]
+    speed_mbps: Option<u32>,
+    mac: Option<String>,
+    #[serde(default = "default_mtu")]
+    mtu: u32,
+    #[serde(default)]
+    ipv4: Vec<String>,
+    #[serde(default)]
+    ipv6: Vec<String>,
+    gateway_v4: Option<String>,
+    #[serde(default)]
+    dns: Vec<String>,
+    setup: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct EventFixture {
+    after_ms: u64,
+    kind: EventKind,
+    iface: Option<String>,
+    cidr: Option<String>,
+    ssid: Option<String>,
+    signal: Option<i16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum EventKind {
+    CarrierLost,
+    CarrierDetected,
+    AddressAdded,
+    AddressRemoved,
+    Signal,
+    WifiDisconnected,
+}
+
+fn open_security() -> String {
+    "open".to_string()
+}
+
+fn yes() -> bool {
+    true
+}
+
+fn default_mtu() -> u32 {
+    1500
+}
+
+impl Fixture {
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("cannot read demo fixture {}", path.display()))?;
+        Self::parse(&raw).with_context(|| format!("invalid demo fixture {}", path.display()))
+    }
+
+    pub fn parse(raw: &str) -> Result<Self> {
+        let fixture: Self = toml::from_str(raw)?;
+        for event in &fixture.events {
+            let missing = match event.kind {
+                EventKind::CarrierLost | EventKind::CarrierDetected => event.iface.is_none(),
+                EventKind::AddressAdded | EventKind::AddressRemoved => {
+                    event.iface.is_none() || event.cidr.is_none()
+                }
+                EventKind::Signal => event.ssid.is_none() || event.signal.is_none(),
+                EventKind::WifiDisconnected => false,
+            };
+            if missing {
+                return Err(std::io::Error::other(format!(
+                    "event {:?} at {} ms is missing fields",
+                    event.kind, event.after_ms
+                ))
+                .into());
+            }
+        }
+        Ok(fixture)
+    }
+}
+
+struct DemoState {
+    adapters: Vec<String>,
+    connected: Option<String>,
+    networks: Vec<NetworkFixture>,
+    ethernet: Vec<EthernetIface>,
+    cables: Vec<bool>,
+}
+
+pub struct DemoBackend {
+    state: Arc<Mutex<DemoState>>,
+    events: Vec<EventFixture>,
+    watching: AtomicBool,
+}
+
+impl DemoBackend {
+    pub fn new(fixture: Fixture) -> Self {
+        let mut adapters = fixture.wifi.adapters;
+        if adapters.is_empty() && !fixture.wifi.networks.is_empty() {
+            adapters.push("wlan0".to_string());
+        }
+
+        let now = SystemTime::now();
+        let mut networks = fixture.wifi.networks;
+        for net in &mut networks {
+            net.last_connected = net
+                .last_connected_mins_ago
+                .map(|mins| now - Duration::from_secs(mins * 60));
+        }
+        let cables = fixture.ethernet.iter().map(|e| e.carrier).collect();
+        let ethernet = fixture
+            .ethernet
+            .into_iter()
+            .enumerate()
+            .map(|(i, e)| ethernet_iface(i as u32 + 2, e))
+            .collect();
+
+        Self {
+            state: Arc::new(Mutex::new(DemoState {
+                adapters,
+                connected: fixture.wifi.connected,
+                networks,
+                ethernet,
+                cables,
+            })),
+            events: fixture.events,
+            watching: AtomicBool::new(false),
+        }
+    }
+
+    fn network(&self, ssid: &str) -> Result<NetworkFixture> {
+        self.state
+            .lock()
+            .unwrap()
+            .networks
+            .iter()
+            .find(|n| n.ssid == ssid && n.in_range)
+            .cloned()
+            .ok_or_else(|| NettuiError::NetworkNotFound(ssid.to_string()).into())
+    }
+
+    async fn join(&self, net: &NetworkFixture, passphrase: Option<&str>) -> Result<()> {
+        if net.outcome == ConnectOutcome::Timeout {
+            tokio::time::sleep(TIMEOUT_DELAY).await;
+        }
+        tokio::time::sleep(CONNECT_DELAY).await;
+
+        let wrong = match (passphrase, net.passphrase.as_deref()) {
+            _ if net.outcome == ConnectOutcome::WrongPassword => true,
+            (Some(given), Some(expected)) => given != expected,
+            _ => false,
+        };
+        if wrong {
+            return Err(NettuiError::WrongPassphrase(net.ssid.clone()).into());
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.connected = Some(net.ssid.clone());
+        if let Some(n) = state.networks.iter_mut().find(|n| n.ssid == net.ssid) {
+            n.known = true;
+            n.last_connected = Some(SystemTime::now());
+        }
+        Ok(())
+    }
+
+    fn start_timeline(&self, events: UnboundedSender<Event>) {
+        if self.watching.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let mut timeline = self.events.clone();
+        timeline.sort_by_key(|e| e.after_ms);
+        let state = Arc::clone(&self.state);
+        tokio::spawn(async move {
+            let start = tokio::time::Instant::now();
+            for event in timeline {
+                tokio::time::sleep_until(start + Duration::from_millis(event.after_ms)).await;
+                let change = Self::apply_event(&mut state.lock().unwrap(), &event);
+                if let Some(change) = change
+                    && events.send(Event::Backend(change)).is_err()
+                {
+                    break;
+                }
+            }
+        });
+    }
+
+    fn apply_event(state: &mut DemoState, event: &EventFixture) -> Option<BackendEvent> {
+        let iface = event.iface.as_deref().unwrap_or_default();
+        match event.kind {
+            EventKind::CarrierLost | EventKind::CarrierDetected => {
+                let up = event.kind == EventKind::CarrierDetected;
+                let pos = state.ethernet.iter().position(|e| e.name == iface)?;
+                state.cables[pos] = up;
+                let link = &mut state.ethernet[pos];
+                link.carrier = Some(up);
+                link.operstate = if up { "up" } else { "down" }.to_string();
+                link.speed_mbps = link.speed_mbps.filter(|_| up);
+                Some(BackendEvent::EthernetChanged(EthernetChange::Link(
+                    Box::new(link.clone()),
+                )))
+            }
+            EventKind::AddressAdded | EventKind::AddressRemoved => {
+                let link = state.ethernet.iter_mut().find(|e| e.name == iface)?;
+                let cidr = event.cidr.clone()?;
+                let v6 = cidr.contains(':');
+                let list = if v6 { &mut link.ipv6 } else { &mut link.ipv4 };
+                let ifindex = link.ifindex;
+                if event.kind == EventKind::AddressAdded {
+                    list.push(cidr.clone());
+                    Some(BackendEvent::EthernetChanged(
+                        EthernetChange::AddressAdded { ifindex, v6, cidr },
+                    ))
+                } else {
+                    list.retain(|a| *a != cidr);
+                    Some(BackendEvent::EthernetChanged(
+                        EthernetChange::AddressRemoved { ifindex, v6, cidr },
+                    ))
+                }
+            }
+            EventKind::Signal => {
+                let ssid = event.ssid.as_deref()?;
+                let net = state.networks.iter_mut().find(|n| n.ssid == ssid)?;
+                net.signal = event.signal?;
+                net.in_range = net.signal > 0;
+                Some(BackendEvent::WifiChanged)
+            }
+            EventKind::WifiDisconnected => {
+                state.connected = None;
+                Some(BackendEvent::WifiChanged)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl WifiBackend for DemoBackend {
+    async fn query_state(&self, iface: Option<&str>) -> Result<WifiState> {
+        let state = self.state.lock().unwrap();
+        let Some(selected) = pick_iface(&state.adapters, iface) else {
+            return Ok(WifiState::empty());
+        };
+
+        let connected_ssid = state.connected.clone();
+        let connected_net = connected_ssid
+            .as_deref()
+            .and_then(|ssid| state.networks.iter().find(|n| n.ssid == ssid));
+        let adapters = state
+            .adapters
+            .iter()
+            .map(|name| {
+                let active = *name == selected && connected_net.is_some();
+                WifiDeviceInfo {
+                    iface: name.clone(),
+                    mode: "station".to_string(),
+                    powered: "On".to_string(),
+                    state: if active { "connected" } else { "disconnected" }.to_string(),
+                    scanning: "No".to_string(),
+                    frequency: if active { "5.18 GHz" } else { "-" }.to_string(),
+                    security: connected_net
+                        .filter(|_| active)
+                        .map(|n| n.security.clone())
+                        .unwrap_or_else(|| "-".to_string()),
+                }
+            })
+            .collect();
+
+        let mut out = WifiState {
+            ifaces: state.adapters.clone(),
+            selected_iface: Some(selected),
+            connected_ssid: connected_ssid.clone(),
+            adapters,
+            ..WifiState::empty()
+        };
+        for net in &state.networks {
+            let entry = WifiNetwork {
+                ssid: net.ssid.clone(),
+                security: net.security.clone(),
+                signal: if net.in_range {
+                    signal_label(net.signal)
+                } else {
+                    "-".to_string()
+                },
+                connected: connected_ssid.as_deref() == Some(net.ssid.as_str()),
+                hidden: net.known.then_some(net.hidden),
+                autoconnect: net.known.then_some(net.autoconnect),
+                available: net.in_range,
+                last_connected: net.last_connected,
+            };
+            match (net.known, net.in_range, net.hidden) {
+                (true, true, _) => out.known_networks.push(entry),
+                (true, false, _) => out.unavailable_known_networks.push(entry),
+                (false, true, true) => out.hidden_networks.push(WifiNetwork {
+                    hidden: Some(true),
+                    available: false,
+                    ..entry
+                }),
+                (false, true, false) => out.new_networks.push(entry),
+                (false, false, _) => {}
+            }
+        }
+        out.known_networks.sort_by(|a, b| a.ssid.cmp(&b.ssid));
+        out.new_networks.sort_by(|a, b| a.ssid.cmp(&b.ssid));
+        Ok(out)
+    }
+
+    async fn scan(&self, _iface: &str) -> Result<()> {
+        tokio::time::sleep(SCAN_DELAY).await;
+        Ok(())
+    }
+
+    async fn connect(&self, _iface: &str, ssid: &str) -> Result<()> {
+        let net = self.network(ssid)?;
+        if !net.known && net.security != "open" {
+            return Err(NettuiError::PassphraseRequired(ssid.to_string()).into());
+        }
+        self.join(&net, None).await
+    }
+
+    async fn connect_hidden(&self, iface: &str, ssid: &str) -> Result<()> {
+        self.connect(iface, ssid).await
+    }
+
+    async fn connect_with_passphrase(
+        &self,
+        _iface: &str,
+        ssid: &str,
+        passphrase: &str,
+    ) -> Result<()> {
+        let net = self.network(ssid)?;
+        self.join(&net, Some(passphrase)).await
+    }
+
+    async fn disconnect(&self, _iface: &str) -> Result<()> {
+        self.state.lock().unwrap().connected = None;
+        Ok(())
+    }
+
+    async fn provision_enterprise(
+        &self,
+        ssid: &str,
+        _config: &EnterpriseConfig,
+    ) -> Result<CommandResult> {
+        let mut state = self.state.lock().unwrap();
+        let net = state
+            .networks
+            .iter_mut()
+            .find(|n| n.ssid == ssid)
+            .ok_or_else(|| NettuiError::NetworkNotFound(ssid.to_string()))?;
+        net.known = true;
+        Ok(demo_result("provision-8021x", ssid))
+    }
+
+    async fn read_known_profile(&self, ssid: &str, _security: &str) -> Result<KnownProfile> {
+        let state = self.state.lock().unwrap();
+        let net = state
+            .networks
+            .iter()
+            .find(|n| n.ssid == ssid && n.known)
+            .ok_or_else(|| NettuiError::KnownNetworkNotFound(ssid.to_string()))?;
+        let profile = net.profile.clone().unwrap_or_default();
+        Ok(KnownProfile {
+            autoconnect: net.autoconnect,
+            hidden: net.hidden,
+            ..profile
+        })
+    }
+
+    async fn write_known_profile(
+        &self,
+        ssid: &str,
+        _security: &str,
+        profile: &KnownProfile,
+    ) -> Result<CommandResult> {
+        tokio::time::sleep(LINK_DELAY).await;
+        let mut state = self.state.lock().unwrap();
+        let net = state
+            .networks
+            .iter_mut()
+            .find(|n| n.ssid == ssid && n.known)
+            .ok_or_else(|| NettuiError::KnownNetworkNotFound(ssid.to_string()))?;
+        net.autoconnect = profile.autoconnect;
+        net.hidden = profile.hidden;
+        net.profile = Some(profile.clone());
+        Ok(demo_result("write-profile", ssid))
+    }
+
+    async fn read_passphrase(&self, ssid: &str, _security: &str) -> Result<Option<String>> {
+        let state = self.state.lock().unwrap();
+        let net = state
+            .networks
+            .iter()
+            .find(|n| n.ssid == ssid && n.known)
+            .ok_or_else(|| NettuiError::KnownNetworkNotFound(ssid.to_string()))?;
+        Ok(net.passphrase.clone())
+    }
+
+    async fn forget_known(&self, _iface: &str, ssid: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.connected.as_deref() == Some(ssid) {
+            state.connected = None;
+        }
+        let net = state
+            .networks
+            .iter_mut()
+            .find(|n| n.ssid == ssid && n.known)
+            .ok_or_else(|| NettuiError::KnownNetworkNotFound(ssid.to_string()))?;
+        net.known = false;
+        Ok(())
+    }
+
+    async fn toggle_autoconnect(&self, _iface: &str, ssid: &str) -> Result<bool> {
+        let mut state = self.state.lock().unwrap();
+        let net = state
+            .networks
+            .iter_mut()
+            .find(|n| n.ssid == ssid && n.known)
+            .ok_or_else(|| NettuiError::KnownNetworkNotFound(ssid.to_string()))?;
+        net.autoconnect = !net.autoconnect;
+        Ok(net.autoconnect)
+    }
+
+    fn watch(&self, events: UnboundedSender<Event>) -> bool {
+        self.start_timeline(events);
+        true
+    }
+}
+
+#[async_trait]
+impl EthernetBackend for DemoBackend {
+    async fn list_ifaces(&self) -> Result<Vec<EthernetIface>> {
+        Ok(self.state.lock().unwrap().ethernet.clone())
+    }
+
+    async fn iface_details(&self, iface: &str) -> Result<EthernetIface> {
+        let state = self.state.lock().unwrap();
+        if let Some(link) = state.ethernet.iter().find(|e| e.name == iface) {
+            return Ok(link.clone());
+        }
+        if !state.adapters.iter().any(|a| a == iface) {
+            return Err(std::io::Error::other(format!("interface not found: {iface}")).into());
+        }
+
+        let connected = state.connected.is_some();
+        Ok(EthernetIface {
+            operstate: if connected { "up" } else { "dormant" }.to_string(),
+            carrier: Some(connected),
+            ..ethernet_iface(
+                (state.ethernet.len() + 2) as u32,
+                EthernetFixture {
+                    name: iface.to_string(),
+                    carrier: connected,
+                    speed_mbps: None,
+                    mac: None,
+                    mtu: default_mtu(),
+                    ipv4: Vec::new(),
+                    ipv6: Vec::new(),
+                    gateway_v4: None,
+                    dns: Vec::new(),
+                    setup: None,
+                },
+            )
+        })
+    }
+
+    async fn renew_dhcp(&self, iface: &str) -> Result<CommandResult> {
+        tokio::time::sleep(LINK_DELAY).await;
+        Ok(demo_result("renew", iface))
+    }
+
+    async fn reconfigure(&self, iface: &str) -> Result<CommandResult> {
+        tokio::time::sleep(LINK_DELAY).await;
+        Ok(demo_result("reconfigure", iface))
+    }
+
+    async fn set_link_admin_state(&self, iface: &str, up: bool) -> Result<CommandResult> {
+        tokio::time::sleep(LINK_DELAY).await;
+        let mut state = self.state.lock().unwrap();
+        let pos = state
+            .ethernet
+            .iter()
+            .position(|e| e.name == iface)
+            .ok_or_else(|| std::io::Error::other(format!("interface not found: {iface}")))?;
+        let carrier = up && state.cables[pos];
+        let link = &mut state.ethernet[pos];
+        link.carrier = Some(carrier);
+        link.operstate = if carrier { "up" } else { "down" }.to_string();
+        link.flags
+            .retain(|f| f != "UP" && f != "RUNNING" && f != "LOWER_UP");
+        if up {
+            link.flags.insert(0, "UP".to_string());
+        }
+        Ok(demo_result(if up { "link-up" } else { "link-down" }, iface))
+    }
+
+    fn watch(&self, events: UnboundedSender<Event>) -> bool {
+        self.start_timeline(events);
+        true
+    }
+}
+
+fn ethernet_iface(ifindex: u32, e: EthernetFixture) -> EthernetIface {
+    let mut routes = Vec::new();
+    if let Some(gw) = &e.gateway_v4 {
+        routes.push(EthernetRoute {
+            destination: "default".to_string(),
+            gateway: Some(gw.clone()),
+            metric: Some(100),
+        });
+    }
+
+    let mut flags = vec!["UP".to_string(), "BROADCAST".to_string()];
+    if e.carrier {
+        flags.push("RUNNING".to_string());
+    }
+    flags.push("MULTICAST".to_string());
+    if e.carrier {
+        flags.push("LOWER_UP".to_string());
+    }
+
+    EthernetIface {
+        name: e.name,
+        ifindex,
+        operstate: if e.carrier { "up" } else { "down" }.to_string(),
+        carrier: Some(e.carrier),
+        mac: e.mac,
+        speed_mbps: e.speed_mbps.filter(|_| e.carrier),
+        mtu: Some(e.mtu),
+        flags,
+        ipv4: e.ipv4,
+        ipv6: e.ipv6,
+        gateway_v4: e.gateway_v4,
+        gateway_v6: None,
+        routes,
+        dns: e.dns,
+        managed: e.setup.map(|setup| ManagedLinkState {
+            operational: if e.carrier { "routable" } else { "no-carrier" }.to_string(),
+            address: if e.carrier { "routable" } else { "off" }.to_string(),
+            setup,
+        }),
+    }
+}
+
+fn demo_result(action: &str, iface: &str) -> CommandResult {
+    CommandResult {
+        program: "demo".to_string(),
+        args: vec![action.to_string(), iface.to_string()],
+        escalation: Escalation::None,
+        dry_run: false,
+        status: 0,
+        stdout: String::new(),
+        stderr: String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn demo() -> DemoBackend {
+        DemoBackend::new(Fixture::parse(include_str!("../../config/demo.toml.example")).unwrap())
+    }
+
+    #[tokio::test]
+    async fn shipped_fixture_builds_wifi_and_ethernet_state() {
+        let backend = demo();
+        let wifi = backend.query_state(None).await.unwrap();
+        assert_eq!(wifi.connected_ssid.as_deref(), Some("HomeNet"));
+        assert_eq!(wifi.known_networks.len(), 1);
+        assert_eq!(wifi.unavailable_known_networks[0].ssid, "Office");
+        assert_eq!(wifi.new_networks.len(), 4);
+
+        let links = backend.list_ifaces().await.unwrap();
+        assert_eq!(links.len(), 2);
+        assert!(links[0].is_active());
+        assert!(!links[1].is_active());
+
+        assert!(Fixture::parse("[[events]]\nafter_ms = 1\nkind = \"carrier_lost\"\n").is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn scripted_connect_outcomes() {
+        let backend = demo();
+
+        let err = backend.connect("wlan0", "Neighbor-5G").await.unwrap_err();
+        assert!(err.to_string().contains("passphrase required"));
+        assert!(
+            backend
+                .connect_with_passphrase("wlan0", "Neighbor-5G", "nope")
+                .await
+                .is_err()
+        );
+        backend
+            .connect_with_passphrase("wlan0", "Neighbor-5G", "hunter22")
+            .await
+            .unwrap();
+        let wifi = backend.query_state(None).await.unwrap();
+        assert_eq!(wifi.connected_ssid.as_deref(), Some("Neighbor-5G"));
+
+        let err = backend
+            .connect_with_passphrase("wlan0", "WrongPassword", "anything")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid passphrase"));
+
+        let slow = tokio::time::timeout(
+            Duration::from_secs(20),
+            backend.connect_with_passphrase("wlan0", "SlowAP", "x"),
+        )
+        .await;
+        assert!(slow.is_err());
+    }
+}
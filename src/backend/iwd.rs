@@ -1,53 +1,242 @@
-use crate::domain::wifi::{WifiDeviceInfo, WifiNetwork, WifiState};
+use crate::{
+    backend::{
+        iwd_agent::{AGENT_PATH, AgentState, IwdAgent, register_agent},
+        iwd_config,
+        iwd_proxy::{
+            IWD_SERVICE, IwdObjectManagerProxy, KnownNetworkProxy, ManagedObjects, NetworkProxy,
+            Properties, StationDiagnosticProxy, StationProxy, iwd_owner,
+        },
+        privilege::PrivilegedRunner,
+        sysfs::Sysfs,
+        traits::{CommandResult, WifiBackend},
+    },
+    domain::wifi::{
+        WifiDeviceInfo, WifiNetwork, WifiSecret, WifiState, parse_utc_timestamp, pick_iface,
+        signal_label,
+    },
+    domain::{enterprise::EnterpriseConfig, profile::KnownProfile},
+    error::NettuiError,
+    event::{BackendEvent, Event},
+};
 use anyhow::{Context, Result};
-use iwdrs::session::Session;
-use std::{collections::HashMap, fs, path::Path};
-use tokio::process::Command;
+use async_trait::async_trait;
+use futures::StreamExt;
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::sync::{Mutex, mpsc::UnboundedSender};
+use zbus::{
+    Connection, MatchRule, Message, MessageStream, message::Type as MessageType,
+    names::OwnedUniqueName, proxy::CacheProperties, zvariant::OwnedObjectPath,
+};
+
+const SERVICE_NAME: &str = "iwd";
+const WATCH_RETRY: Duration = Duration::from_secs(2);
+const DEVICE_INTERFACE: &str = "net.connman.iwd.Device";
+const STATION_INTERFACE: &str = "net.connman.iwd.Station";
+const NETWORK_INTERFACE: &str = "net.connman.iwd.Network";
+const KNOWN_NETWORK_INTERFACE: &str = "net.connman.iwd.KnownNetwork";
+const WATCHED_INTERFACES: [&str; 4] = [
+    DEVICE_INTERFACE,
+    STATION_INTERFACE,
+    NETWORK_INTERFACE,
+    KNOWN_NETWORK_INTERFACE,
+];
+const SIGNAL_DEBOUNCE: Duration = Duration::from_millis(150);
+
+pub struct IwdBackend {
+    shared: Mutex<Option<SharedSession>>,
+    agent: Arc<AgentState>,
+    sysfs: Sysfs,
+    bus_address: Option<String>,
+    runner: Arc<PrivilegedRunner>,
+    state_dir: PathBuf,
+}
+
+struct SharedSession {
+    connection: Connection,
+    owner: Option<OwnedUniqueName>,
+    agent_registered: bool,
+    stations: HashMap<OwnedObjectPath, StationProxies>,
+}
 
-pub struct IwdBackend;
+#[derive(Clone)]
+struct StationProxies {
+    station: StationProxy<'static>,
+    diagnostic: StationDiagnosticProxy<'static>,
+}
 
 impl IwdBackend {
     pub fn new() -> Self {
-        Self
+        Self::with_sysfs(Sysfs::default())
+    }
+
+    pub fn with_sysfs(sysfs: Sysfs) -> Self {
+        Self {
+            shared: Mutex::new(None),
+            agent: Arc::new(AgentState::default()),
+            sysfs,
+            bus_address: None,
+            runner: Arc::new(PrivilegedRunner::default()),
+            state_dir: PathBuf::from(iwd_config::STATE_DIR),
+        }
+    }
+
+    pub fn with_runner(mut self, runner: Arc<PrivilegedRunner>) -> Self {
+        self.runner = runner;
+        self
     }
 
-    pub async fn query_state(&self) -> Result<WifiState> {
-        let ifaces = list_wifi_ifaces();
+    pub fn with_state_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.state_dir = dir.into();
+        self
+    }
+
+    pub fn with_bus_address(mut self, address: impl Into<String>) -> Self {
+        self.bus_address = Some(address.into());
+        self
+    }
+
+    async fn session(&self) -> Result<IwdSession> {
+        let mut guard = self.shared.lock().await;
+        let mut shared = match guard.take() {
+            Some(shared) => shared,
+            None => SharedSession::connect(&self.agent, self.bus_address.as_deref()).await?,
+        };
+
+        let owner = match iwd_owner(&shared.connection).await {
+            Ok(owner) => owner,
+            Err(_) => {
+                shared = SharedSession::connect(&self.agent, self.bus_address.as_deref()).await?;
+                iwd_owner(&shared.connection)
+                    .await
+                    .context("cannot access iwd service")?
+            }
+        };
+        if owner != shared.owner {
+            shared.stations.clear();
+            shared.agent_registered = false;
+            shared.owner = owner;
+            self.agent.cancel_all();
+        }
+        if shared.owner.is_none() {
+            *guard = Some(shared);
+            return Err(NettuiError::ServiceUnavailable(SERVICE_NAME.to_string()).into());
+        }
+        if !shared.agent_registered {
+            shared.agent_registered = register_agent(&shared.connection).await.is_ok();
+        }
+
+        let objects = match IwdObjectManagerProxy::new(&shared.connection).await {
+            Ok(manager) => manager.get_managed_objects().await,
+            Err(e) => Err(e),
+        };
+        let objects = match objects {
+            Ok(objects) => objects,
+            Err(e) => {
+                shared.stations.clear();
+                shared.owner = None;
+                *guard = Some(shared);
+                return match NettuiError::from_dbus(&e, SERVICE_NAME, "") {
+                    Some(typed @ NettuiError::ServiceUnavailable(_)) => Err(typed.into()),
+                    _ => Err(anyhow::Error::from(e).context("cannot access iwd service")),
+                };
+            }
+        };
+
+        shared
+            .stations
+            .retain(|path, _| has_interface(&objects, path, STATION_INTERFACE));
+        for path in objects.keys() {
+            if shared.stations.contains_key(path)
+                || !has_interface(&objects, path, STATION_INTERFACE)
+            {
+                continue;
+            }
+            let proxies = StationProxies::build(&shared.connection, path).await?;
+            shared.stations.insert(path.clone(), proxies);
+        }
+
+        let session = IwdSession {
+            connection: shared.connection.clone(),
+            objects,
+            stations: shared.stations.clone(),
+        };
+        *guard = Some(shared);
+        Ok(session)
+    }
+}
+
+impl IwdBackend {
+    // Without a registered agent, or with one that has nowhere to prompt, iwd cannot
+    // ask for the secret itself; report it so the caller can collect a passphrase.
+    fn needs_passphrase(&self, err: &zbus::Error) -> bool {
+        let zbus::Error::MethodError(name, _, _) = err else {
+            return false;
+        };
+        match name.as_str() {
+            "net.connman.iwd.NoAgent" => true,
+            "net.connman.iwd.Aborted" => !self.agent.can_prompt(),
+            _ => false,
+        }
+    }
+}
+
+impl Default for IwdBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl WifiBackend for IwdBackend {
+    async fn query_state(&self, iface: Option<&str>) -> Result<WifiState> {
+        let ifaces = self.sysfs.wifi_ifaces();
         if ifaces.is_empty() {
             return Ok(WifiState::empty());
         }
-        let iface = ifaces[0].clone();
-
-        let session = Session::new().await.context("cannot access iwd service")?;
-        let station = session
-            .stations()
-            .await?
-            .pop()
-            .context("no wifi station found")?;
-
-        let connected_ssid = if let Some(n) = station.connected_network().await? {
-            n.name().await.ok()
-        } else {
-            None
-        };
 
-        let known_meta = load_known_meta(&session).await;
-        let discovered = station.discovered_networks().await?;
+        let session = self.session().await?;
+        let mut adapters = Vec::new();
+        for name in &ifaces {
+            adapters.push(session.adapter_info(name).await);
+        }
+
+        let stations: Vec<String> = ifaces
+            .iter()
+            .filter(|name| session.station_path(name).is_ok())
+            .cloned()
+            .collect();
+        let selected = pick_iface(&stations, iface).context("no wifi station found")?;
+        let station_path = session.station_path(&selected)?;
+        let station = session.station(&station_path)?;
+
+        let connected_ssid = session
+            .properties(&station_path, STATION_INTERFACE)
+            .filter(|p| prop_str(p, "State").as_deref() == Some("connected"))
+            .and_then(|p| prop_path(p, "ConnectedNetwork"))
+            .and_then(|path| session.properties(&path, NETWORK_INTERFACE))
+            .and_then(|p| prop_str(p, "Name"));
+
+        let known_meta = session.known_meta();
+        let discovered = station.get_ordered_networks().await?;
 
         let mut known_networks = Vec::new();
         let mut new_networks = Vec::new();
-        let mut available_names = std::collections::HashSet::new();
+        let mut available_names = HashSet::new();
 
-        for (network, signal_dbm) in discovered {
-            let name = match network.name().await {
-                Ok(v) if !v.is_empty() => v,
+        for (path, signal_dbm) in discovered {
+            let Some(props) = session.properties(&path, NETWORK_INTERFACE) else {
+                continue;
+            };
+            let name = match prop_str(props, "Name") {
+                Some(v) if !v.is_empty() => v,
                 _ => continue,
             };
-            let security = network
-                .network_type()
-                .await
-                .map(|v| v.to_string())
-                .unwrap_or_else(|_| "-".to_string());
+            let security = prop_str(props, "Type").unwrap_or_else(|| "-".to_string());
             let connected = connected_ssid.as_deref() == Some(name.as_str());
             let signal = percent_signal(signal_dbm);
 
@@ -61,6 +250,7 @@ impl IwdBackend {
                     hidden: Some(meta.hidden),
                     autoconnect: Some(meta.autoconnect),
                     available: true,
+                    last_connected: meta.last_connected,
                 });
             } else {
                 new_networks.push(WifiNetwork {
@@ -71,6 +261,7 @@ impl IwdBackend {
                     hidden: None,
                     autoconnect: None,
                     available: true,
+                    last_connected: None,
                 });
             }
         }
@@ -91,214 +282,434 @@ impl IwdBackend {
                 hidden: Some(meta.hidden),
                 autoconnect: Some(meta.autoconnect),
                 available: false,
+                last_connected: meta.last_connected,
             });
         }
         unavailable_known_networks.sort_by(|a, b| a.ssid.cmp(&b.ssid));
 
         let mut hidden_networks = Vec::new();
-        if let Ok(hidden_list) = station.get_hidden_networks().await {
-            for net in hidden_list {
-                let security = net
-                    .network_type
-                    .to_string()
-                    .split("::")
-                    .last()
-                    .unwrap_or("-")
-                    .to_string();
+        if let Ok(hidden_list) = station.get_hidden_access_points().await {
+            for (address, signal_dbm, security) in hidden_list {
                 hidden_networks.push(WifiNetwork {
-                    ssid: net.address,
+                    ssid: address,
                     security,
-                    signal: percent_signal(net.signal_strength),
+                    signal: percent_signal(signal_dbm),
                     connected: false,
                     hidden: Some(true),
                     autoconnect: None,
                     available: false,
+                    last_connected: None,
                 });
             }
             hidden_networks.sort_by(|a, b| a.ssid.cmp(&b.ssid));
         }
 
-        let state = station
-            .state()
-            .await
-            .map(|v| v.to_string())
-            .unwrap_or_else(|_| "-".to_string());
-        let scanning = station
-            .is_scanning()
-            .await
-            .map(|v| {
-                if v {
-                    "Yes".to_string()
-                } else {
-                    "No".to_string()
-                }
-            })
-            .unwrap_or_else(|_| "-".to_string());
-
-        let powered = match session.devices().await {
-            Ok(mut devices) => {
-                if let Some(device) = devices.pop() {
-                    match device.is_powered().await {
-                        Ok(true) => "On".to_string(),
-                        Ok(false) => "Off".to_string(),
-                        Err(_) => "-".to_string(),
-                    }
-                } else {
-                    "-".to_string()
-                }
-            }
-            Err(_) => "-".to_string(),
-        };
-
-        let mut frequency = "-".to_string();
-        let mut security = "-".to_string();
-        if let Ok(mut diagnostics) = session.stations_diagnostics().await
-            && let Some(diag) = diagnostics.pop()
-            && let Ok(d) = diag.get().await
-        {
-            frequency = format!("{:.2} GHz", d.frequency_mhz as f32 / 1000.0);
-            security = d.security.to_string();
-        }
-
         Ok(WifiState {
             ifaces,
+            selected_iface: Some(selected),
             connected_ssid,
             known_networks,
             unavailable_known_networks,
             new_networks,
             hidden_networks,
-            device: Some(WifiDeviceInfo {
-                iface,
-                mode: "station".to_string(),
-                powered,
-                state,
-                scanning,
-                frequency,
-                security,
-            }),
+            adapters,
         })
     }
 
-    pub async fn scan(&self) -> Result<()> {
-        let session = Session::new().await.context("cannot access iwd service")?;
-        let station = session
-            .stations()
-            .await?
-            .pop()
-            .context("no wifi station found")?;
-        station.scan().await?;
+    async fn scan(&self, iface: &str) -> Result<()> {
+        let session = self.session().await?;
+        let station = session.station(&session.station_path(iface)?)?;
+        station
+            .scan()
+            .await
+            .map_err(|e| NettuiError::wrap_dbus(e, SERVICE_NAME, ""))?;
         Ok(())
     }
 
-    pub async fn disconnect(&self) -> Result<()> {
-        let session = Session::new().await.context("cannot access iwd service")?;
-        let station = session
-            .stations()
-            .await?
-            .pop()
-            .context("no wifi station found")?;
-        station.disconnect().await?;
+    async fn disconnect(&self, iface: &str) -> Result<()> {
+        let session = self.session().await?;
+        let station = session.station(&session.station_path(iface)?)?;
+        station
+            .disconnect()
+            .await
+            .map_err(|e| NettuiError::wrap_dbus(e, SERVICE_NAME, ""))?;
         Ok(())
     }
 
-    pub async fn connect(&self, ssid: &str) -> Result<()> {
-        let session = Session::new().await.context("cannot access iwd service")?;
-        let station = session
-            .stations()
-            .await?
-            .pop()
-            .context("no wifi station found")?;
-        let discovered = station.discovered_networks().await?;
+    async fn provision_enterprise(
+        &self,
+        ssid: &str,
+        config: &EnterpriseConfig,
+    ) -> Result<CommandResult> {
+        let file_name = format!("{}.8021x", iwd_config::storage_name(ssid));
+        let profile = iwd_config::enterprise_profile(config);
+        iwd_config::install(&self.runner, &self.state_dir, &file_name, &profile).await
+    }
 
-        for (network, _) in discovered {
-            let name = match network.name().await {
-                Ok(v) => v,
-                Err(_) => continue,
-            };
-            if name == ssid {
-                network.connect().await?;
-                return Ok(());
+    async fn read_known_profile(&self, ssid: &str, security: &str) -> Result<KnownProfile> {
+        let file_name = iwd_config::profile_file_name(ssid, security)?;
+        let source = iwd_config::read(&self.runner, &self.state_dir, &file_name).await?;
+        Ok(iwd_config::parse_known_profile(&source))
+    }
+
+    async fn write_known_profile(
+        &self,
+        ssid: &str,
+        security: &str,
+        profile: &KnownProfile,
+    ) -> Result<CommandResult> {
+        let file_name = iwd_config::profile_file_name(ssid, security)?;
+        let contents = iwd_config::render_known_profile(profile);
+        iwd_config::install(&self.runner, &self.state_dir, &file_name, &contents).await
+    }
+
+    async fn read_passphrase(&self, ssid: &str, security: &str) -> Result<Option<String>> {
+        let file_name = iwd_config::profile_file_name(ssid, security)?;
+        let source = iwd_config::read(&self.runner, &self.state_dir, &file_name).await?;
+        iwd_config::stored_passphrase(ssid, &source)
+    }
+
+    async fn cancel_connect(&self, iface: &str) -> Result<()> {
+        match self.disconnect(iface).await {
+            Err(e) if NettuiError::of(&e) == Some(&NettuiError::NotConnected) => Ok(()),
+            other => other,
+        }
+    }
+
+    async fn connect(&self, iface: &str, ssid: &str) -> Result<()> {
+        let session = self.session().await?;
+        let path = session.network_path(iface, ssid).await?;
+        match session.network(&path).await?.connect().await {
+            Ok(()) => Ok(()),
+            Err(e) if self.needs_passphrase(&e) => {
+                Err(NettuiError::PassphraseRequired(ssid.to_string()).into())
             }
+            Err(e) => Err(NettuiError::wrap_dbus(e, SERVICE_NAME, ssid)),
         }
+    }
 
-        Err(std::io::Error::other(format!("network not found: {ssid}")).into())
+    async fn connect_hidden(&self, iface: &str, ssid: &str) -> Result<()> {
+        let session = self.session().await?;
+        let station = session.station(&session.station_path(iface)?)?;
+        station
+            .connect_hidden_network(ssid)
+            .await
+            .map_err(|e| NettuiError::wrap_dbus(e, SERVICE_NAME, ssid))?;
+        Ok(())
+    }
+
+    async fn connect_with_passphrase(
+        &self,
+        iface: &str,
+        ssid: &str,
+        passphrase: &str,
+    ) -> Result<()> {
+        let session = self.session().await?;
+        let path = session.network_path(iface, ssid).await?;
+        let network = session.network(&path).await?;
+
+        self.agent.preset(path.clone(), passphrase.to_string());
+        let result = network.connect().await;
+        let supplied = self.agent.clear_preset(&path);
+        match result {
+            Ok(()) => Ok(()),
+            // iwd only reports a rejected key as a plain Failed once the handshake that
+            // used it has run, so a failure before the agent was asked is something else.
+            Err(e) => match NettuiError::from_dbus(&e, SERVICE_NAME, ssid) {
+                Some(NettuiError::ConnectFailed) if supplied => {
+                    Err(NettuiError::WrongPassphrase(ssid.to_string()).into())
+                }
+                _ => Err(NettuiError::wrap_dbus(e, SERVICE_NAME, ssid)),
+            },
+        }
     }
 
-    pub async fn connect_hidden(&self, ssid: &str) -> Result<()> {
-        let session = Session::new().await.context("cannot access iwd service")?;
-        let station = session
-            .stations()
-            .await?
-            .pop()
-            .context("no wifi station found")?;
-        station.connect_hidden_network(ssid.to_string()).await?;
+    async fn forget_known(&self, _iface: &str, ssid: &str) -> Result<()> {
+        let session = self.session().await?;
+        let network = session.known_network(ssid).await?;
+        network
+            .forget()
+            .await
+            .map_err(|e| NettuiError::wrap_dbus(e, SERVICE_NAME, ssid))?;
         Ok(())
     }
 
-    pub async fn connect_with_passphrase(&self, ssid: &str, passphrase: &str) -> Result<()> {
-        let iface = list_wifi_ifaces()
-            .into_iter()
-            .next()
-            .context("no wifi adapter found")?;
-
-        let out = Command::new("iwctl")
-            .arg("--passphrase")
-            .arg(passphrase)
-            .arg("station")
-            .arg(&iface)
-            .arg("connect")
-            .arg(ssid)
-            .output()
+    async fn toggle_autoconnect(&self, _iface: &str, ssid: &str) -> Result<bool> {
+        let session = self.session().await?;
+        let network = session.known_network(ssid).await?;
+        let current = network.auto_connect().await.unwrap_or(false);
+        let next = !current;
+        network
+            .set_auto_connect(next)
             .await
-            .context("failed to run iwctl")?;
+            .map_err(|e| NettuiError::wrap_dbus(e, SERVICE_NAME, ssid))?;
+        Ok(next)
+    }
+
+    fn answer_secret(&self, id: u64, secret: Option<WifiSecret>) {
+        self.agent.answer(id, secret);
+    }
 
-        if out.status.success() {
-            return Ok(());
+    fn watch(&self, events: UnboundedSender<Event>) -> bool {
+        self.agent.set_events(events.clone());
+        let address = self.bus_address.clone();
+        tokio::spawn(async move {
+            while !events.is_closed() {
+                let _ = forward_iwd_signals(&events, address.as_deref()).await;
+                tokio::time::sleep(WATCH_RETRY).await;
+            }
+        });
+        true
+    }
+}
+
+async fn forward_iwd_signals(events: &UnboundedSender<Event>, address: Option<&str>) -> Result<()> {
+    let connection = bus_connection(address).await?;
+    let mut rules = Vec::new();
+    for interface in WATCHED_INTERFACES {
+        rules.push(
+            MatchRule::builder()
+                .msg_type(MessageType::Signal)
+                .sender(IWD_SERVICE)?
+                .interface("org.freedesktop.DBus.Properties")?
+                .member("PropertiesChanged")?
+                .arg(0, interface)?
+                .build(),
+        );
+    }
+    for member in ["InterfacesAdded", "InterfacesRemoved"] {
+        rules.push(
+            MatchRule::builder()
+                .msg_type(MessageType::Signal)
+                .sender(IWD_SERVICE)?
+                .interface("org.freedesktop.DBus.ObjectManager")?
+                .member(member)?
+                .build(),
+        );
+    }
+    rules.push(
+        MatchRule::builder()
+            .msg_type(MessageType::Signal)
+            .sender("org.freedesktop.DBus")?
+            .interface("org.freedesktop.DBus")?
+            .member("NameOwnerChanged")?
+            .arg(0, IWD_SERVICE)?
+            .build(),
+    );
+
+    let mut streams = Vec::new();
+    for rule in rules {
+        streams.push(MessageStream::for_match_rule(rule, &connection, None).await?);
+    }
+
+    let mut signals = futures::stream::select_all(streams);
+    while let Some(msg) = signals.next().await {
+        if !touches_watched_interface(&msg?) {
+            continue;
         }
+        // A scan adds and drops objects in a burst; fold it into one refresh.
+        let deadline = tokio::time::Instant::now() + SIGNAL_DEBOUNCE;
+        while let Ok(Some(msg)) = tokio::time::timeout_at(deadline, signals.next()).await {
+            msg?;
+        }
+        if events
+            .send(Event::Backend(BackendEvent::WifiChanged))
+            .is_err()
+        {
+            break;
+        }
+    }
+    Ok(())
+}
 
-        let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
-        let stdout = String::from_utf8_lossy(&out.stdout).trim().to_string();
-        let msg = if !stderr.is_empty() { stderr } else { stdout };
-        Err(std::io::Error::other(if msg.is_empty() {
-            "iwctl connect failed".to_string()
-        } else {
-            msg
+fn touches_watched_interface(msg: &Message) -> bool {
+    let watched = |interface: &String| WATCHED_INTERFACES.contains(&interface.as_str());
+    let body = msg.body();
+    match msg.header().member().map(|m| m.as_str()) {
+        Some("InterfacesAdded") => body
+            .deserialize::<(OwnedObjectPath, HashMap<String, Properties>)>()
+            .is_ok_and(|(_, added)| added.keys().any(watched)),
+        Some("InterfacesRemoved") => body
+            .deserialize::<(OwnedObjectPath, Vec<String>)>()
+            .is_ok_and(|(_, removed)| removed.iter().any(watched)),
+        _ => true,
+    }
+}
+
+impl SharedSession {
+    async fn connect(agent: &Arc<AgentState>, address: Option<&str>) -> Result<Self> {
+        let connection = bus_connection(address)
+            .await
+            .context("cannot access system bus")?;
+        connection
+            .object_server()
+            .at(AGENT_PATH, IwdAgent::new(Arc::clone(agent)))
+            .await?;
+        Ok(Self {
+            connection,
+            owner: None,
+            agent_registered: false,
+            stations: HashMap::new(),
         })
-        .into())
     }
+}
 
-    pub async fn forget_known(&self, ssid: &str) -> Result<()> {
-        let session = Session::new().await.context("cannot access iwd service")?;
-        let known = session.known_networks().await?;
-        for network in known {
-            let name = network.name().await.unwrap_or_default();
-            if name == ssid {
-                network.forget().await?;
-                return Ok(());
+impl StationProxies {
+    async fn build(connection: &Connection, path: &OwnedObjectPath) -> Result<Self> {
+        Ok(Self {
+            station: StationProxy::builder(connection)
+                .path(path.clone())?
+                .cache_properties(CacheProperties::No)
+                .build()
+                .await?,
+            diagnostic: StationDiagnosticProxy::builder(connection)
+                .path(path.clone())?
+                .cache_properties(CacheProperties::No)
+                .build()
+                .await?,
+        })
+    }
+}
+
+struct IwdSession {
+    connection: Connection,
+    objects: ManagedObjects,
+    stations: HashMap<OwnedObjectPath, StationProxies>,
+}
+
+impl IwdSession {
+    fn properties(&self, path: &OwnedObjectPath, interface: &str) -> Option<&Properties> {
+        self.objects.get(path)?.get(interface)
+    }
+
+    fn find_path(&self, interface: &str, name: &str) -> Option<OwnedObjectPath> {
+        self.objects.iter().find_map(|(path, interfaces)| {
+            let props = interfaces.get(interface)?;
+            (prop_str(props, "Name").as_deref() == Some(name)).then(|| path.clone())
+        })
+    }
+
+    async fn network_path(&self, iface: &str, ssid: &str) -> Result<OwnedObjectPath> {
+        let station = self.station(&self.station_path(iface)?)?;
+        for (path, _) in station.get_ordered_networks().await? {
+            let name = self
+                .properties(&path, NETWORK_INTERFACE)
+                .and_then(|p| prop_str(p, "Name"));
+            if name.as_deref() == Some(ssid) {
+                return Ok(path);
             }
         }
-        Err(std::io::Error::other(format!("known network not found: {ssid}")).into())
-    }
-
-    pub async fn toggle_autoconnect(&self, ssid: &str) -> Result<bool> {
-        let session = Session::new().await.context("cannot access iwd service")?;
-        let known = session.known_networks().await?;
-        for network in known {
-            let name = network.name().await.unwrap_or_default();
-            if name == ssid {
-                let current = network.get_autoconnect().await.unwrap_or(false);
-                let next = !current;
-                network.set_autoconnect(next).await?;
-                return Ok(next);
+
+        Err(NettuiError::NetworkNotFound(ssid.to_string()).into())
+    }
+
+    fn station_path(&self, iface: &str) -> Result<OwnedObjectPath> {
+        self.find_path(DEVICE_INTERFACE, iface)
+            .filter(|path| self.properties(path, STATION_INTERFACE).is_some())
+            .with_context(|| format!("no wifi station found for {iface}"))
+    }
+
+    fn known_meta(&self) -> HashMap<String, KnownMeta> {
+        let mut map = HashMap::new();
+        for interfaces in self.objects.values() {
+            let Some(known) = interfaces.get(KNOWN_NETWORK_INTERFACE) else {
+                continue;
+            };
+            let name = prop_str(known, "Name").unwrap_or_default();
+            if name.is_empty() {
+                continue;
             }
+            map.insert(
+                name,
+                KnownMeta {
+                    security: prop_str(known, "Type").unwrap_or_else(|| "-".to_string()),
+                    hidden: prop_bool(known, "Hidden").unwrap_or(false),
+                    autoconnect: prop_bool(known, "AutoConnect").unwrap_or(false),
+                    last_connected: prop_str(known, "LastConnectedTime")
+                        .and_then(|t| parse_utc_timestamp(&t)),
+                },
+            );
         }
-        Err(std::io::Error::other(format!("known network not found: {ssid}")).into())
+        map
     }
-}
 
-impl Default for IwdBackend {
-    fn default() -> Self {
-        Self::new()
+    async fn adapter_info(&self, iface: &str) -> WifiDeviceInfo {
+        let mut info = WifiDeviceInfo {
+            iface: iface.to_string(),
+            mode: "station".to_string(),
+            powered: "-".to_string(),
+            state: "-".to_string(),
+            scanning: "-".to_string(),
+            frequency: "-".to_string(),
+            security: "-".to_string(),
+        };
+        let Some(path) = self.find_path(DEVICE_INTERFACE, iface) else {
+            return info;
+        };
+
+        if let Some(device) = self.properties(&path, DEVICE_INTERFACE) {
+            if let Some(mode) = prop_str(device, "Mode") {
+                info.mode = mode;
+            }
+            info.powered = match prop_bool(device, "Powered") {
+                Some(true) => "On".to_string(),
+                Some(false) => "Off".to_string(),
+                None => "-".to_string(),
+            };
+        }
+
+        let Some(station) = self.properties(&path, STATION_INTERFACE) else {
+            return info;
+        };
+        let state = prop_str(station, "State").unwrap_or_default();
+        if !state.is_empty() {
+            info.state = title_case(&state);
+        }
+        info.scanning = match prop_bool(station, "Scanning") {
+            Some(true) => "Yes".to_string(),
+            Some(false) => "No".to_string(),
+            None => "-".to_string(),
+        };
+
+        if state == "connected"
+            && let Some(proxies) = self.stations.get(&path)
+            && let Ok(d) = proxies.diagnostic.get_diagnostics().await
+        {
+            if let Some(mhz) = d.get("Frequency").and_then(|v| u32::try_from(v).ok()) {
+                info.frequency = format!("{:.2} GHz", mhz as f32 / 1000.0);
+            }
+            if let Some(security) = prop_str(&d, "Security") {
+                info.security = security;
+            }
+        }
+
+        info
+    }
+
+    fn station(&self, path: &OwnedObjectPath) -> Result<&StationProxy<'static>> {
+        self.stations
+            .get(path)
+            .map(|p| &p.station)
+            .with_context(|| format!("no wifi station at {}", path.as_str()))
+    }
+
+    async fn network(&self, path: &OwnedObjectPath) -> Result<NetworkProxy<'static>> {
+        Ok(NetworkProxy::builder(&self.connection)
+            .path(path.clone())?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?)
+    }
+
+    async fn known_network(&self, ssid: &str) -> Result<KnownNetworkProxy<'static>> {
+        let path = self
+            .find_path(KNOWN_NETWORK_INTERFACE, ssid)
+            .ok_or_else(|| NettuiError::KnownNetworkNotFound(ssid.to_string()))?;
+        Ok(KnownNetworkProxy::builder(&self.connection)
+            .path(path)?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?)
     }
 }
 
@@ -307,37 +718,46 @@ struct KnownMeta {
     security: String,
     hidden: bool,
     autoconnect: bool,
+    last_connected: Option<SystemTime>,
 }
 
-async fn load_known_meta(session: &Session) -> HashMap<String, KnownMeta> {
-    let mut map = HashMap::new();
-    let Ok(known) = session.known_networks().await else {
-        return map;
-    };
-
-    for network in known {
-        let name = network.name().await.unwrap_or_default();
-        if name.is_empty() {
-            continue;
-        }
-        let security = network
-            .network_type()
-            .await
-            .map(|v| v.to_string())
-            .unwrap_or_else(|_| "-".to_string());
-        let hidden = network.hidden().await.unwrap_or(false);
-        let autoconnect = network.get_autoconnect().await.unwrap_or(false);
-        map.insert(
-            name,
-            KnownMeta {
-                security,
-                hidden,
-                autoconnect,
-            },
-        );
+async fn bus_connection(address: Option<&str>) -> zbus::Result<Connection> {
+    match address {
+        Some(address) => zbus::connection::Builder::address(address)?.build().await,
+        None => Connection::system().await,
     }
+}
+
+fn has_interface(objects: &ManagedObjects, path: &OwnedObjectPath, interface: &str) -> bool {
+    objects
+        .get(path)
+        .is_some_and(|interfaces| interfaces.contains_key(interface))
+}
+
+fn prop_str(props: &Properties, key: &str) -> Option<String> {
+    props
+        .get(key)
+        .and_then(|v| <&str>::try_from(v).ok())
+        .map(|s| s.to_string())
+}
 
-    map
+fn prop_bool(props: &Properties, key: &str) -> Option<bool> {
+    props.get(key).and_then(|v| bool::try_from(v).ok())
+}
+
+fn prop_path(props: &Properties, key: &str) -> Option<OwnedObjectPath> {
+    props
+        .get(key)
+        .and_then(|v| v.try_clone().ok())
+        .and_then(|v| OwnedObjectPath::try_from(v).ok())
+}
+
+fn title_case(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
 }
 
 fn percent_signal(signal_dbm: i16) -> String {
@@ -347,32 +767,5 @@ fn percent_signal(signal_dbm: i16) -> String {
         2 * (100 + signal_dbm / 100)
     };
 
-    match signal {
-        n if n >= 75 => format!("{signal:3}% 󰤨"),
-        n if (50..75).contains(&n) => format!("{signal:3}% 󰤥"),
-        n if (25..50).contains(&n) => format!("{signal:3}% 󰤢"),
-        _ => format!("{signal:3}% 󰤟"),
-    }
-}
-
-fn list_wifi_ifaces() -> Vec<String> {
-    let mut out = Vec::new();
-    let Ok(entries) = fs::read_dir("/sys/class/net") else {
-        return out;
-    };
-
-    for entry in entries.flatten() {
-        let name = entry.file_name().to_string_lossy().to_string();
-        if name == "lo" {
-            continue;
-        }
-
-        let p = Path::new("/sys/class/net").join(&name);
-        if p.join("wireless").is_dir() || p.join("phy80211").exists() {
-            out.push(name);
-        }
-    }
-
-    out.sort();
-    out
+    signal_label(signal)
 }
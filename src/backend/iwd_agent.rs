@@ -0,0 +1,286 @@
+use crate::{
+    backend::iwd_proxy::{NetworkProxy, iwd_owner},
+    domain::wifi::{SecretKind, SecretRequest, WifiSecret},
+    event::{BackendEvent, Event},
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+use tokio::sync::{mpsc::UnboundedSender, oneshot};
+use zbus::{
+    Connection, interface,
+    message::Header,
+    proxy,
+    proxy::CacheProperties,
+    zvariant::{ObjectPath, OwnedObjectPath},
+};
+
+pub const AGENT_PATH: &str = "/org/nettui/agent";
+
+#[proxy(
+    interface = "net.connman.iwd.AgentManager",
+    default_service = "net.connman.iwd",
+    default_path = "/net/connman/iwd"
+)]
+trait AgentManager {
+    fn register_agent(&self, path: &ObjectPath<'_>) -> zbus::Result<()>;
+}
+
+#[derive(Debug, zbus::DBusError)]
+#[zbus(prefix = "net.connman.iwd.Agent.Error")]
+enum AgentError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    Canceled(String),
+}
+
+#[derive(Default)]
+pub struct AgentState {
+    events: Mutex<Option<UnboundedSender<Event>>>,
+    pending: Mutex<HashMap<u64, oneshot::Sender<Option<WifiSecret>>>>,
+    presets: Mutex<HashMap<OwnedObjectPath, String>>,
+    next_id: AtomicU64,
+}
+
+impl AgentState {
+    pub fn set_events(&self, events: UnboundedSender<Event>) {
+        *self.events.lock().unwrap() = Some(events);
+    }
+
+    pub fn can_prompt(&self) -> bool {
+        self.events
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|events| !events.is_closed())
+    }
+
+    pub fn preset(&self, network: OwnedObjectPath, passphrase: String) {
+        self.presets.lock().unwrap().insert(network, passphrase);
+    }
+
+    /// Drops the preset for `network` and reports whether iwd asked for it first.
+    pub fn clear_preset(&self, network: &OwnedObjectPath) -> bool {
+        self.presets.lock().unwrap().remove(network).is_none()
+    }
+
+    pub fn answer(&self, id: u64, secret: Option<WifiSecret>) {
+        if let Some(reply) = self.pending.lock().unwrap().remove(&id) {
+            let _ = reply.send(secret);
+        }
+    }
+
+    pub fn cancel_all(&self) {
+        let had_pending = {
+            let mut pending = self.pending.lock().unwrap();
+            let had_pending = !pending.is_empty();
+            pending.clear();
+            had_pending
+        };
+        if had_pending && let Some(events) = self.events.lock().unwrap().as_ref() {
+            let _ = events.send(Event::Backend(BackendEvent::SecretCanceled));
+        }
+    }
+
+    async fn ask(&self, ssid: String, kind: SecretKind) -> Result<WifiSecret, AgentError> {
+        let Some(events) = self.events.lock().unwrap().clone() else {
+            return Err(AgentError::Canceled("no prompt available".to_string()));
+        };
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (reply, answer) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, reply);
+
+        let request = SecretRequest { id, ssid, kind };
+        if events
+            .send(Event::Backend(BackendEvent::SecretRequested(request)))
+            .is_err()
+        {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(AgentError::Canceled("no prompt available".to_string()));
+        }
+
+        match answer.await {
+            Ok(Some(secret)) => Ok(secret),
+            _ => Err(AgentError::Canceled("canceled by user".to_string())),
+        }
+    }
+}
+
+pub struct IwdAgent {
+    state: Arc<AgentState>,
+}
+
+impl IwdAgent {
+    pub fn new(state: Arc<AgentState>) -> Self {
+        Self { state }
+    }
+}
+
+#[interface(name = "net.connman.iwd.Agent")]
+impl IwdAgent {
+    fn release(&self) {
+        self.state.cancel_all();
+    }
+
+    async fn request_passphrase(
+        &self,
+        network: OwnedObjectPath,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> Result<String, AgentError> {
+        ensure_from_iwd(connection, &header).await?;
+        if let Some(passphrase) = self.state.presets.lock().unwrap().remove(&network) {
+            return Ok(passphrase);
+        }
+        let ssid = network_name(connection, &network).await;
+        let secret = self.state.ask(ssid, SecretKind::Passphrase).await?;
+        Ok(secret.password)
+    }
+
+    async fn request_private_key_passphrase(
+        &self,
+        network: OwnedObjectPath,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> Result<String, AgentError> {
+        ensure_from_iwd(connection, &header).await?;
+        let ssid = network_name(connection, &network).await;
+        let secret = self
+            .state
+            .ask(ssid, SecretKind::PrivateKeyPassphrase)
+            .await?;
+        Ok(secret.password)
+    }
+
+    async fn request_user_name_and_password(
+        &self,
+        network: OwnedObjectPath,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> Result<(String, String), AgentError> {
+        ensure_from_iwd(connection, &header).await?;
+        let ssid = network_name(connection, &network).await;
+        let secret = self
+            .state
+            .ask(ssid, SecretKind::UserNameAndPassword)
+            .await?;
+        Ok((secret.username.unwrap_or_default(), secret.password))
+    }
+
+    async fn request_user_password(
+        &self,
+        network: OwnedObjectPath,
+        user: String,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(header)] header: Header<'_>,
+    ) -> Result<String, AgentError> {
+        ensure_from_iwd(connection, &header).await?;
+        let ssid = network_name(connection, &network).await;
+        let secret = self.state.ask(ssid, SecretKind::UserPassword(user)).await?;
+        Ok(secret.password)
+    }
+
+    fn cancel(&self, _reason: String) {
+        self.state.cancel_all();
+    }
+}
+
+pub async fn register_agent(connection: &Connection) -> zbus::Result<()> {
+    AgentManagerProxy::new(connection)
+        .await?
+        .register_agent(&ObjectPath::try_from(AGENT_PATH)?)
+        .await
+}
+
+// The agent object is reachable by any peer on the bus, and a preset passphrase must
+// only ever go back to iwd itself.
+async fn ensure_from_iwd(connection: &Connection, header: &Header<'_>) -> Result<(), AgentError> {
+    let owner = iwd_owner(connection).await.ok().flatten();
+    match (header.sender(), owner) {
+        (Some(sender), Some(owner)) if sender.as_str() == owner.as_str() => Ok(()),
+        _ => Err(AgentError::Canceled(
+            "request did not come from iwd".to_string(),
+        )),
+    }
+}
+
+async fn network_name(connection: &Connection, network: &OwnedObjectPath) -> String {
+    let proxy = NetworkProxy::builder(connection)
+        .path(network.clone())
+        .ok()
+        .map(|b| b.cache_properties(CacheProperties::No));
+    let name = match proxy {
+        Some(builder) => match builder.build().await {
+            Ok(proxy) => proxy.name().await.ok(),
+            Err(_) => None,
+        },
+        None => None,
+    };
+    name.unwrap_or_else(|| network.as_str().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn ask_forwards_request_and_returns_answer() {
+        let state = Arc::new(AgentState::default());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        state.set_events(tx);
+
+        let asking = Arc::clone(&state);
+        let task = tokio::spawn(async move {
+            asking
+                .ask("Office".to_string(), SecretKind::UserNameAndPassword)
+                .await
+        });
+
+        let Some(Event::Backend(BackendEvent::SecretRequested(request))) = rx.recv().await else {
+            panic!("expected a secret request");
+        };
+        assert_eq!(request.ssid, "Office");
+        assert_eq!(request.kind, SecretKind::UserNameAndPassword);
+
+        state.answer(
+            request.id,
+            Some(WifiSecret {
+                username: Some("alice".to_string()),
+                password: "s3cret".to_string(),
+            }),
+        );
+        let secret = task.await.unwrap().unwrap();
+        assert_eq!(secret.username.as_deref(), Some("alice"));
+        assert_eq!(secret.password, "s3cret");
+    }
+
+    #[tokio::test]
+    async fn cancel_all_fails_pending_requests() {
+        let state = Arc::new(AgentState::default());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        state.set_events(tx);
+
+        let asking = Arc::clone(&state);
+        let task =
+            tokio::spawn(
+                async move { asking.ask("Home".to_string(), SecretKind::Passphrase).await },
+            );
+        assert!(matches!(
+            rx.recv().await,
+            Some(Event::Backend(BackendEvent::SecretRequested(_)))
+        ));
+
+        state.cancel_all();
+        assert!(matches!(task.await.unwrap(), Err(AgentError::Canceled(_))));
+        assert!(matches!(
+            rx.recv().await,
+            Some(Event::Backend(BackendEvent::SecretCanceled))
+        ));
+    }
+}
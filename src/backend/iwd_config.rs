@@ -0,0 +1,477 @@
+use crate::{
+    backend::{
+        privilege::{Escalation, PrivilegedRunner},
+        traits::CommandResult,
+    },
+    domain::{
+        enterprise::EnterpriseConfig,
+        profile::{KnownProfile, MacPolicy},
+    },
+    error::NettuiError,
+};
+use anyhow::{Context, Result};
+use std::{
+    fs,
+    io::{ErrorKind, Write},
+    os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    path::Path,
+};
+
+pub const STATE_DIR: &str = "/var/lib/iwd";
+
+// iwd keeps plain SSIDs as file names and hex-encodes anything else behind a leading '='.
+pub fn storage_name(ssid: &str) -> String {
+    let plain = ssid
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ' '));
+    if plain && !ssid.is_empty() {
+        return ssid.to_string();
+    }
+    let hex: String = ssid.bytes().map(|b| format!("{b:02x}")).collect();
+    format!("={hex}")
+}
+
+pub fn profile_file_name(ssid: &str, security: &str) -> Result<String> {
+    let ext = security.to_ascii_lowercase();
+    if !matches!(ext.as_str(), "open" | "psk" | "8021x") {
+        let msg = format!("Unsupported security type for {ssid}: {security}");
+        return Err(NettuiError::InvalidInput(msg).into());
+    }
+    Ok(format!("{}.{ext}", storage_name(ssid)))
+}
+
+pub fn parse_known_profile(source: &str) -> KnownProfile {
+    let ini = Ini::parse(source);
+    let flag = |section, key, default| match ini.get(section, key) {
+        Some("true" | "1") => true,
+        Some("false" | "0") => false,
+        _ => default,
+    };
+    let text = |section, key| ini.get(section, key).unwrap_or_default().to_string();
+
+    let address_override = text("Settings", "AddressOverride");
+    let mac_policy = if flag("Settings", "AlwaysRandomizeAddress", false) {
+        MacPolicy::Randomize
+    } else if !address_override.is_empty() {
+        MacPolicy::Override
+    } else {
+        MacPolicy::Default
+    };
+    KnownProfile {
+        autoconnect: flag("Settings", "AutoConnect", true),
+        hidden: flag("Settings", "Hidden", false),
+        mac_policy,
+        address_override,
+        transition_disable: flag("Settings", "TransitionDisable", false),
+        ipv4_address: text("IPv4", "Address"),
+        ipv4_netmask: text("IPv4", "Netmask"),
+        ipv4_gateway: text("IPv4", "Gateway"),
+        ipv4_dns: text("IPv4", "DNS"),
+        source: source.to_string(),
+    }
+}
+
+// PreSharedKey is the 64-hex key iwd derives from the passphrase and SSID; a QR code
+// carrying it in place of the passphrase does not join the network, so refuse instead.
+pub fn stored_passphrase(ssid: &str, source: &str) -> Result<Option<String>> {
+    let ini = Ini::parse(source);
+    let stored = |key| ini.get("Security", key).filter(|v| !v.is_empty());
+    if let Some(passphrase) = stored("Passphrase") {
+        return Ok(Some(unescape_value(passphrase)));
+    }
+    if stored("PreSharedKey").is_some() {
+        let msg = format!("{ssid} only has a derived key stored; its passphrase cannot be shared");
+        return Err(NettuiError::InvalidInput(msg).into());
+    }
+    Ok(None)
+}
+
+// Only keys that differ from iwd's defaults are written; everything else in the file is kept.
+pub fn render_known_profile(profile: &KnownProfile) -> String {
+    let mut ini = Ini::parse(&profile.source);
+    let on = |set: bool| set.then(|| "true".to_string());
+    let text = |value: &str| (!value.is_empty()).then(|| value.to_string());
+
+    ini.set(
+        "Settings",
+        "AutoConnect",
+        (!profile.autoconnect).then(|| "false".to_string()),
+    );
+    ini.set("Settings", "Hidden", on(profile.hidden));
+    ini.set(
+        "Settings",
+        "AlwaysRandomizeAddress",
+        on(profile.mac_policy == MacPolicy::Randomize),
+    );
+    ini.set(
+        "Settings",
+        "AddressOverride",
+        text(&profile.address_override).filter(|_| profile.mac_policy == MacPolicy::Override),
+    );
+    ini.set(
+        "Settings",
+        "TransitionDisable",
+        on(profile.transition_disable),
+    );
+    let static_ipv4 = profile.is_static_ipv4();
+    let ipv4 = |value: &str| text(value).filter(|_| static_ipv4);
+    ini.set("IPv4", "Address", ipv4(&profile.ipv4_address));
+    ini.set("IPv4", "Netmask", ipv4(&profile.ipv4_netmask));
+    ini.set("IPv4", "Gateway", ipv4(&profile.ipv4_gateway));
+    ini.set("IPv4", "DNS", ipv4(&profile.dns_servers().join(" ")));
+    ini.render()
+}
+
+struct Section {
+    name: String,
+    lines: Vec<String>,
+}
+
+struct Ini {
+    sections: Vec<Section>,
+}
+
+impl Ini {
+    fn parse(source: &str) -> Self {
+        let mut sections = vec![Section {
+            name: String::new(),
+            lines: Vec::new(),
+        }];
+        for line in source.lines() {
+            let trimmed = line.trim();
+            if let Some(name) = trimmed.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                sections.push(Section {
+                    name: name.to_string(),
+                    lines: Vec::new(),
+                });
+            } else if let Some(section) = sections.last_mut() {
+                section.lines.push(line.to_string());
+            }
+        }
+        Self { sections }
+    }
+
+    fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections
+            .iter()
+            .filter(|s| s.name == section)
+            .flat_map(|s| s.lines.iter())
+            .find_map(|line| entry_value(line, key))
+    }
+
+    fn set(&mut self, section: &str, key: &str, value: Option<String>) {
+        let mut value = value.map(|v| format!("{key}={v}"));
+        for s in self.sections.iter_mut().filter(|s| s.name == section) {
+            s.lines.retain_mut(|line| {
+                if entry_value(line, key).is_none() {
+                    return true;
+                }
+                match value.take() {
+                    Some(entry) => {
+                        *line = entry;
+                        true
+                    }
+                    None => false,
+                }
+            });
+        }
+        let Some(entry) = value else {
+            return;
+        };
+        let pos = match self.sections.iter().position(|s| s.name == section) {
+            Some(pos) => pos,
+            None => {
+                self.sections.push(Section {
+                    name: section.to_string(),
+                    lines: Vec::new(),
+                });
+                self.sections.len() - 1
+            }
+        };
+        let lines = &mut self.sections[pos].lines;
+        let at = lines
+            .iter()
+            .rposition(|l| !l.trim().is_empty())
+            .map_or(0, |i| i + 1);
+        lines.insert(at, entry);
+    }
+
+    fn render(&self) -> String {
+        let mut blocks = Vec::new();
+        for section in &self.sections {
+            let body: Vec<&str> = section.lines.iter().map(String::as_str).collect();
+            let body = body.join("\n").trim().to_string();
+            match (section.name.is_empty(), body.is_empty()) {
+                (_, true) => {}
+                (true, false) => blocks.push(body),
+                (false, false) => blocks.push(format!("[{}]\n{body}", section.name)),
+            }
+        }
+        let mut out = blocks.join("\n\n");
+        out.push('\n');
+        out
+    }
+}
+
+fn entry_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let (k, v) = line.split_once('=')?;
+    (k.trim() == key).then(|| v.trim())
+}
+
+pub fn enterprise_profile(config: &EnterpriseConfig) -> String {
+    let method = config.method.outer();
+    let outer_identity = if config.anonymous_identity.is_empty() {
+        &config.identity
+    } else {
+        &config.anonymous_identity
+    };
+
+    let mut out = String::from("[Security]\n");
+    out.push_str(&format!("EAP-Method={method}\n"));
+    out.push_str(&format!("EAP-Identity={}\n", escape_value(outer_identity)));
+    if !config.ca_cert.is_empty() {
+        out.push_str(&format!(
+            "EAP-{method}-CACert={}\n",
+            escape_value(&config.ca_cert)
+        ));
+    }
+    if !config.domain_mask.is_empty() {
+        out.push_str(&format!(
+            "EAP-{method}-ServerDomainMask={}\n",
+            escape_value(&config.domain_mask)
+        ));
+    }
+    out.push_str(&format!(
+        "EAP-{method}-Phase2-Method={}\n",
+        config.method.phase2()
+    ));
+    out.push_str(&format!(
+        "EAP-{method}-Phase2-Identity={}\n",
+        escape_value(&config.identity)
+    ));
+    out.push_str(&format!(
+        "EAP-{method}-Phase2-Password={}\n",
+        escape_value(&config.password)
+    ));
+    out.push_str("\n[Settings]\nAutoConnect=true\n");
+    out
+}
+
+// iwd parses its files with ell, which unescapes backslash sequences and trims blanks
+// around values; this mirrors l_settings_set_string.
+fn escape_value(value: &str) -> String {
+    let start = value.len() - value.trim_start_matches([' ', '\t']).len();
+    let end = value.trim_end_matches([' ', '\t']).len();
+    let mut out = String::with_capacity(value.len());
+    for (i, c) in value.char_indices() {
+        let edge = i < start || i >= end;
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            ' ' if edge => out.push_str("\\s"),
+            '\t' if edge => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => out.push(' '),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+pub async fn read(runner: &PrivilegedRunner, dir: &Path, file_name: &str) -> Result<String> {
+    let path = dir.join(file_name);
+    match fs::read_to_string(&path) {
+        Ok(raw) => return Ok(raw),
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => {}
+        Err(e) => return Err(e).with_context(|| format!("cannot read {}", path.display())),
+    }
+    if runner.dry_run() {
+        let msg = format!("Dry run: {} needs privileges to read", path.display());
+        return Err(std::io::Error::other(msg).into());
+    }
+    let out = runner.escalate("cat", &[&path.to_string_lossy()]).await?;
+    Ok(out.stdout + "\n")
+}
+
+pub async fn install(
+    runner: &PrivilegedRunner,
+    dir: &Path,
+    file_name: &str,
+    contents: &str,
+) -> Result<CommandResult> {
+    let target = dir.join(file_name);
+    let target_arg = target.to_string_lossy().to_string();
+    let written = CommandResult {
+        program: "write".to_string(),
+        args: vec![target_arg.clone()],
+        escalation: Escalation::None,
+        dry_run: runner.dry_run(),
+        status: 0,
+        stdout: String::new(),
+        stderr: String::new(),
+    };
+    if !runner.dry_run() {
+        match write_private(&target, contents) {
+            Ok(()) => return Ok(written),
+            Err(e) if e.kind() == ErrorKind::PermissionDenied => {}
+            Err(e) => return Err(e).with_context(|| format!("cannot write {target_arg}")),
+        }
+    }
+
+    let staged = std::env::temp_dir().join(format!("nettui-{}-{file_name}", std::process::id()));
+    let _ = fs::remove_file(&staged);
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&staged)
+        .and_then(|mut f| f.write_all(contents.as_bytes()))
+        .with_context(|| format!("cannot stage {}", staged.display()))?;
+
+    let staged_arg = staged.to_string_lossy().to_string();
+    let args = ["-m", "600", &staged_arg, &target_arg];
+    if runner.dry_run() {
+        // The staged copy stays behind so the planned command can be checked by hand.
+        let native = format!("write {target_arg}");
+        let mut out = runner.plan_with_fallback(&native, "install", &args);
+        out.stdout
+            .push_str(&format!(" (contents staged at {staged_arg})"));
+        return Ok(out);
+    }
+    let out = runner.escalate("install", &args).await;
+    let _ = fs::remove_file(&staged);
+    out
+}
+
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(contents.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::enterprise::EapMethod;
+
+    #[test]
+    fn renders_enterprise_profile_and_file_names() {
+        assert_eq!(storage_name("Campus WiFi"), "Campus WiFi");
+        assert_eq!(storage_name("café"), "=636166c3a9");
+
+        let config = EnterpriseConfig {
+            method: EapMethod::TtlsPap,
+            identity: "alice@example.org".to_string(),
+            anonymous_identity: "anonymous@example.org".to_string(),
+            password: "s3cret".to_string(),
+            ca_cert: "/etc/ssl/certs/campus.pem".to_string(),
+            domain_mask: "radius.example.org".to_string(),
+        };
+        assert_eq!(
+            enterprise_profile(&config),
+            "[Security]\n\
+             EAP-Method=TTLS\n\
+             EAP-Identity=anonymous@example.org\n\
+             EAP-TTLS-CACert=/etc/ssl/certs/campus.pem\n\
+             EAP-TTLS-ServerDomainMask=radius.example.org\n\
+             EAP-TTLS-Phase2-Method=Tunneled-PAP\n\
+             EAP-TTLS-Phase2-Identity=alice@example.org\n\
+             EAP-TTLS-Phase2-Password=s3cret\n\
+             \n\
+             [Settings]\n\
+             AutoConnect=true\n"
+        );
+    }
+
+    #[test]
+    fn enterprise_values_are_escaped_for_iwd() {
+        let config = EnterpriseConfig {
+            method: EapMethod::PeapMschapv2,
+            identity: "DOMAIN\\alice".to_string(),
+            password: " pa\\ss\tword ".to_string(),
+            ..EnterpriseConfig::default()
+        };
+        let profile = enterprise_profile(&config);
+        assert!(profile.contains("EAP-Identity=DOMAIN\\\\alice\n"));
+        assert!(profile.contains("EAP-PEAP-Phase2-Password=\\spa\\\\ss\tword\\s\n"));
+        assert_eq!(
+            unescape_value(&escape_value(&config.password)),
+            config.password
+        );
+        assert_eq!(escape_value("\t a b"), "\\t\\sa b");
+    }
+
+    #[test]
+    fn known_profile_round_trips_and_keeps_other_keys() {
+        let source = "[Security]\n\
+                      PreSharedKey=abcd\n\
+                      \n\
+                      [Settings]\n\
+                      AutoConnect=false\n\
+                      AlwaysRandomizeAddress=true\n\
+                      # keep me\n\
+                      \n\
+                      [IPv4]\n\
+                      Address=10.0.0.5\n\
+                      Gateway=10.0.0.1\n";
+        let mut profile = parse_known_profile(source);
+        assert!(!profile.autoconnect);
+        assert_eq!(profile.mac_policy, MacPolicy::Randomize);
+        assert_eq!(profile.ipv4_gateway, "10.0.0.1");
+        assert_eq!(render_known_profile(&profile), source);
+
+        profile.autoconnect = true;
+        profile.hidden = true;
+        profile.mac_policy = MacPolicy::Override;
+        profile.address_override = "02:00:00:00:00:01".to_string();
+        profile.ipv4_address.clear();
+        assert_eq!(
+            render_known_profile(&profile),
+            "[Security]\n\
+             PreSharedKey=abcd\n\
+             \n\
+             [Settings]\n\
+             # keep me\n\
+             Hidden=true\n\
+             AddressOverride=02:00:00:00:00:01\n"
+        );
+        assert!(stored_passphrase("Home", source).is_err());
+        let both = "[Security]\nPassphrase=hunter22\nPreSharedKey=abcd\n";
+        assert_eq!(
+            stored_passphrase("Home", both).unwrap().as_deref(),
+            Some("hunter22")
+        );
+        assert_eq!(stored_passphrase("Home", "[Settings]\n").unwrap(), None);
+        let escaped = "[Security]\nPassphrase=\\shunter\\\\22\n";
+        assert_eq!(
+            stored_passphrase("Home", escaped).unwrap().as_deref(),
+            Some(" hunter\\22")
+        );
+        assert_eq!(profile_file_name("Home", "Psk").unwrap(), "Home.psk");
+        assert!(profile_file_name("Home", "wep").is_err());
+    }
+}
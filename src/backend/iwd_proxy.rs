@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use zbus::{
+    Connection,
+    fdo::DBusProxy,
+    names::{BusName, OwnedUniqueName},
+    proxy,
+    zvariant::{OwnedObjectPath, OwnedValue},
+};
+
+// Hand-written instead of iwdrs: its Session always opens a fresh system-bus connection
+// and keeps object paths private, so it cannot share one connection with the agent and
+// signal watch, run against a private test bus, or map a station to its interface name
+// for the adapter selector.
+
+pub(crate) const IWD_SERVICE: &str = "net.connman.iwd";
+
+pub type Properties = HashMap<String, OwnedValue>;
+pub type ManagedObjects = HashMap<OwnedObjectPath, HashMap<String, Properties>>;
+
+#[proxy(
+    interface = "org.freedesktop.DBus.ObjectManager",
+    default_service = "net.connman.iwd",
+    default_path = "/"
+)]
+pub(crate) trait IwdObjectManager {
+    fn get_managed_objects(&self) -> zbus::Result<ManagedObjects>;
+}
+
+#[proxy(
+    interface = "net.connman.iwd.Station",
+    default_service = "net.connman.iwd"
+)]
+pub(crate) trait Station {
+    fn scan(&self) -> zbus::Result<()>;
+
+    fn disconnect(&self) -> zbus::Result<()>;
+
+    fn get_ordered_networks(&self) -> zbus::Result<Vec<(OwnedObjectPath, i16)>>;
+
+    fn get_hidden_access_points(&self) -> zbus::Result<Vec<(String, i16, String)>>;
+
+    fn connect_hidden_network(&self, name: &str) -> zbus::Result<()>;
+}
+
+#[proxy(
+    interface = "net.connman.iwd.Network",
+    default_service = "net.connman.iwd"
+)]
+pub(crate) trait Network {
+    fn connect(&self) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn name(&self) -> zbus::Result<String>;
+}
+
+#[proxy(
+    interface = "net.connman.iwd.KnownNetwork",
+    default_service = "net.connman.iwd"
+)]
+pub(crate) trait KnownNetwork {
+    fn forget(&self) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn auto_connect(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn set_auto_connect(&self, value: bool) -> zbus::Result<()>;
+}
+
+#[proxy(
+    interface = "net.connman.iwd.StationDiagnostic",
+    default_service = "net.connman.iwd"
+)]
+pub(crate) trait StationDiagnostic {
+    fn get_diagnostics(&self) -> zbus::Result<Properties>;
+}
+
+/// The unique bus name iwd currently owns, or `None` while it is not running.
+pub(crate) async fn iwd_owner(connection: &Connection) -> zbus::Result<Option<OwnedUniqueName>> {
+    let dbus = DBusProxy::new(connection).await?;
+    match dbus
+        .get_name_owner(BusName::from_static_str(IWD_SERVICE)?)
+        .await
+    {
+        Ok(owner) => Ok(Some(owner)),
+        Err(zbus::fdo::Error::NameHasNoOwner(_)) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -1,3 +1,104 @@
+pub mod demo;
 pub mod iwd;
+pub mod iwd_agent;
+pub mod iwd_config;
+pub mod iwd_proxy;
+pub mod netlink;
 pub mod networkd;
+pub mod networkmanager;
+pub mod privilege;
+pub mod sysfs;
 pub mod traits;
+pub mod wpa_supplicant;
+
+use crate::backend::{
+    demo::{DemoBackend, Fixture},
+    iwd::IwdBackend,
+    networkd::NetworkdBackend,
+    networkmanager::NetworkManagerBackend,
+    privilege::{PrivilegeConfig, PrivilegedRunner},
+    traits::{EthernetBackend, WifiBackend},
+    wpa_supplicant::WpaSupplicantBackend,
+};
+use anyhow::Result;
+use std::{path::Path, sync::Arc};
+use tokio::process::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    Iwd,
+    NetworkManager,
+    WpaSupplicant,
+    Demo,
+}
+
+impl BackendKind {
+    pub fn wifi_service(self) -> &'static str {
+        match self {
+            Self::Iwd => "iwd",
+            Self::NetworkManager => "NetworkManager",
+            Self::WpaSupplicant => "wpa_supplicant",
+            Self::Demo => "demo",
+        }
+    }
+}
+
+pub struct Backends {
+    pub kind: BackendKind,
+    pub wifi: Arc<dyn WifiBackend>,
+    pub ethernet: Arc<dyn EthernetBackend>,
+}
+
+impl Backends {
+    pub fn demo(fixture: &Path) -> Result<Self> {
+        let demo = Arc::new(DemoBackend::new(Fixture::load(fixture)?));
+        Ok(Self {
+            kind: BackendKind::Demo,
+            wifi: demo.clone(),
+            ethernet: demo,
+        })
+    }
+
+    pub async fn detect(privilege: PrivilegeConfig) -> Self {
+        let runner = Arc::new(PrivilegedRunner::new(privilege));
+
+        if is_service_active("NetworkManager.service").await {
+            let nm = Arc::new(NetworkManagerBackend::new());
+            return Self {
+                kind: BackendKind::NetworkManager,
+                wifi: nm.clone(),
+                ethernet: nm,
+            };
+        }
+
+        // Templated wpa_supplicant@<iface> units are common, so look for the control
+        // sockets rather than a particular unit name.
+        let wpa = WpaSupplicantBackend::new();
+        if !is_service_active("iwd.service").await && wpa.is_running() {
+            return Self {
+                kind: BackendKind::WpaSupplicant,
+                wifi: Arc::new(wpa),
+                ethernet: Arc::new(NetworkdBackend::new(runner)),
+            };
+        }
+
+        Self {
+            kind: BackendKind::Iwd,
+            wifi: Arc::new(IwdBackend::new().with_runner(Arc::clone(&runner))),
+            ethernet: Arc::new(NetworkdBackend::new(runner)),
+        }
+    }
+}
+
+pub async fn is_service_active(unit: &str) -> bool {
+    match Command::new("systemctl")
+        .arg("is-active")
+        .arg("--quiet")
+        .arg(unit)
+        .status()
+        .await
+    {
+        Ok(status) => status.success(),
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,511 @@
+use crate::{
+    domain::ethernet::{EthernetChange, EthernetIface, EthernetRoute},
+    event::{BackendEvent, Event},
+};
+use anyhow::{Context, Result};
+use futures::{StreamExt, TryStreamExt};
+use netlink_packet_route::{
+    AddressFamily, RouteNetlinkMessage,
+    address::{AddressAttribute, AddressMessage},
+    link::{LinkAttribute, LinkFlags, LinkLayerType, LinkMessage, State},
+    route::{RouteAddress, RouteAttribute, RouteMessage, RouteType},
+};
+use rtnetlink::{
+    Handle, LinkUnspec, MulticastGroup, RouteMessageBuilder, packet_core::NetlinkPayload,
+};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use tokio::sync::mpsc::UnboundedSender;
+
+const RT_TABLE_LOCAL: u8 = 255;
+// What RTM_GETLINK answers for a name no link has.
+const ENODEV: i32 = 19;
+
+const FLAG_NAMES: [(LinkFlags, &str); 9] = [
+    (LinkFlags::Up, "UP"),
+    (LinkFlags::Broadcast, "BROADCAST"),
+    (LinkFlags::Loopback, "LOOPBACK"),
+    (LinkFlags::Pointopoint, "POINTOPOINT"),
+    (LinkFlags::Running, "RUNNING"),
+    (LinkFlags::Noarp, "NOARP"),
+    (LinkFlags::Promisc, "PROMISC"),
+    (LinkFlags::Multicast, "MULTICAST"),
+    (LinkFlags::LowerUp, "LOWER_UP"),
+];
+
+pub async fn list_links() -> Result<Vec<EthernetIface>> {
+    let handle = connect()?;
+
+    let links: Vec<LinkMessage> = handle
+        .link()
+        .get()
+        .execute()
+        .try_collect()
+        .await
+        .context("RTM_GETLINK failed")?;
+    let addrs: Vec<AddressMessage> = handle
+        .address()
+        .get()
+        .execute()
+        .try_collect()
+        .await
+        .context("RTM_GETADDR failed")?;
+    let routes = routes(&handle, None).await?;
+
+    Ok(build_ifaces(&links, &addrs, &routes))
+}
+
+pub async fn link(name: &str) -> Result<EthernetIface> {
+    let handle = connect()?;
+
+    let links: Vec<LinkMessage> = match handle
+        .link()
+        .get()
+        .match_name(name)
+        .execute()
+        .try_collect()
+        .await
+    {
+        Ok(links) => links,
+        Err(rtnetlink::Error::NetlinkError(msg)) if msg.to_io().raw_os_error() == Some(ENODEV) => {
+            Vec::new()
+        }
+        Err(e) => return Err(e).context("RTM_GETLINK failed"),
+    };
+    let Some(ifindex) = links.first().map(|l| l.header.index) else {
+        return Err(std::io::Error::other(format!("interface not found: {name}")).into());
+    };
+    let addrs: Vec<AddressMessage> = handle
+        .address()
+        .get()
+        .set_link_index_filter(ifindex)
+        .execute()
+        .try_collect()
+        .await
+        .context("RTM_GETADDR failed")?;
+    let routes = routes(&handle, Some(ifindex)).await?;
+
+    build_ifaces(&links, &addrs, &routes)
+        .into_iter()
+        .next()
+        .ok_or_else(|| std::io::Error::other(format!("interface not found: {name}")).into())
+}
+
+pub async fn set_link_up(ifindex: u32, up: bool) -> std::io::Result<()> {
+    let handle = connect().map_err(std::io::Error::other)?;
+    let builder = LinkUnspec::new_with_index(ifindex);
+    let message = if up { builder.up() } else { builder.down() }.build();
+
+    handle
+        .link()
+        .set(message)
+        .execute()
+        .await
+        .map_err(|e| match e {
+            rtnetlink::Error::NetlinkError(msg) => msg.to_io(),
+            other => std::io::Error::other(other),
+        })
+}
+
+pub fn watch(events: UnboundedSender<Event>) -> bool {
+    let groups = [
+        MulticastGroup::Link,
+        MulticastGroup::Ipv4Ifaddr,
+        MulticastGroup::Ipv6Ifaddr,
+        MulticastGroup::Ipv4Route,
+        MulticastGroup::Ipv6Route,
+    ];
+    let Ok((connection, _handle, mut messages)) = rtnetlink::new_multicast_connection(&groups)
+    else {
+        return false;
+    };
+    tokio::spawn(connection);
+
+    tokio::spawn(async move {
+        while let Some((message, _)) = messages.next().await {
+            let NetlinkPayload::InnerMessage(message) = message.payload else {
+                continue;
+            };
+            let Some(change) = to_change(message) else {
+                continue;
+            };
+            if events
+                .send(Event::Backend(BackendEvent::EthernetChanged(change)))
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+    true
+}
+
+fn to_change(message: RouteNetlinkMessage) -> Option<EthernetChange> {
+    match message {
+        // Loopback, tunnels and other non-Ethernet links never show up in the table.
+        RouteNetlinkMessage::NewLink(link)
+            if link.header.link_layer_type == LinkLayerType::Ether =>
+        {
+            link_to_iface(&link).map(|i| EthernetChange::Link(Box::new(i)))
+        }
+        RouteNetlinkMessage::DelLink(link) => Some(EthernetChange::LinkRemoved(link.header.index)),
+        RouteNetlinkMessage::NewAddress(addr) => {
+            let ip = address_of(&addr)?;
+            Some(EthernetChange::AddressAdded {
+                ifindex: addr.header.index,
+                v6: addr.header.family == AddressFamily::Inet6,
+                cidr: format!("{ip}/{}", addr.header.prefix_len),
+            })
+        }
+        RouteNetlinkMessage::DelAddress(addr) => {
+            let ip = address_of(&addr)?;
+            Some(EthernetChange::AddressRemoved {
+                ifindex: addr.header.index,
+                v6: addr.header.family == AddressFamily::Inet6,
+                cidr: format!("{ip}/{}", addr.header.prefix_len),
+            })
+        }
+        RouteNetlinkMessage::NewRoute(route) if is_listed_route(&route) => {
+            Some(EthernetChange::RouteAdded {
+                ifindex: route_oif(&route)?,
+                v6: route.header.address_family == AddressFamily::Inet6,
+                route: to_route(&route),
+            })
+        }
+        RouteNetlinkMessage::DelRoute(route) if is_listed_route(&route) => {
+            Some(EthernetChange::RouteRemoved {
+                ifindex: route_oif(&route)?,
+                v6: route.header.address_family == AddressFamily::Inet6,
+                route: to_route(&route),
+            })
+        }
+        _ => None,
+    }
+}
+
+// Routes of both families, limited to one outgoing interface when `oif` is set.
+async fn routes(handle: &Handle, oif: Option<u32>) -> Result<Vec<RouteMessage>> {
+    let mut v4 = RouteMessageBuilder::<Ipv4Addr>::new();
+    let mut v6 = RouteMessageBuilder::<Ipv6Addr>::new();
+    if let Some(oif) = oif {
+        v4 = v4.output_interface(oif);
+        v6 = v6.output_interface(oif);
+    }
+
+    let mut routes: Vec<RouteMessage> = handle
+        .route()
+        .get(v4.build())
+        .execute()
+        .try_collect()
+        .await
+        .context("RTM_GETROUTE failed")?;
+    let routes_v6: Vec<RouteMessage> = handle
+        .route()
+        .get(v6.build())
+        .execute()
+        .try_collect()
+        .await
+        .context("RTM_GETROUTE failed")?;
+    routes.extend(routes_v6);
+    Ok(routes)
+}
+
+fn connect() -> Result<Handle> {
+    let (connection, handle, _) =
+        rtnetlink::new_connection().context("failed to open netlink socket")?;
+    tokio::spawn(connection);
+    Ok(handle)
+}
+
+pub(crate) fn build_ifaces(
+    links: &[LinkMessage],
+    addrs: &[AddressMessage],
+    routes: &[RouteMessage],
+) -> Vec<EthernetIface> {
+    let mut ifaces: Vec<EthernetIface> = links
+        .iter()
+        .filter(|l| !l.header.flags.contains(LinkFlags::Loopback))
+        .filter_map(|link| {
+            let ifindex = link.header.index;
+            let mut iface = link_to_iface(link)?;
+
+            for addr in addrs.iter().filter(|a| a.header.index == ifindex) {
+                let Some(ip) = address_of(addr) else {
+                    continue;
+                };
+                let cidr = format!("{ip}/{}", addr.header.prefix_len);
+                match addr.header.family {
+                    AddressFamily::Inet6 => iface.ipv6.push(cidr),
+                    _ => iface.ipv4.push(cidr),
+                }
+            }
+
+            for route in routes.iter().filter(|r| route_oif(r) == Some(ifindex)) {
+                if route.header.table == RT_TABLE_LOCAL || route.header.kind != RouteType::Unicast {
+                    continue;
+                }
+                let is_v6 = route.header.address_family == AddressFamily::Inet6;
+                let route = to_route(route);
+                if route.destination == "default" {
+                    let gateway = route.gateway.clone();
+                    if is_v6 {
+                        iface.gateway_v6 = iface.gateway_v6.or(gateway);
+                    } else {
+                        iface.gateway_v4 = iface.gateway_v4.or(gateway);
+                    }
+                }
+                iface.routes.push(route);
+            }
+
+            Some(iface)
+        })
+        .collect();
+
+    ifaces.sort_by(|a, b| a.name.cmp(&b.name));
+    ifaces
+}
+
+fn link_to_iface(link: &LinkMessage) -> Option<EthernetIface> {
+    let mut name = None;
+    let mut operstate = "?".to_string();
+    let mut carrier = None;
+    let mut mac = None;
+    let mut mtu = None;
+
+    for attr in &link.attributes {
+        match attr {
+            LinkAttribute::IfName(n) => name = Some(n.clone()),
+            LinkAttribute::OperState(s) => operstate = operstate_label(*s),
+            LinkAttribute::Carrier(c) => carrier = Some(*c != 0),
+            LinkAttribute::Address(bytes) if !bytes.is_empty() => mac = Some(format_mac(bytes)),
+            LinkAttribute::Mtu(m) => mtu = Some(*m),
+            _ => {}
+        }
+    }
+
+    Some(EthernetIface {
+        name: name?,
+        ifindex: link.header.index,
+        operstate,
+        carrier,
+        mac,
+        speed_mbps: None,
+        mtu,
+        flags: flag_labels(link.header.flags),
+        ipv4: Vec::new(),
+        ipv6: Vec::new(),
+        gateway_v4: None,
+        gateway_v6: None,
+        routes: Vec::new(),
+        dns: Vec::new(),
+        managed: None,
+    })
+}
+
+fn address_of(addr: &AddressMessage) -> Option<std::net::IpAddr> {
+    let local = addr.attributes.iter().find_map(|a| match a {
+        AddressAttribute::Local(ip) => Some(*ip),
+        _ => None,
+    });
+    local.or_else(|| {
+        addr.attributes.iter().find_map(|a| match a {
+            AddressAttribute::Address(ip) => Some(*ip),
+            _ => None,
+        })
+    })
+}
+
+fn is_listed_route(route: &RouteMessage) -> bool {
+    route.header.table != RT_TABLE_LOCAL && route.header.kind == RouteType::Unicast
+}
+
+fn route_oif(route: &RouteMessage) -> Option<u32> {
+    route.attributes.iter().find_map(|a| match a {
+        RouteAttribute::Oif(idx) => Some(*idx),
+        _ => None,
+    })
+}
+
+fn to_route(route: &RouteMessage) -> EthernetRoute {
+    let mut destination = None;
+    let mut gateway = None;
+    let mut metric = None;
+
+    for attr in &route.attributes {
+        match attr {
+            RouteAttribute::Destination(d) => destination = route_address(d),
+            RouteAttribute::Gateway(g) => gateway = route_address(g),
+            RouteAttribute::Priority(p) => metric = Some(*p),
+            _ => {}
+        }
+    }
+
+    let destination = match destination {
+        Some(d) if route.header.destination_prefix_length > 0 => {
+            format!("{d}/{}", route.header.destination_prefix_length)
+        }
+        _ => "default".to_string(),
+    };
+
+    EthernetRoute {
+        destination,
+        gateway,
+        metric,
+    }
+}
+
+fn route_address(addr: &RouteAddress) -> Option<String> {
+    match addr {
+        RouteAddress::Inet(ip) => Some(ip.to_string()),
+        RouteAddress::Inet6(ip) => Some(ip.to_string()),
+        _ => None,
+    }
+}
+
+fn operstate_label(state: State) -> String {
+    match state {
+        State::Up => "up",
+        State::Down => "down",
+        State::Dormant => "dormant",
+        State::LowerLayerDown => "lowerlayerdown",
+        State::NotPresent => "notpresent",
+        State::Testing => "testing",
+        _ => "unknown",
+    }
+    .to_string()
+}
+
+pub(crate) fn flag_labels(flags: LinkFlags) -> Vec<String> {
+    FLAG_NAMES
+        .iter()
+        .filter(|(flag, _)| flags.contains(*flag))
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+fn format_mac(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use netlink_packet_route::route::RouteHeader;
+    use std::net::IpAddr;
+
+    #[test]
+    fn only_ethernet_link_changes_are_reported() {
+        let mut eth = LinkMessage::default();
+        eth.header.index = 2;
+        eth.header.link_layer_type = LinkLayerType::Ether;
+        eth.attributes
+            .push(LinkAttribute::IfName("enp3s0".to_string()));
+        let mut tunnel = eth.clone();
+        tunnel.header.index = 7;
+        tunnel.header.link_layer_type = LinkLayerType::None;
+
+        assert!(matches!(
+            to_change(RouteNetlinkMessage::NewLink(eth)),
+            Some(EthernetChange::Link(link)) if link.ifindex == 2
+        ));
+        assert!(to_change(RouteNetlinkMessage::NewLink(tunnel.clone())).is_none());
+        assert!(matches!(
+            to_change(RouteNetlinkMessage::DelLink(tunnel)),
+            Some(EthernetChange::LinkRemoved(7))
+        ));
+    }
+
+    #[test]
+    fn build_ifaces_joins_addresses_and_routes_by_index() {
+        let mut lo = LinkMessage::default();
+        lo.header.index = 1;
+        lo.header.flags = LinkFlags::Loopback | LinkFlags::Up;
+        lo.attributes.push(LinkAttribute::IfName("lo".to_string()));
+
+        let mut eth = LinkMessage::default();
+        eth.header.index = 2;
+        eth.header.flags = LinkFlags::Up | LinkFlags::Broadcast | LinkFlags::LowerUp;
+        eth.attributes = vec![
+            LinkAttribute::IfName("enp3s0".to_string()),
+            LinkAttribute::OperState(State::Up),
+            LinkAttribute::Carrier(1),
+            LinkAttribute::Mtu(1500),
+            LinkAttribute::Address(vec![0x52, 0x54, 0, 0x12, 0x34, 0x56]),
+        ];
+
+        let mut v4 = AddressMessage::default();
+        v4.header.index = 2;
+        v4.header.family = AddressFamily::Inet;
+        v4.header.prefix_len = 24;
+        v4.attributes
+            .push(AddressAttribute::Local(IpAddr::from([192, 168, 1, 20])));
+        let mut v6 = AddressMessage::default();
+        v6.header.index = 2;
+        v6.header.family = AddressFamily::Inet6;
+        v6.header.prefix_len = 64;
+        v6.attributes
+            .push(AddressAttribute::Address("fe80::1".parse().unwrap()));
+
+        let default_v4 = RouteMessageBuilder::<Ipv4Addr>::new()
+            .output_interface(2)
+            .gateway(Ipv4Addr::new(192, 168, 1, 1))
+            .priority(100)
+            .build();
+        let subnet_v4 = RouteMessageBuilder::<Ipv4Addr>::new()
+            .output_interface(2)
+            .destination_prefix(Ipv4Addr::new(192, 168, 1, 0), 24)
+            .build();
+        let default_v6 = RouteMessageBuilder::<Ipv6Addr>::new()
+            .output_interface(2)
+            .gateway("fe80::ff".parse().unwrap())
+            .build();
+        let mut local = RouteMessageBuilder::<Ipv4Addr>::new()
+            .output_interface(2)
+            .destination_prefix(Ipv4Addr::new(192, 168, 1, 20), 32)
+            .build();
+        local.header.table = RT_TABLE_LOCAL;
+        assert_eq!(default_v4.header.table, RouteHeader::RT_TABLE_MAIN);
+
+        let ifaces = build_ifaces(
+            &[lo, eth],
+            &[v4, v6],
+            &[default_v4, subnet_v4, default_v6, local],
+        );
+        assert_eq!(ifaces.len(), 1);
+        let iface = &ifaces[0];
+        assert_eq!(iface.name, "enp3s0");
+        assert_eq!(iface.ifindex, 2);
+        assert_eq!(iface.operstate, "up");
+        assert_eq!(iface.carrier, Some(true));
+        assert_eq!(iface.mtu, Some(1500));
+        assert_eq!(iface.mac.as_deref(), Some("52:54:00:12:34:56"));
+        assert_eq!(iface.flags, vec!["UP", "BROADCAST", "LOWER_UP"]);
+        assert_eq!(iface.ipv4, vec!["192.168.1.20/24"]);
+        assert_eq!(iface.ipv6, vec!["fe80::1/64"]);
+        assert_eq!(iface.gateway_v4.as_deref(), Some("192.168.1.1"));
+        assert_eq!(iface.gateway_v6.as_deref(), Some("fe80::ff"));
+        assert_eq!(
+            iface.routes,
+            vec![
+                EthernetRoute {
+                    destination: "default".to_string(),
+                    gateway: Some("192.168.1.1".to_string()),
+                    metric: Some(100),
+                },
+                EthernetRoute {
+                    destination: "192.168.1.0/24".to_string(),
+                    gateway: None,
+                    metric: None,
+                },
+                EthernetRoute {
+                    destination: "default".to_string(),
+                    gateway: Some("fe80::ff".to_string()),
+                    metric: None,
+                },
+            ]
+        );
+    }
+}
@@ -1,391 +1,322 @@
 use crate::{
-    backend::traits::{CommandResult, EthernetBackend},
-    domain::ethernet::EthernetIface,
+    backend::{
+        netlink,
+        privilege::{Escalation, PrivilegedRunner},
+        sysfs::Sysfs,
+        traits::{CommandResult, EthernetBackend},
+    },
+    domain::ethernet::{EthernetIface, ManagedLinkState},
+    error::NettuiError,
+    event::Event,
 };
 use anyhow::{Context, Result};
-use if_addrs::IfAddr;
-use std::{fs, net::Ipv4Addr, path::Path};
-use tokio::process::Command;
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::{OnceCell, mpsc::UnboundedSender};
+use zbus::{Connection, proxy, proxy::CacheProperties, zvariant::OwnedObjectPath};
 
-pub struct NetworkdBackend;
+const SERVICE_NAME: &str = "systemd-networkd";
 
-impl NetworkdBackend {
-    pub fn new() -> Self {
-        Self
-    }
+#[proxy(
+    interface = "org.freedesktop.network1.Manager",
+    default_service = "org.freedesktop.network1",
+    default_path = "/org/freedesktop/network1"
+)]
+trait NetworkdManager {
+    fn get_link_by_name(&self, name: &str) -> zbus::Result<(i32, OwnedObjectPath)>;
 
-    pub async fn renew_dhcp(&self, iface: &str) -> Result<CommandResult> {
-        // Try plain first.
-        if let Ok(out) = Command::new("networkctl")
-            .arg("renew")
-            .arg(iface)
-            .output()
-            .await
-        {
-            if out.status.success() {
-                return Ok(CommandResult {
-                    program: "networkctl".to_string(),
-                    args: vec!["renew".to_string(), iface.to_string()],
-                    used_sudo: false,
-                    status: out.status.code().unwrap_or(0),
-                    stdout: String::from_utf8_lossy(&out.stdout).trim().to_string(),
-                    stderr: String::from_utf8_lossy(&out.stderr).trim().to_string(),
-                });
-            }
+    #[zbus(allow_interactive_auth)]
+    fn renew_link(&self, ifindex: i32) -> zbus::Result<()>;
 
-            let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
-            if stderr.contains("Operation not permitted")
-                || stderr.contains("Permission denied")
-                || out.status.code() == Some(1)
-            {
-                let pkexec_out = Command::new("pkexec")
-                    .arg("networkctl")
-                    .arg("renew")
-                    .arg(iface)
-                    .output()
-                    .await;
-
-                if let Ok(pkexec_out) = pkexec_out
-                    && pkexec_out.status.success()
-                {
-                    return Ok(CommandResult {
-                        program: "networkctl".to_string(),
-                        args: vec!["renew".to_string(), iface.to_string()],
-                        used_sudo: true,
-                        status: pkexec_out.status.code().unwrap_or(0),
-                        stdout: String::from_utf8_lossy(&pkexec_out.stdout)
-                            .trim()
-                            .to_string(),
-                        stderr: String::from_utf8_lossy(&pkexec_out.stderr)
-                            .trim()
-                            .to_string(),
-                    });
-                }
+    #[zbus(allow_interactive_auth)]
+    fn reconfigure_link(&self, ifindex: i32) -> zbus::Result<()>;
 
-                let sudo_out = Command::new("sudo")
-                    .arg("-n")
-                    .arg("networkctl")
-                    .arg("renew")
-                    .arg(iface)
-                    .output()
-                    .await?;
-
-                if sudo_out.status.success() {
-                    return Ok(CommandResult {
-                        program: "networkctl".to_string(),
-                        args: vec!["renew".to_string(), iface.to_string()],
-                        used_sudo: true,
-                        status: sudo_out.status.code().unwrap_or(0),
-                        stdout: String::from_utf8_lossy(&sudo_out.stdout).trim().to_string(),
-                        stderr: String::from_utf8_lossy(&sudo_out.stderr).trim().to_string(),
-                    });
-                }
+    #[zbus(allow_interactive_auth)]
+    fn force_renew_link(&self, ifindex: i32) -> zbus::Result<()>;
+}
 
-                return Err(std::io::Error::other(
-                    String::from_utf8_lossy(&sudo_out.stderr).trim().to_string(),
-                )
-                .into());
-            }
+#[proxy(
+    interface = "org.freedesktop.network1.Link",
+    default_service = "org.freedesktop.network1"
+)]
+trait NetworkdLink {
+    #[zbus(property)]
+    fn operational_state(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn address_state(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn administrative_state(&self) -> zbus::Result<String>;
+}
+
+#[derive(Debug, Clone, Copy)]
+enum LinkAction {
+    Renew,
+    Reconfigure,
+    ForceRenew,
+}
 
-            return Err(std::io::Error::other(if stderr.is_empty() {
-                "networkctl renew failed".to_string()
-            } else {
-                stderr
-            })
-            .into());
+impl LinkAction {
+    fn method(self) -> &'static str {
+        match self {
+            Self::Renew => "RenewLink",
+            Self::Reconfigure => "ReconfigureLink",
+            Self::ForceRenew => "ForceRenewLink",
         }
+    }
 
-        Err(std::io::Error::other("failed to run networkctl").into())
+    fn networkctl(self) -> &'static str {
+        match self {
+            Self::Renew => "renew",
+            Self::Reconfigure => "reconfigure",
+            Self::ForceRenew => "forcerenew",
+        }
     }
+}
 
-    pub async fn set_link_admin_state(&self, iface: &str, up: bool) -> Result<CommandResult> {
-        let state_arg = if up { "up" } else { "down" };
+pub struct NetworkdBackend {
+    runner: Arc<PrivilegedRunner>,
+    sysfs: Sysfs,
+    connection: OnceCell<Connection>,
+}
 
-        if let Ok(out) = Command::new("ip")
-            .arg("link")
-            .arg("set")
-            .arg("dev")
-            .arg(iface)
-            .arg(state_arg)
-            .output()
-            .await
-        {
-            if out.status.success() {
-                return Ok(CommandResult {
-                    program: "ip".to_string(),
-                    args: vec![
-                        "link".to_string(),
-                        "set".to_string(),
-                        "dev".to_string(),
-                        iface.to_string(),
-                        state_arg.to_string(),
-                    ],
-                    used_sudo: false,
-                    status: out.status.code().unwrap_or(0),
-                    stdout: String::from_utf8_lossy(&out.stdout).trim().to_string(),
-                    stderr: String::from_utf8_lossy(&out.stderr).trim().to_string(),
-                });
-            }
+impl NetworkdBackend {
+    pub fn new(runner: Arc<PrivilegedRunner>) -> Self {
+        Self::with_sysfs(runner, Sysfs::default())
+    }
 
-            let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
-            if stderr.contains("Operation not permitted")
-                || stderr.contains("Permission denied")
-                || out.status.code() == Some(1)
-            {
-                let pkexec_out = Command::new("pkexec")
-                    .arg("ip")
-                    .arg("link")
-                    .arg("set")
-                    .arg("dev")
-                    .arg(iface)
-                    .arg(state_arg)
-                    .output()
-                    .await;
-
-                if let Ok(pkexec_out) = pkexec_out
-                    && pkexec_out.status.success()
-                {
-                    return Ok(CommandResult {
-                        program: "ip".to_string(),
-                        args: vec![
-                            "link".to_string(),
-                            "set".to_string(),
-                            "dev".to_string(),
-                            iface.to_string(),
-                            state_arg.to_string(),
-                        ],
-                        used_sudo: true,
-                        status: pkexec_out.status.code().unwrap_or(0),
-                        stdout: String::from_utf8_lossy(&pkexec_out.stdout)
-                            .trim()
-                            .to_string(),
-                        stderr: String::from_utf8_lossy(&pkexec_out.stderr)
-                            .trim()
-                            .to_string(),
-                    });
-                }
+    pub fn with_sysfs(runner: Arc<PrivilegedRunner>, sysfs: Sysfs) -> Self {
+        Self {
+            runner,
+            sysfs,
+            connection: OnceCell::new(),
+        }
+    }
 
-                let sudo_out = Command::new("sudo")
-                    .arg("-n")
-                    .arg("ip")
-                    .arg("link")
-                    .arg("set")
-                    .arg("dev")
-                    .arg(iface)
-                    .arg(state_arg)
-                    .output()
-                    .await?;
-
-                if sudo_out.status.success() {
-                    return Ok(CommandResult {
-                        program: "ip".to_string(),
-                        args: vec![
-                            "link".to_string(),
-                            "set".to_string(),
-                            "dev".to_string(),
-                            iface.to_string(),
-                            state_arg.to_string(),
-                        ],
-                        used_sudo: true,
-                        status: sudo_out.status.code().unwrap_or(0),
-                        stdout: String::from_utf8_lossy(&sudo_out.stdout).trim().to_string(),
-                        stderr: String::from_utf8_lossy(&sudo_out.stderr).trim().to_string(),
-                    });
-                }
+    async fn manager(&self) -> Result<NetworkdManagerProxy<'static>> {
+        let conn = self
+            .connection
+            .get_or_try_init(|| async { Connection::system().await })
+            .await
+            .context("cannot access the system bus")?;
+        let proxy = NetworkdManagerProxy::builder(conn)
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        Ok(proxy)
+    }
 
-                return Err(std::io::Error::other(
-                    String::from_utf8_lossy(&sudo_out.stderr).trim().to_string(),
-                )
-                .into());
-            }
+    async fn managed_state(&self, iface: &str) -> Option<ManagedLinkState> {
+        let manager = self.manager().await.ok()?;
+        let (_, path) = manager.get_link_by_name(iface).await.ok()?;
+        let link = NetworkdLinkProxy::builder(manager.inner().connection())
+            .path(path)
+            .ok()?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await
+            .ok()?;
 
-            return Err(std::io::Error::other(if stderr.is_empty() {
-                format!("ip link set dev {iface} {state_arg} failed")
-            } else {
-                stderr
-            })
-            .into());
-        }
+        Some(ManagedLinkState {
+            operational: link.operational_state().await.ok()?,
+            address: link.address_state().await.ok()?,
+            setup: link.administrative_state().await.ok()?,
+        })
+    }
 
-        Err(std::io::Error::other("failed to run ip").into())
+    async fn with_managed_state(&self, mut iface: EthernetIface) -> EthernetIface {
+        iface.managed = self.managed_state(&iface.name).await;
+        iface
     }
 
-    pub fn iface_details(&self, iface: &str) -> Result<EthernetIface> {
-        let base = Path::new("/sys/class/net").join(iface);
-        if !base.exists() {
-            return Err(std::io::Error::other(format!("interface not found: {iface}")).into());
-        }
-        if iface == "lo" {
-            return Err(std::io::Error::other("loopback interface is not supported").into());
-        }
-        if !is_physical_iface(iface) {
-            return Err(std::io::Error::other(format!("not a physical interface: {iface}")).into());
+    async fn link_action(&self, iface: &str, action: LinkAction) -> Result<CommandResult> {
+        let fallback = [action.networkctl(), iface];
+        let manager = self.manager().await?;
+        let ifindex = match manager.get_link_by_name(iface).await {
+            Ok((ifindex, _)) => ifindex,
+            Err(e) => return Err(NettuiError::wrap_dbus(e, SERVICE_NAME, iface)),
+        };
+        if self.runner.dry_run() {
+            let native = format!(
+                "busctl call org.freedesktop.network1 /org/freedesktop/network1 \
+                 org.freedesktop.network1.Manager {} i {ifindex}",
+                action.method()
+            );
+            return Ok(self
+                .runner
+                .plan_with_fallback(&native, "networkctl", &fallback));
         }
 
-        build_iface(iface)
+        let call = match action {
+            LinkAction::Renew => manager.renew_link(ifindex).await,
+            LinkAction::Reconfigure => manager.reconfigure_link(ifindex).await,
+            LinkAction::ForceRenew => manager.force_renew_link(ifindex).await,
+        };
+
+        match call {
+            Ok(()) => Ok(CommandResult {
+                program: "networkd".to_string(),
+                args: vec![action.method().to_string(), iface.to_string()],
+                escalation: Escalation::None,
+                dry_run: false,
+                status: 0,
+                stdout: String::new(),
+                stderr: String::new(),
+            }),
+            Err(e) => match NettuiError::from_dbus(&e, SERVICE_NAME, iface) {
+                Some(NettuiError::PermissionDenied) => {
+                    self.runner.escalate("networkctl", &fallback).await
+                }
+                Some(typed) => Err(typed.into()),
+                None => Err(e).context(format!("systemd-networkd {} failed", action.method())),
+            },
+        }
     }
 }
 
 impl Default for NetworkdBackend {
     fn default() -> Self {
-        Self::new()
+        Self::new(Arc::new(PrivilegedRunner::default()))
     }
 }
 
+#[async_trait]
 impl EthernetBackend for NetworkdBackend {
-    fn list_ifaces(&self) -> Result<Vec<EthernetIface>> {
-        list_ethernet_ifaces()
+    async fn list_ifaces(&self) -> Result<Vec<EthernetIface>> {
+        // A non-host sysfs root does not describe the running kernel, so skip netlink there.
+        let links = if self.sysfs.is_host() {
+            netlink::list_links().await.ok()
+        } else {
+            None
+        };
+        let ifaces = match links {
+            Some(links) => links
+                .into_iter()
+                .filter(|i| self.sysfs.is_physical(&i.name) && !self.sysfs.is_wifi(&i.name))
+                .map(|i| with_sysfs_extras(&self.sysfs, i))
+                .collect(),
+            None => self.sysfs.ethernet_ifaces()?,
+        };
+
+        let mut out = Vec::with_capacity(ifaces.len());
+        for iface in ifaces {
+            out.push(self.with_managed_state(iface).await);
+        }
+        Ok(out)
     }
-}
 
-fn is_physical_iface(name: &str) -> bool {
-    Path::new("/sys/class/net")
-        .join(name)
-        .join("device")
-        .exists()
-}
-
-fn is_wifi_iface(name: &str) -> bool {
-    let p = Path::new("/sys/class/net").join(name);
-    p.join("wireless").is_dir() || p.join("phy80211").exists()
-}
-
-fn read_to_string(path: impl AsRef<Path>) -> Option<String> {
-    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
-}
-
-fn read_bool(path: impl AsRef<Path>) -> Option<bool> {
-    read_to_string(path).and_then(|s| match s.as_str() {
-        "0" => Some(false),
-        "1" => Some(true),
-        _ => None,
-    })
-}
+    async fn iface_details(&self, iface: &str) -> Result<EthernetIface> {
+        Ok(self
+            .with_managed_state(iface_details(&self.sysfs, iface).await?)
+            .await)
+    }
 
-fn read_u32(path: impl AsRef<Path>) -> Option<u32> {
-    read_to_string(path).and_then(|s| s.parse::<u32>().ok())
-}
+    async fn renew_dhcp(&self, iface: &str) -> Result<CommandResult> {
+        self.link_action(iface, LinkAction::Renew).await
+    }
 
-fn list_dns_servers() -> Vec<String> {
-    let resolv = fs::read_to_string("/etc/resolv.conf").unwrap_or_default();
-    resolv
-        .lines()
-        .filter_map(|line| {
-            let line = line.trim();
-            if line.starts_with("nameserver ") {
-                line.split_whitespace().nth(1).map(|s| s.to_string())
-            } else {
-                None
-            }
-        })
-        .collect()
-}
+    async fn reconfigure(&self, iface: &str) -> Result<CommandResult> {
+        self.link_action(iface, LinkAction::Reconfigure).await
+    }
 
-fn parse_default_gateway_v4_for_iface(iface: &str) -> Option<Ipv4Addr> {
-    let content = fs::read_to_string("/proc/net/route").ok()?;
-    for (i, line) in content.lines().enumerate() {
-        if i == 0 {
-            continue;
-        }
+    async fn force_renew(&self, iface: &str) -> Result<CommandResult> {
+        self.link_action(iface, LinkAction::ForceRenew).await
+    }
 
-        let cols: Vec<&str> = line.split_whitespace().collect();
-        if cols.len() < 3 {
-            continue;
+    async fn set_link_admin_state(&self, iface: &str, up: bool) -> Result<CommandResult> {
+        let state_arg = if up { "up" } else { "down" };
+        let fallback = ["link", "set", "dev", iface, state_arg];
+        let ifindex = netlink::link(iface).await?.ifindex;
+        if self.runner.dry_run() {
+            let native = format!("RTM_SETLINK ifindex {ifindex} {state_arg}");
+            return Ok(self.runner.plan_with_fallback(&native, "ip", &fallback));
         }
 
-        let ifname = cols[0];
-        let destination = cols[1];
-        let gateway_hex = cols[2];
-
-        if ifname != iface || destination != "00000000" {
-            continue;
+        match netlink::set_link_up(ifindex, up).await {
+            Ok(()) => Ok(CommandResult {
+                program: "rtnetlink".to_string(),
+                args: vec![
+                    "setlink".to_string(),
+                    iface.to_string(),
+                    state_arg.to_string(),
+                ],
+                escalation: Escalation::None,
+                dry_run: false,
+                status: 0,
+                stdout: String::new(),
+                stderr: String::new(),
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                self.runner.escalate("ip", &fallback).await
+            }
+            Err(e) => Err(e.into()),
         }
-
-        let gw = u32::from_str_radix(gateway_hex, 16).ok()?;
-        let b = gw.to_le_bytes();
-        return Some(Ipv4Addr::new(b[0], b[1], b[2], b[3]));
     }
 
-    None
+    fn watch(&self, events: UnboundedSender<Event>) -> bool {
+        netlink::watch(events)
+    }
 }
 
-fn list_ip_addrs_for_iface(iface: &str) -> Result<(Vec<String>, Vec<String>)> {
-    let ifas = if_addrs::get_if_addrs().context("get_if_addrs failed")?;
-    let mut v4 = Vec::new();
-    let mut v6 = Vec::new();
-
-    for ifa in ifas {
-        if ifa.name != iface {
-            continue;
-        }
-        match ifa.addr {
-            IfAddr::V4(a) => {
-                let prefix = v4_netmask_to_prefix(a.netmask);
-                v4.push(format!("{}/{}", a.ip, prefix));
-            }
-            IfAddr::V6(a) => {
-                let prefix = v6_netmask_to_prefix(a.netmask);
-                v6.push(format!("{}/{}", a.ip, prefix));
-            }
-        }
+pub(crate) async fn iface_details(sysfs: &Sysfs, iface: &str) -> Result<EthernetIface> {
+    if !sysfs.exists(iface) {
+        return Err(std::io::Error::other(format!("interface not found: {iface}")).into());
+    }
+    if iface == "lo" {
+        return Err(std::io::Error::other("loopback interface is not supported").into());
+    }
+    if !sysfs.is_physical(iface) {
+        return Err(std::io::Error::other(format!("not a physical interface: {iface}")).into());
     }
 
-    Ok((v4, v6))
+    load_iface(sysfs, iface).await
 }
 
-fn v4_netmask_to_prefix(mask: Ipv4Addr) -> u8 {
-    let bits = u32::from_be_bytes(mask.octets());
-    bits.count_ones() as u8
+pub(crate) async fn load_iface(sysfs: &Sysfs, name: &str) -> Result<EthernetIface> {
+    if !sysfs.is_host() {
+        return sysfs.iface(name);
+    }
+    match netlink::link(name).await {
+        Ok(iface) => Ok(with_sysfs_extras(sysfs, iface)),
+        Err(_) => sysfs.iface(name),
+    }
 }
 
-fn v6_netmask_to_prefix(mask: std::net::Ipv6Addr) -> u8 {
-    mask.octets()
-        .into_iter()
-        .map(|b| b.count_ones() as u16)
-        .sum::<u16>() as u8
+fn with_sysfs_extras(sysfs: &Sysfs, mut iface: EthernetIface) -> EthernetIface {
+    iface.speed_mbps = sysfs.speed_mbps(&iface.name);
+    iface.dns = sysfs.dns_servers();
+    iface
 }
 
-fn list_ethernet_ifaces() -> Result<Vec<EthernetIface>> {
-    let mut devices = Vec::new();
-
-    for entry in fs::read_dir("/sys/class/net").context("read_dir /sys/class/net failed")? {
-        let entry = entry?;
-        let name = entry.file_name().to_string_lossy().to_string();
-        if name == "lo" {
-            continue;
-        }
-
-        if !is_physical_iface(&name) || is_wifi_iface(&name) {
-            continue;
-        }
-
-        devices.push(build_iface(&name)?);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_networkd_call_errors() {
+        let denied = zbus::Error::FDO(Box::new(zbus::fdo::Error::AccessDenied(
+            "polkit".to_string(),
+        )));
+        assert_eq!(
+            NettuiError::from_dbus(&denied, SERVICE_NAME, "enp3s0"),
+            Some(NettuiError::PermissionDenied)
+        );
+
+        let gone = zbus::Error::FDO(Box::new(zbus::fdo::Error::ServiceUnknown(
+            "org.freedesktop.network1".to_string(),
+        )));
+        let err = NettuiError::wrap_dbus(gone, SERVICE_NAME, "enp3s0");
+        assert_eq!(
+            NettuiError::of(&err)
+                .unwrap()
+                .user_message("Renewing DHCP on enp3s0"),
+            "Renewing DHCP on enp3s0 failed: systemd-networkd is not running."
+        );
+
+        assert_eq!(
+            NettuiError::from_dbus(&zbus::Error::InvalidReply, SERVICE_NAME, "enp3s0"),
+            None
+        );
     }
-
-    devices.sort_by(|a, b| a.name.cmp(&b.name));
-    Ok(devices)
-}
-
-fn build_iface(name: &str) -> Result<EthernetIface> {
-    let base = Path::new("/sys/class/net").join(name);
-    let operstate = read_to_string(base.join("operstate")).unwrap_or_else(|| "?".into());
-    let carrier = read_bool(base.join("carrier"));
-    let mac = read_to_string(base.join("address"));
-    let speed_mbps = read_u32(base.join("speed"));
-
-    let (ipv4, ipv6) = list_ip_addrs_for_iface(name).unwrap_or_default();
-    let gateway_v4 = parse_default_gateway_v4_for_iface(name).map(|g| g.to_string());
-    let dns = list_dns_servers();
-
-    Ok(EthernetIface {
-        name: name.to_string(),
-        operstate,
-        carrier,
-        mac,
-        speed_mbps,
-        ipv4,
-        ipv6,
-        gateway_v4,
-        dns,
-    })
 }
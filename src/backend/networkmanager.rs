@@ -0,0 +1,1117 @@
+use crate::{
+    backend::{
+        netlink,
+        networkd::{iface_details, load_iface},
+        privilege::Escalation,
+        sysfs::Sysfs,
+        traits::{CommandResult, EthernetBackend, WifiBackend},
+    },
+    domain::{
+        ethernet::EthernetIface,
+        wifi::{WifiDeviceInfo, WifiNetwork, WifiState, pick_iface, signal_label},
+    },
+    error::NettuiError,
+    event::Event,
+};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures::StreamExt;
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::{OnceCell, mpsc::UnboundedSender};
+use zbus::{
+    Connection, proxy,
+    proxy::CacheProperties,
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Str, Value},
+};
+
+const SERVICE_NAME: &str = "NetworkManager";
+const NM_DEVICE_TYPE_ETHERNET: u32 = 1;
+const NM_DEVICE_TYPE_WIFI: u32 = 2;
+const NM_DEVICE_STATE_DISCONNECTED: u32 = 30;
+const NM_DEVICE_STATE_ACTIVATED: u32 = 100;
+const NM_DEVICE_STATE_FAILED: u32 = 120;
+
+const NM_DEVICE_STATE_REASON_NO_SECRETS: u32 = 7;
+const NM_DEVICE_STATE_REASON_SUPPLICANT_DISCONNECT: u32 = 8;
+const NM_DEVICE_STATE_REASON_NEW_ACTIVATION: u32 = 60;
+
+const NM_802_11_AP_FLAGS_PRIVACY: u32 = 0x1;
+const NM_802_11_AP_SEC_KEY_MGMT_PSK: u32 = 0x100;
+const NM_802_11_AP_SEC_KEY_MGMT_802_1X: u32 = 0x200;
+const NM_802_11_AP_SEC_KEY_MGMT_SAE: u32 = 0x400;
+
+const SECRET_SETTINGS: [&str; 2] = ["802-11-wireless-security", "802-1x"];
+
+type ConnectionSettings = HashMap<String, HashMap<String, OwnedValue>>;
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager",
+    default_service = "org.freedesktop.NetworkManager",
+    default_path = "/org/freedesktop/NetworkManager"
+)]
+trait NetworkManager {
+    fn get_devices(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+
+    fn activate_connection(
+        &self,
+        connection: &ObjectPath<'_>,
+        device: &ObjectPath<'_>,
+        specific_object: &ObjectPath<'_>,
+    ) -> zbus::Result<OwnedObjectPath>;
+
+    fn add_and_activate_connection(
+        &self,
+        connection: HashMap<&str, HashMap<&str, Value<'_>>>,
+        device: &ObjectPath<'_>,
+        specific_object: &ObjectPath<'_>,
+    ) -> zbus::Result<(OwnedObjectPath, OwnedObjectPath)>;
+
+    #[zbus(property)]
+    fn wireless_enabled(&self) -> zbus::Result<bool>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Device",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait Device {
+    fn disconnect(&self) -> zbus::Result<()>;
+
+    #[zbus(signal, name = "StateChanged")]
+    fn device_state_changed(&self, new_state: u32, old_state: u32, reason: u32)
+    -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn interface(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn device_type(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn state(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn active_connection(&self) -> zbus::Result<OwnedObjectPath>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Device.Wireless",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait Wireless {
+    fn get_all_access_points(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+
+    fn request_scan(&self, options: HashMap<&str, Value<'_>>) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn active_access_point(&self) -> zbus::Result<OwnedObjectPath>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.AccessPoint",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait AccessPoint {
+    #[zbus(property)]
+    fn ssid(&self) -> zbus::Result<Vec<u8>>;
+
+    #[zbus(property)]
+    fn strength(&self) -> zbus::Result<u8>;
+
+    #[zbus(property)]
+    fn flags(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn wpa_flags(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn rsn_flags(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn frequency(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn hw_address(&self) -> zbus::Result<String>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Connection.Active",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait ActiveConnection {
+    #[zbus(property)]
+    fn connection(&self) -> zbus::Result<OwnedObjectPath>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Settings",
+    default_service = "org.freedesktop.NetworkManager",
+    default_path = "/org/freedesktop/NetworkManager/Settings"
+)]
+trait Settings {
+    fn list_connections(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Settings.Connection",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+trait SettingsConnection {
+    fn get_settings(&self) -> zbus::Result<ConnectionSettings>;
+
+    fn get_secrets(&self, setting_name: &str) -> zbus::Result<ConnectionSettings>;
+
+    fn update(&self, properties: HashMap<&str, HashMap<&str, Value<'_>>>) -> zbus::Result<()>;
+
+    fn delete(&self) -> zbus::Result<()>;
+}
+
+pub struct NetworkManagerBackend {
+    sysfs: Sysfs,
+    connection: OnceCell<Connection>,
+}
+
+impl NetworkManagerBackend {
+    pub fn new() -> Self {
+        Self {
+            sysfs: Sysfs::default(),
+            connection: OnceCell::new(),
+        }
+    }
+
+    async fn conn(&self) -> Result<&Connection> {
+        self.connection
+            .get_or_try_init(|| async { Connection::system().await })
+            .await
+            .context("cannot access NetworkManager service")
+    }
+
+    async fn manager(&self) -> Result<NetworkManagerProxy<'static>> {
+        let proxy = NetworkManagerProxy::builder(self.conn().await?)
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        Ok(proxy)
+    }
+
+    async fn device(&self, path: &OwnedObjectPath) -> Result<DeviceProxy<'static>> {
+        let proxy = DeviceProxy::builder(self.conn().await?)
+            .path(path.clone())?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        Ok(proxy)
+    }
+
+    async fn devices_of_type(&self, device_type: u32) -> Result<Vec<(String, OwnedObjectPath)>> {
+        let mut out = Vec::new();
+        let devices = self
+            .manager()
+            .await?
+            .get_devices()
+            .await
+            .map_err(|e| NettuiError::wrap_dbus(e, SERVICE_NAME, ""))?;
+        for path in devices {
+            let device = self.device(&path).await?;
+            if device.device_type().await.unwrap_or(0) != device_type {
+                continue;
+            }
+            if let Ok(iface) = device.interface().await {
+                out.push((iface, path));
+            }
+        }
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(out)
+    }
+
+    async fn wifi_device(&self, iface: &str) -> Result<OwnedObjectPath> {
+        self.devices_of_type(NM_DEVICE_TYPE_WIFI)
+            .await?
+            .into_iter()
+            .find(|(name, _)| name == iface)
+            .map(|(_, path)| path)
+            .with_context(|| format!("no wifi device found for {iface}"))
+    }
+
+    async fn ethernet_device(&self, iface: &str) -> Result<OwnedObjectPath> {
+        self.devices_of_type(NM_DEVICE_TYPE_ETHERNET)
+            .await?
+            .into_iter()
+            .find(|(name, _)| name == iface)
+            .map(|(_, path)| path)
+            .with_context(|| format!("NetworkManager does not manage {iface}"))
+    }
+
+    async fn wireless(&self, path: &OwnedObjectPath) -> Result<WirelessProxy<'static>> {
+        let proxy = WirelessProxy::builder(self.conn().await?)
+            .path(path.clone())?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        Ok(proxy)
+    }
+
+    async fn access_points(&self, device: &OwnedObjectPath) -> Result<Vec<ApInfo>> {
+        let wireless = self.wireless(device).await?;
+        let mut out = Vec::new();
+        for path in wireless.get_all_access_points().await? {
+            if let Ok(ap) = self.access_point(&path).await {
+                out.push(ap);
+            }
+        }
+        Ok(out)
+    }
+
+    async fn access_point(&self, path: &OwnedObjectPath) -> Result<ApInfo> {
+        let proxy = AccessPointProxy::builder(self.conn().await?)
+            .path(path.clone())?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+
+        let ssid = String::from_utf8_lossy(&proxy.ssid().await.unwrap_or_default()).to_string();
+        let flags = proxy.flags().await.unwrap_or(0);
+        let wpa_flags = proxy.wpa_flags().await.unwrap_or(0);
+        let rsn_flags = proxy.rsn_flags().await.unwrap_or(0);
+
+        Ok(ApInfo {
+            path: path.clone(),
+            ssid,
+            bssid: proxy.hw_address().await.unwrap_or_default(),
+            strength: proxy.strength().await.unwrap_or(0),
+            frequency_mhz: proxy.frequency().await.unwrap_or(0),
+            security: ap_security(flags, wpa_flags, rsn_flags).to_string(),
+        })
+    }
+
+    async fn settings_connection(
+        &self,
+        path: &OwnedObjectPath,
+    ) -> Result<SettingsConnectionProxy<'static>> {
+        let proxy = SettingsConnectionProxy::builder(self.conn().await?)
+            .path(path.clone())?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        Ok(proxy)
+    }
+
+    async fn saved_wifi_connections(&self) -> Result<Vec<SavedWifi>> {
+        let settings = SettingsProxy::builder(self.conn().await?)
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+
+        let mut out = Vec::new();
+        for path in settings.list_connections().await? {
+            let Ok(conn) = self.settings_connection(&path).await else {
+                continue;
+            };
+            let Ok(settings) = conn.get_settings().await else {
+                continue;
+            };
+            if let Some(saved) = parse_saved_wifi(path, &settings) {
+                out.push(saved);
+            }
+        }
+        Ok(out)
+    }
+
+    async fn find_saved(&self, ssid: &str) -> Result<Vec<SavedWifi>> {
+        Ok(self
+            .saved_wifi_connections()
+            .await?
+            .into_iter()
+            .filter(|s| s.ssid == ssid)
+            .collect())
+    }
+
+    async fn add_and_activate(
+        &self,
+        iface: &str,
+        ssid: &str,
+        passphrase: Option<&str>,
+        security: &str,
+        hidden: bool,
+    ) -> Result<()> {
+        let device = self.wifi_device(iface).await?;
+        let specific = if hidden {
+            None
+        } else {
+            self.access_points(&device)
+                .await?
+                .into_iter()
+                .filter(|ap| ap.ssid == ssid)
+                .max_by_key(|ap| ap.strength)
+                .map(|ap| ap.path)
+        };
+
+        let mut wireless = HashMap::new();
+        wireless.insert("ssid", Value::from(ssid.as_bytes().to_vec()));
+        if hidden {
+            wireless.insert("hidden", Value::from(true));
+        }
+
+        let mut settings = HashMap::new();
+        settings.insert("802-11-wireless", wireless);
+        if let Some(passphrase) = passphrase {
+            let key_mgmt = if security == "sae" { "sae" } else { "wpa-psk" };
+            let mut sec = HashMap::new();
+            sec.insert("key-mgmt", Value::from(key_mgmt));
+            sec.insert("psk", Value::from(passphrase));
+            settings.insert("802-11-wireless-security", sec);
+        }
+
+        let root = ObjectPath::try_from("/")?;
+        let specific = specific
+            .as_ref()
+            .map(|p| p.as_ref())
+            .unwrap_or_else(|| root.clone());
+        let manager = self.manager().await?;
+        let activate = async {
+            manager
+                .add_and_activate_connection(settings, &device.as_ref(), &specific)
+                .await
+                .map_err(|e| NettuiError::wrap_dbus(e, SERVICE_NAME, ssid))?;
+            Ok(())
+        };
+        self.activate_and_wait(&device, ssid, passphrase.is_some(), activate)
+            .await
+    }
+
+    async fn activate_saved(
+        &self,
+        device: &OwnedObjectPath,
+        saved: &SavedWifi,
+        passphrase_tried: bool,
+    ) -> Result<()> {
+        let root = ObjectPath::try_from("/")?;
+        let manager = self.manager().await?;
+        let activate = async {
+            manager
+                .activate_connection(&saved.path.as_ref(), &device.as_ref(), &root)
+                .await
+                .map_err(|e| NettuiError::wrap_dbus(e, SERVICE_NAME, &saved.ssid))?;
+            Ok(())
+        };
+        self.activate_and_wait(device, &saved.ssid, passphrase_tried, activate)
+            .await
+    }
+
+    // NM accepts an activation request long before the handshake runs, so follow the
+    // device until it either comes up or gives a reason for failing.
+    async fn activate_and_wait(
+        &self,
+        device: &OwnedObjectPath,
+        ssid: &str,
+        passphrase_tried: bool,
+        activate: impl Future<Output = Result<()>>,
+    ) -> Result<()> {
+        let mut changes = self
+            .device(device)
+            .await?
+            .receive_device_state_changed()
+            .await?;
+        activate.await?;
+        while let Some(change) = changes.next().await {
+            let args = change.args()?;
+            if let Some(outcome) =
+                activation_outcome(args.new_state, args.reason, ssid, passphrase_tried)
+            {
+                return outcome.map_err(Into::into);
+            }
+        }
+        Err(NettuiError::ConnectFailed.into())
+    }
+
+    // Update replaces the whole connection and GetSettings leaves secrets out, so the
+    // secrets are fetched and merged back in before `edit` runs.
+    async fn update_saved(
+        &self,
+        saved: &SavedWifi,
+        edit: impl FnOnce(&mut ConnectionSettings),
+    ) -> Result<()> {
+        let wrap = |e| NettuiError::wrap_dbus(e, SERVICE_NAME, &saved.ssid);
+        let conn = self.settings_connection(&saved.path).await?;
+        let settings = conn.get_settings().await.map_err(wrap)?;
+
+        // Agent-owned secrets never live in the profile, and a group whose secrets cannot
+        // be read is left to NM rather than failing the whole update.
+        let mut secrets = Vec::new();
+        for group in SECRET_SETTINGS {
+            if settings.contains_key(group)
+                && let Ok(values) = conn.get_secrets(group).await
+            {
+                secrets.push(values);
+            }
+        }
+        let mut settings = merge_secrets(settings, secrets);
+        edit(&mut settings);
+
+        let mut update: HashMap<&str, HashMap<&str, Value<'_>>> = HashMap::new();
+        for (group, values) in &settings {
+            let entry = update.entry(group.as_str()).or_default();
+            for (key, value) in values {
+                entry.insert(key.as_str(), Value::from(value.try_clone()?));
+            }
+        }
+        conn.update(update).await.map_err(wrap)?;
+        Ok(())
+    }
+
+    async fn active_settings_path(&self, device: &OwnedObjectPath) -> Result<OwnedObjectPath> {
+        let active = self.device(device).await?.active_connection().await?;
+        if active.as_str() == "/" {
+            return Err(std::io::Error::other("no active connection on device").into());
+        }
+        let proxy = ActiveConnectionProxy::builder(self.conn().await?)
+            .path(active)?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        Ok(proxy.connection().await?)
+    }
+}
+
+impl Default for NetworkManagerBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl WifiBackend for NetworkManagerBackend {
+    async fn query_state(&self, iface: Option<&str>) -> Result<WifiState> {
+        let devices = self.devices_of_type(NM_DEVICE_TYPE_WIFI).await?;
+        let ifaces: Vec<String> = devices.iter().map(|(name, _)| name.clone()).collect();
+        let Some(selected) = pick_iface(&ifaces, iface) else {
+            return Ok(WifiState::empty());
+        };
+
+        let powered = match self.manager().await?.wireless_enabled().await {
+            Ok(true) => "On".to_string(),
+            Ok(false) => "Off".to_string(),
+            Err(_) => "-".to_string(),
+        };
+
+        let mut adapters = Vec::new();
+        let mut connected_ssid = None;
+        let mut aps = Vec::new();
+        for (name, device_path) in &devices {
+            let device_state = self.device(device_path).await?.state().await.unwrap_or(0);
+            let active_ap_path = self
+                .wireless(device_path)
+                .await?
+                .active_access_point()
+                .await
+                .ok();
+            let device_aps = self.access_points(device_path).await?;
+            let active_ap = active_ap_path
+                .as_ref()
+                .and_then(|p| device_aps.iter().find(|ap| &ap.path == p))
+                .filter(|_| device_state == NM_DEVICE_STATE_ACTIVATED)
+                .cloned();
+
+            let (frequency, security) = match &active_ap {
+                Some(ap) => (
+                    format!("{:.2} GHz", ap.frequency_mhz as f32 / 1000.0),
+                    ap.security.clone(),
+                ),
+                None => ("-".to_string(), "-".to_string()),
+            };
+            adapters.push(WifiDeviceInfo {
+                iface: name.clone(),
+                mode: "station".to_string(),
+                powered: powered.clone(),
+                state: device_state_label(device_state).to_string(),
+                scanning: "-".to_string(),
+                frequency,
+                security,
+            });
+
+            if *name == selected {
+                connected_ssid = active_ap.map(|ap| ap.ssid);
+                aps = device_aps;
+            }
+        }
+
+        let saved = self.saved_wifi_connections().await.unwrap_or_default();
+        let saved_by_ssid: HashMap<&str, &SavedWifi> =
+            saved.iter().map(|s| (s.ssid.as_str(), s)).collect();
+
+        let mut best: HashMap<String, ApInfo> = HashMap::new();
+        let mut hidden_networks = Vec::new();
+        for ap in aps {
+            if ap.ssid.is_empty() {
+                hidden_networks.push(WifiNetwork {
+                    ssid: ap.bssid.clone(),
+                    security: ap.security.clone(),
+                    signal: signal_label(ap.strength as i16),
+                    connected: false,
+                    hidden: Some(true),
+                    autoconnect: None,
+                    available: false,
+                    last_connected: None,
+                });
+                continue;
+            }
+            match best.get(&ap.ssid) {
+                Some(current) if current.strength >= ap.strength => {}
+                _ => {
+                    best.insert(ap.ssid.clone(), ap);
+                }
+            }
+        }
+        hidden_networks.sort_by(|a, b| a.ssid.cmp(&b.ssid));
+
+        let mut known_networks = Vec::new();
+        let mut new_networks = Vec::new();
+        let mut available_names = HashSet::new();
+        for (ssid, ap) in best {
+            let connected = connected_ssid.as_deref() == Some(ssid.as_str());
+            let signal = signal_label(ap.strength as i16);
+            if let Some(saved) = saved_by_ssid.get(ssid.as_str()) {
+                available_names.insert(ssid.clone());
+                known_networks.push(WifiNetwork {
+                    ssid,
+                    security: ap.security,
+                    signal,
+                    connected,
+                    hidden: Some(saved.hidden),
+                    autoconnect: Some(saved.autoconnect),
+                    available: true,
+                    last_connected: saved.last_connected,
+                });
+            } else {
+                new_networks.push(WifiNetwork {
+                    ssid,
+                    security: ap.security,
+                    signal,
+                    connected,
+                    hidden: None,
+                    autoconnect: None,
+                    available: true,
+                    last_connected: None,
+                });
+            }
+        }
+        known_networks.sort_by(|a, b| a.ssid.cmp(&b.ssid));
+        new_networks.sort_by(|a, b| a.ssid.cmp(&b.ssid));
+
+        let mut unavailable_known_networks: Vec<WifiNetwork> = saved
+            .iter()
+            .filter(|s| !available_names.contains(&s.ssid))
+            .map(|s| WifiNetwork {
+                ssid: s.ssid.clone(),
+                security: s.security.clone(),
+                signal: "-".to_string(),
+                connected: false,
+                hidden: Some(s.hidden),
+                autoconnect: Some(s.autoconnect),
+                available: false,
+                last_connected: s.last_connected,
+            })
+            .collect();
+        unavailable_known_networks.sort_by(|a, b| a.ssid.cmp(&b.ssid));
+        unavailable_known_networks.dedup_by(|a, b| a.ssid == b.ssid);
+
+        Ok(WifiState {
+            ifaces,
+            selected_iface: Some(selected),
+            connected_ssid,
+            known_networks,
+            unavailable_known_networks,
+            new_networks,
+            hidden_networks,
+            adapters,
+        })
+    }
+
+    async fn scan(&self, iface: &str) -> Result<()> {
+        let device = self.wifi_device(iface).await?;
+        self.wireless(&device)
+            .await?
+            .request_scan(HashMap::new())
+            .await
+            .map_err(|e| NettuiError::wrap_dbus(e, SERVICE_NAME, ""))?;
+        Ok(())
+    }
+
+    async fn connect(&self, iface: &str, ssid: &str) -> Result<()> {
+        let device = self.wifi_device(iface).await?;
+        if let Some(saved) = self.find_saved(ssid).await?.into_iter().next() {
+            return self.activate_saved(&device, &saved, false).await;
+        }
+
+        let ap = self
+            .access_points(&device)
+            .await?
+            .into_iter()
+            .find(|ap| ap.ssid == ssid)
+            .ok_or_else(|| NettuiError::NetworkNotFound(ssid.to_string()))?;
+        if ap.security != "open" {
+            return Err(NettuiError::PassphraseRequired(ssid.to_string()).into());
+        }
+        self.add_and_activate(iface, ssid, None, &ap.security, false)
+            .await
+    }
+
+    async fn connect_hidden(&self, iface: &str, ssid: &str) -> Result<()> {
+        self.add_and_activate(iface, ssid, None, "open", true).await
+    }
+
+    async fn connect_with_passphrase(
+        &self,
+        iface: &str,
+        ssid: &str,
+        passphrase: &str,
+    ) -> Result<()> {
+        let device = self.wifi_device(iface).await?;
+        let security = self
+            .access_points(&device)
+            .await?
+            .into_iter()
+            .find(|ap| ap.ssid == ssid)
+            .map(|ap| ap.security)
+            .unwrap_or_else(|| "psk".to_string());
+
+        // Retrying a passphrase edits the profile from the first attempt instead of
+        // adding another one for the same SSID.
+        let Some(saved) = self.find_saved(ssid).await?.into_iter().next() else {
+            return self
+                .add_and_activate(iface, ssid, Some(passphrase), &security, false)
+                .await;
+        };
+        self.update_saved(&saved, |settings| {
+            set_passphrase(settings, &security, passphrase)
+        })
+        .await?;
+        self.activate_saved(&device, &saved, true).await
+    }
+
+    async fn disconnect(&self, iface: &str) -> Result<()> {
+        let device = self.wifi_device(iface).await?;
+        self.device(&device)
+            .await?
+            .disconnect()
+            .await
+            .map_err(|e| NettuiError::wrap_dbus(e, SERVICE_NAME, ""))?;
+        Ok(())
+    }
+
+    async fn forget_known(&self, _iface: &str, ssid: &str) -> Result<()> {
+        let saved = self.find_saved(ssid).await?;
+        if saved.is_empty() {
+            return Err(NettuiError::KnownNetworkNotFound(ssid.to_string()).into());
+        }
+        for s in saved {
+            self.settings_connection(&s.path)
+                .await?
+                .delete()
+                .await
+                .map_err(|e| NettuiError::wrap_dbus(e, SERVICE_NAME, ssid))?;
+        }
+        Ok(())
+    }
+
+    async fn toggle_autoconnect(&self, _iface: &str, ssid: &str) -> Result<bool> {
+        let saved = self
+            .find_saved(ssid)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| NettuiError::KnownNetworkNotFound(ssid.to_string()))?;
+        let next = !saved.autoconnect;
+        self.update_saved(&saved, |settings| {
+            set_setting(settings, "connection", "autoconnect", next)
+        })
+        .await?;
+        Ok(next)
+    }
+}
+
+#[async_trait]
+impl EthernetBackend for NetworkManagerBackend {
+    async fn list_ifaces(&self) -> Result<Vec<EthernetIface>> {
+        let mut out = Vec::new();
+        for (iface, _) in self.devices_of_type(NM_DEVICE_TYPE_ETHERNET).await? {
+            if let Ok(loaded) = load_iface(&self.sysfs, &iface).await {
+                out.push(loaded);
+            }
+        }
+        Ok(out)
+    }
+
+    async fn iface_details(&self, iface: &str) -> Result<EthernetIface> {
+        iface_details(&self.sysfs, iface).await
+    }
+
+    async fn renew_dhcp(&self, iface: &str) -> Result<CommandResult> {
+        let device = self.ethernet_device(iface).await?;
+        let settings = self.active_settings_path(&device).await?;
+        let root = ObjectPath::try_from("/")?;
+        self.manager()
+            .await?
+            .activate_connection(&settings.as_ref(), &device.as_ref(), &root)
+            .await
+            .map_err(|e| NettuiError::wrap_dbus(e, SERVICE_NAME, ""))?;
+        Ok(dbus_result("ActivateConnection", iface))
+    }
+
+    async fn set_link_admin_state(&self, iface: &str, up: bool) -> Result<CommandResult> {
+        let device = self.ethernet_device(iface).await?;
+        if up {
+            let root = ObjectPath::try_from("/")?;
+            self.manager()
+                .await?
+                .activate_connection(&root, &device.as_ref(), &root)
+                .await
+                .map_err(|e| NettuiError::wrap_dbus(e, SERVICE_NAME, ""))?;
+            Ok(dbus_result("ActivateConnection", iface))
+        } else {
+            self.device(&device)
+                .await?
+                .disconnect()
+                .await
+                .map_err(|e| NettuiError::wrap_dbus(e, SERVICE_NAME, ""))?;
+            Ok(dbus_result("Disconnect", iface))
+        }
+    }
+
+    fn watch(&self, events: UnboundedSender<Event>) -> bool {
+        netlink::watch(events)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ApInfo {
+    path: OwnedObjectPath,
+    ssid: String,
+    bssid: String,
+    strength: u8,
+    frequency_mhz: u32,
+    security: String,
+}
+
+#[derive(Debug, Clone)]
+struct SavedWifi {
+    path: OwnedObjectPath,
+    ssid: String,
+    security: String,
+    hidden: bool,
+    autoconnect: bool,
+    last_connected: Option<SystemTime>,
+}
+
+fn parse_saved_wifi(path: OwnedObjectPath, settings: &ConnectionSettings) -> Option<SavedWifi> {
+    let connection = settings.get("connection")?;
+    let kind = setting_str(connection, "type")?;
+    if kind != "802-11-wireless" {
+        return None;
+    }
+
+    let wireless = settings.get("802-11-wireless")?;
+    let ssid_bytes = wireless
+        .get("ssid")
+        .and_then(|v| v.try_clone().ok())
+        .and_then(|v| Vec::<u8>::try_from(v).ok())?;
+    let ssid = String::from_utf8_lossy(&ssid_bytes).to_string();
+    if ssid.is_empty() {
+        return None;
+    }
+
+    let key_mgmt = settings
+        .get("802-11-wireless-security")
+        .and_then(|sec| setting_str(sec, "key-mgmt"));
+
+    Some(SavedWifi {
+        path,
+        ssid,
+        security: key_mgmt_security(key_mgmt.as_deref()).to_string(),
+        hidden: setting_bool(wireless, "hidden").unwrap_or(false),
+        autoconnect: setting_bool(connection, "autoconnect").unwrap_or(true),
+        // Seconds since the epoch of the last successful activation; 0 means never.
+        last_connected: connection
+            .get("timestamp")
+            .and_then(|v| u64::try_from(v).ok())
+            .filter(|secs| *secs > 0)
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+    })
+}
+
+fn setting_str(group: &HashMap<String, OwnedValue>, key: &str) -> Option<String> {
+    group
+        .get(key)
+        .and_then(|v| <&str>::try_from(v).ok())
+        .map(|s| s.to_string())
+}
+
+fn setting_bool(group: &HashMap<String, OwnedValue>, key: &str) -> Option<bool> {
+    group.get(key).and_then(|v| bool::try_from(v).ok())
+}
+
+fn ap_security(flags: u32, wpa_flags: u32, rsn_flags: u32) -> &'static str {
+    let sec = wpa_flags | rsn_flags;
+    if sec & NM_802_11_AP_SEC_KEY_MGMT_802_1X != 0 {
+        "8021x"
+    } else if sec & NM_802_11_AP_SEC_KEY_MGMT_SAE != 0 && sec & NM_802_11_AP_SEC_KEY_MGMT_PSK == 0 {
+        "sae"
+    } else if sec & (NM_802_11_AP_SEC_KEY_MGMT_PSK | NM_802_11_AP_SEC_KEY_MGMT_SAE) != 0 {
+        "psk"
+    } else if flags & NM_802_11_AP_FLAGS_PRIVACY != 0 {
+        "wep"
+    } else {
+        "open"
+    }
+}
+
+fn key_mgmt_security(key_mgmt: Option<&str>) -> &'static str {
+    match key_mgmt {
+        None => "open",
+        Some("wpa-psk") => "psk",
+        Some("sae") => "sae",
+        Some("wpa-eap") | Some("wpa-eap-suite-b-192") => "8021x",
+        Some("owe") => "owe",
+        Some(_) => "wep",
+    }
+}
+
+fn merge_secrets(
+    mut settings: ConnectionSettings,
+    secrets: Vec<ConnectionSettings>,
+) -> ConnectionSettings {
+    for (group, values) in secrets.into_iter().flatten() {
+        settings.entry(group).or_default().extend(values);
+    }
+    settings
+}
+
+fn set_setting(
+    settings: &mut ConnectionSettings,
+    group: &str,
+    key: &str,
+    value: impl Into<OwnedValue>,
+) {
+    settings
+        .entry(group.to_string())
+        .or_default()
+        .insert(key.to_string(), value.into());
+}
+
+fn set_passphrase(settings: &mut ConnectionSettings, security: &str, passphrase: &str) {
+    let group = "802-11-wireless-security";
+    let has_key_mgmt = settings
+        .get(group)
+        .is_some_and(|g| g.contains_key("key-mgmt"));
+    if !has_key_mgmt {
+        let key_mgmt = if security == "sae" { "sae" } else { "wpa-psk" };
+        set_setting(
+            settings,
+            group,
+            "key-mgmt",
+            OwnedValue::from(Str::from(key_mgmt)),
+        );
+    }
+    set_setting(
+        settings,
+        group,
+        "psk",
+        OwnedValue::from(Str::from(passphrase.to_string())),
+    );
+}
+
+fn activation_outcome(
+    state: u32,
+    reason: u32,
+    ssid: &str,
+    passphrase_tried: bool,
+) -> Option<Result<(), NettuiError>> {
+    match (state, reason) {
+        (NM_DEVICE_STATE_ACTIVATED, _) => Some(Ok(())),
+        (
+            NM_DEVICE_STATE_FAILED,
+            NM_DEVICE_STATE_REASON_NO_SECRETS | NM_DEVICE_STATE_REASON_SUPPLICANT_DISCONNECT,
+        ) if passphrase_tried => Some(Err(NettuiError::WrongPassphrase(ssid.to_string()))),
+        (
+            NM_DEVICE_STATE_FAILED,
+            NM_DEVICE_STATE_REASON_NO_SECRETS | NM_DEVICE_STATE_REASON_SUPPLICANT_DISCONNECT,
+        ) => Some(Err(NettuiError::PassphraseRequired(ssid.to_string()))),
+        (NM_DEVICE_STATE_FAILED, _) => Some(Err(NettuiError::ConnectFailed)),
+        // Switching networks passes through DISCONNECTED; any other drop ends the attempt.
+        (NM_DEVICE_STATE_DISCONNECTED, NM_DEVICE_STATE_REASON_NEW_ACTIVATION) => None,
+        (NM_DEVICE_STATE_DISCONNECTED, _) => Some(Err(NettuiError::Aborted)),
+        _ => None,
+    }
+}
+
+fn device_state_label(state: u32) -> &'static str {
+    match state {
+        10 => "unmanaged",
+        20 => "unavailable",
+        30 => "disconnected",
+        40..=90 => "connecting",
+        100 => "connected",
+        110 => "disconnecting",
+        120 => "failed",
+        _ => "-",
+    }
+}
+
+fn dbus_result(method: &str, iface: &str) -> CommandResult {
+    CommandResult {
+        program: "NetworkManager".to_string(),
+        args: vec![method.to_string(), iface.to_string()],
+        escalation: Escalation::None,
+        dry_run: false,
+        status: 0,
+        stdout: String::new(),
+        stderr: String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ap_security_prefers_enterprise_then_psk() {
+        assert_eq!(ap_security(0, 0, 0), "open");
+        assert_eq!(ap_security(NM_802_11_AP_FLAGS_PRIVACY, 0, 0), "wep");
+        assert_eq!(ap_security(1, 0, NM_802_11_AP_SEC_KEY_MGMT_PSK), "psk");
+        assert_eq!(ap_security(1, 0, NM_802_11_AP_SEC_KEY_MGMT_SAE), "sae");
+        assert_eq!(
+            ap_security(
+                1,
+                NM_802_11_AP_SEC_KEY_MGMT_PSK,
+                NM_802_11_AP_SEC_KEY_MGMT_802_1X
+            ),
+            "8021x"
+        );
+    }
+
+    #[test]
+    fn saved_wifi_is_parsed_from_connection_settings() {
+        let mut connection = HashMap::new();
+        connection.insert(
+            "type".to_string(),
+            OwnedValue::try_from(Value::from("802-11-wireless")).unwrap(),
+        );
+        connection.insert(
+            "autoconnect".to_string(),
+            OwnedValue::try_from(Value::from(false)).unwrap(),
+        );
+        connection.insert(
+            "timestamp".to_string(),
+            OwnedValue::try_from(Value::from(1_700_000_000u64)).unwrap(),
+        );
+        let mut wireless = HashMap::new();
+        wireless.insert(
+            "ssid".to_string(),
+            OwnedValue::try_from(Value::from(b"Office".to_vec())).unwrap(),
+        );
+        let mut security = HashMap::new();
+        security.insert(
+            "key-mgmt".to_string(),
+            OwnedValue::try_from(Value::from("wpa-psk")).unwrap(),
+        );
+
+        let mut settings = HashMap::new();
+        settings.insert("connection".to_string(), connection);
+        settings.insert("802-11-wireless".to_string(), wireless);
+        settings.insert("802-11-wireless-security".to_string(), security);
+
+        let path = OwnedObjectPath::try_from("/org/freedesktop/NetworkManager/Settings/1").unwrap();
+        let saved = parse_saved_wifi(path, &settings).unwrap();
+        assert_eq!(saved.ssid, "Office");
+        assert_eq!(saved.security, "psk");
+        assert!(!saved.autoconnect);
+        assert!(!saved.hidden);
+        assert_eq!(
+            saved.last_connected,
+            Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+        );
+    }
+
+    #[test]
+    fn settings_update_keeps_settings_and_secrets() {
+        let value = |v: Value<'_>| OwnedValue::try_from(v).unwrap();
+        let group = |pairs: Vec<(&str, OwnedValue)>| -> HashMap<String, OwnedValue> {
+            pairs.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+        };
+        let settings = HashMap::from([
+            (
+                "connection".to_string(),
+                group(vec![
+                    ("id", value(Value::from("Office"))),
+                    ("autoconnect", value(Value::from(true))),
+                ]),
+            ),
+            (
+                "ipv4".to_string(),
+                group(vec![("method", value(Value::from("manual")))]),
+            ),
+            (
+                "802-11-wireless-security".to_string(),
+                group(vec![("key-mgmt", value(Value::from("wpa-psk")))]),
+            ),
+        ]);
+        let secrets = HashMap::from([(
+            "802-11-wireless-security".to_string(),
+            group(vec![("psk", value(Value::from("hunter22")))]),
+        )]);
+
+        let mut update = merge_secrets(settings, vec![secrets]);
+        set_setting(&mut update, "connection", "autoconnect", false);
+        let get = |g: &str, k: &str| update[g][k].try_clone().unwrap();
+        assert_eq!(get("connection", "autoconnect"), OwnedValue::from(false));
+        assert_eq!(get("connection", "id"), value(Value::from("Office")));
+        assert_eq!(get("ipv4", "method"), value(Value::from("manual")));
+        let security = &update["802-11-wireless-security"];
+        assert_eq!(security.len(), 2);
+        assert_eq!(
+            get("802-11-wireless-security", "psk"),
+            value(Value::from("hunter22"))
+        );
+    }
+
+    #[test]
+    fn passphrase_update_adds_key_mgmt_once() {
+        let mut settings = ConnectionSettings::new();
+        set_passphrase(&mut settings, "sae", "hunter22");
+        let security = &settings["802-11-wireless-security"];
+        assert_eq!(security["key-mgmt"], OwnedValue::from(Str::from("sae")));
+        assert_eq!(security["psk"], OwnedValue::from(Str::from("hunter22")));
+
+        set_passphrase(&mut settings, "psk", "hunter23");
+        let security = &settings["802-11-wireless-security"];
+        assert_eq!(security["key-mgmt"], OwnedValue::from(Str::from("sae")));
+        assert_eq!(security["psk"], OwnedValue::from(Str::from("hunter23")));
+    }
+
+    #[test]
+    fn activation_outcome_maps_failure_reasons() {
+        let outcome = |state, reason, tried| activation_outcome(state, reason, "Home", tried);
+        assert_eq!(outcome(70, 0, true), None);
+        assert_eq!(outcome(100, 0, true), Some(Ok(())));
+        assert_eq!(
+            outcome(120, NM_DEVICE_STATE_REASON_NO_SECRETS, false),
+            Some(Err(NettuiError::PassphraseRequired("Home".to_string())))
+        );
+        assert_eq!(
+            outcome(120, NM_DEVICE_STATE_REASON_SUPPLICANT_DISCONNECT, true),
+            Some(Err(NettuiError::WrongPassphrase("Home".to_string())))
+        );
+        assert_eq!(
+            outcome(120, 11, true),
+            Some(Err(NettuiError::ConnectFailed))
+        );
+        assert_eq!(
+            outcome(30, NM_DEVICE_STATE_REASON_NEW_ACTIVATION, true),
+            None
+        );
+        assert_eq!(outcome(30, 39, true), Some(Err(NettuiError::Aborted)));
+    }
+}
@@ -0,0 +1,287 @@
+use crate::{backend::traits::CommandResult, error::NettuiError};
+use anyhow::Result;
+use serde::Deserialize;
+use std::{env, fs, path::PathBuf};
+use tokio::process::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Escalation {
+    None,
+    Pkexec,
+    Sudo,
+    Doas,
+    Run0,
+}
+
+impl Escalation {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Pkexec => "pkexec",
+            Self::Sudo => "sudo",
+            Self::Doas => "doas",
+            Self::Run0 => "run0",
+        }
+    }
+
+    pub fn wrap(self, program: &str, args: &[String]) -> (String, Vec<String>) {
+        let (wrapper, flags): (&str, &[&str]) = match self {
+            Self::None => return (program.to_string(), args.to_vec()),
+            Self::Pkexec => ("pkexec", &[]),
+            Self::Sudo => ("sudo", &["-n"]),
+            Self::Doas => ("doas", &["-n"]),
+            Self::Run0 => ("run0", &[]),
+        };
+
+        let mut wrapped: Vec<String> = flags.iter().map(|f| f.to_string()).collect();
+        wrapped.push(program.to_string());
+        wrapped.extend(args.iter().cloned());
+        (wrapper.to_string(), wrapped)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivilegeConfig {
+    pub order: Vec<Escalation>,
+    pub dry_run: bool,
+}
+
+impl Default for PrivilegeConfig {
+    fn default() -> Self {
+        Self {
+            order: vec![Escalation::None, Escalation::Pkexec, Escalation::Sudo],
+            dry_run: false,
+        }
+    }
+}
+
+impl PrivilegeConfig {
+    pub fn load() -> Self {
+        let Some(path) = config_path() else {
+            return Self::default();
+        };
+        let Ok(raw) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        Self::parse(&raw)
+    }
+
+    fn parse(raw: &str) -> Self {
+        let mut out = Self::default();
+        let Ok(file) = toml::from_str::<ConfigFile>(raw) else {
+            return out;
+        };
+        let Some(privilege) = file.privilege else {
+            return out;
+        };
+
+        if let Some(order) = privilege.order
+            && !order.is_empty()
+        {
+            out.order = order;
+        }
+        if let Some(dry_run) = privilege.dry_run {
+            out.dry_run = dry_run;
+        }
+        out
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ConfigFile {
+    privilege: Option<PrivilegePartial>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PrivilegePartial {
+    order: Option<Vec<Escalation>>,
+    dry_run: Option<bool>,
+}
+
+fn config_path() -> Option<PathBuf> {
+    let home = env::var_os("HOME")?;
+    Some(PathBuf::from(home).join(".config/nettui/config.toml"))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PrivilegedRunner {
+    config: PrivilegeConfig,
+}
+
+impl PrivilegedRunner {
+    pub fn new(config: PrivilegeConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.config.dry_run
+    }
+
+    // Callers make the call natively first and only come here once it was denied, so the
+    // decision to escalate never rests on a command's exit code or output.
+    pub async fn escalate(&self, program: &str, args: &[&str]) -> Result<CommandResult> {
+        if self.config.dry_run {
+            return Ok(self.plan(program, args));
+        }
+
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        let mut last_error = None;
+        for escalation in self.escalations() {
+            let (wrapper, wrapped) = escalation.wrap(program, &args);
+            let out = match Command::new(&wrapper).args(&wrapped).output().await {
+                Ok(out) => out,
+                Err(e) => {
+                    last_error = Some(NettuiError::CommandFailed {
+                        command: command_line(escalation, program, &args),
+                        stderr: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+            if out.status.success() {
+                return Ok(CommandResult {
+                    program: program.to_string(),
+                    args,
+                    escalation,
+                    dry_run: false,
+                    status: out.status.code().unwrap_or(0),
+                    stdout: String::from_utf8_lossy(&out.stdout).trim().to_string(),
+                    stderr,
+                });
+            }
+
+            // Only a refused or unusable wrapper moves on to the next method; anything
+            // else is the command's own failure and would fail the same way again.
+            if !authorization_failed(out.status.code()) {
+                return Err(NettuiError::CommandFailed {
+                    command: command_line(escalation, program, &args),
+                    stderr,
+                }
+                .into());
+            }
+            last_error = Some(NettuiError::PermissionDenied);
+        }
+
+        Err(last_error
+            .unwrap_or_else(|| NettuiError::CommandFailed {
+                command: program.to_string(),
+                stderr: "no privilege escalation method configured".to_string(),
+            })
+            .into())
+    }
+}
+
+impl PrivilegedRunner {
+    /// The dry-run result for a native call, described by `native`, that falls back to
+    /// the escalated `program` when it is denied.
+    pub fn plan_with_fallback(&self, native: &str, program: &str, args: &[&str]) -> CommandResult {
+        let mut out = self.plan(program, args);
+        out.stdout = format!("{native}; if denied: {}", out.stdout);
+        out
+    }
+
+    fn plan(&self, program: &str, args: &[&str]) -> CommandResult {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        let escalations = self.escalations();
+        let planned: Vec<String> = escalations
+            .iter()
+            .map(|e| command_line(*e, program, &args))
+            .collect();
+        CommandResult {
+            program: program.to_string(),
+            args,
+            escalation: escalations.first().copied().unwrap_or(Escalation::None),
+            dry_run: true,
+            status: 0,
+            stdout: planned.join(" || "),
+            stderr: String::new(),
+        }
+    }
+
+    fn escalations(&self) -> Vec<Escalation> {
+        self.config
+            .order
+            .iter()
+            .copied()
+            .filter(|e| *e != Escalation::None)
+            .collect()
+    }
+}
+
+pub fn command_line(escalation: Escalation, program: &str, args: &[String]) -> String {
+    let (wrapper, wrapped) = escalation.wrap(program, args);
+    std::iter::once(wrapper)
+        .chain(wrapped)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// pkexec and run0 exit 126 when authorization is dismissed or refused and 127 when it
+// could not be obtained; a killed wrapper has no exit code at all.
+fn authorization_failed(code: Option<i32>) -> bool {
+    matches!(code, None | Some(126 | 127))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_order_and_dry_run() {
+        let config = PrivilegeConfig::parse(
+            r#"
+            [privilege]
+            order = ["doas", "run0"]
+            dry_run = true
+            "#,
+        );
+        assert_eq!(config.order, vec![Escalation::Doas, Escalation::Run0]);
+        assert!(config.dry_run);
+
+        assert_eq!(
+            PrivilegeConfig::parse("[privilege]\norder = []\n"),
+            PrivilegeConfig::default()
+        );
+        assert_eq!(
+            PrivilegeConfig::parse("[privilege]\norder = [\"su\"]\n"),
+            PrivilegeConfig::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn dry_run_reports_planned_commands_without_running() {
+        let runner = PrivilegedRunner::new(PrivilegeConfig {
+            order: vec![Escalation::None, Escalation::Pkexec, Escalation::Sudo],
+            dry_run: true,
+        });
+
+        let out = runner
+            .escalate("ip", &["link", "set", "dev", "enp3s0", "down"])
+            .await
+            .unwrap();
+        assert!(out.dry_run);
+        assert_eq!(out.escalation, Escalation::Pkexec);
+        assert_eq!(
+            out.stdout,
+            "pkexec ip link set dev enp3s0 down || sudo -n ip link set dev enp3s0 down"
+        );
+
+        let out = runner.plan_with_fallback("RTM_SETLINK enp3s0 down", "ip", &["link"]);
+        assert_eq!(
+            out.stdout,
+            "RTM_SETLINK enp3s0 down; if denied: pkexec ip link || sudo -n ip link"
+        );
+    }
+
+    #[test]
+    fn only_refused_authorization_tries_the_next_method() {
+        assert!(authorization_failed(Some(126)));
+        assert!(authorization_failed(Some(127)));
+        assert!(authorization_failed(None));
+        assert!(!authorization_failed(Some(1)));
+        assert!(!authorization_failed(Some(2)));
+    }
+}
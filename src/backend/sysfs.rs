@@ -0,0 +1,293 @@
+use crate::{
+    backend::netlink,
+    domain::ethernet::{EthernetIface, EthernetRoute},
+};
+use anyhow::{Context, Result};
+use if_addrs::IfAddr;
+use std::{
+    fs,
+    net::Ipv4Addr,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sysfs {
+    sys: PathBuf,
+    proc: PathBuf,
+    etc: PathBuf,
+}
+
+impl Default for Sysfs {
+    fn default() -> Self {
+        Self::new("/sys", "/proc", "/etc")
+    }
+}
+
+impl Sysfs {
+    pub fn new(sys: impl Into<PathBuf>, proc: impl Into<PathBuf>, etc: impl Into<PathBuf>) -> Self {
+        Self {
+            sys: sys.into(),
+            proc: proc.into(),
+            etc: etc.into(),
+        }
+    }
+
+    pub fn under(root: impl AsRef<Path>) -> Self {
+        let root = root.as_ref();
+        Self::new(root.join("sys"), root.join("proc"), root.join("etc"))
+    }
+
+    pub fn is_host(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn class_net(&self) -> PathBuf {
+        self.sys.join("class/net")
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        self.class_net().join(name).exists()
+    }
+
+    pub fn is_physical(&self, name: &str) -> bool {
+        self.class_net().join(name).join("device").exists()
+    }
+
+    pub fn is_wifi(&self, name: &str) -> bool {
+        let p = self.class_net().join(name);
+        p.join("wireless").is_dir() || p.join("phy80211").exists()
+    }
+
+    pub fn speed_mbps(&self, name: &str) -> Option<u32> {
+        read_u32(self.class_net().join(name).join("speed"))
+    }
+
+    pub fn dns_servers(&self) -> Vec<String> {
+        parse_resolv_conf(&fs::read_to_string(self.etc.join("resolv.conf")).unwrap_or_default())
+    }
+
+    pub fn routes_v4(&self, name: &str) -> Vec<EthernetRoute> {
+        parse_routes_v4_for_iface(
+            &fs::read_to_string(self.proc.join("net/route")).unwrap_or_default(),
+            name,
+        )
+    }
+
+    pub fn wifi_ifaces(&self) -> Vec<String> {
+        let mut out: Vec<String> = self
+            .iface_names()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|name| self.is_wifi(name))
+            .collect();
+        out.sort();
+        out
+    }
+
+    pub fn ethernet_ifaces(&self) -> Result<Vec<EthernetIface>> {
+        let names = self
+            .iface_names()
+            .with_context(|| format!("read_dir {} failed", self.class_net().display()))?;
+
+        let mut devices = Vec::new();
+        for name in names {
+            if !self.is_physical(&name) || self.is_wifi(&name) {
+                continue;
+            }
+            devices.push(self.iface(&name)?);
+        }
+
+        devices.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(devices)
+    }
+
+    pub fn iface(&self, name: &str) -> Result<EthernetIface> {
+        let base = self.class_net().join(name);
+        let operstate = read_to_string(base.join("operstate")).unwrap_or_else(|| "?".into());
+        let carrier = read_bool(base.join("carrier"));
+        let mac = read_to_string(base.join("address"));
+        let speed_mbps = read_u32(base.join("speed"));
+        let ifindex = read_u32(base.join("ifindex")).unwrap_or(0);
+        let mtu = read_u32(base.join("mtu"));
+        let flags = read_to_string(base.join("flags"))
+            .map(|f| parse_link_flags(&f))
+            .unwrap_or_default();
+
+        let (ipv4, ipv6) = list_ip_addrs_for_iface(name).unwrap_or_default();
+        let routes = self.routes_v4(name);
+        let gateway_v4 = routes
+            .iter()
+            .find(|r| r.destination == "default")
+            .and_then(|r| r.gateway.clone());
+
+        Ok(EthernetIface {
+            name: name.to_string(),
+            ifindex,
+            operstate,
+            carrier,
+            mac,
+            speed_mbps,
+            mtu,
+            flags,
+            ipv4,
+            ipv6,
+            gateway_v4,
+            gateway_v6: None,
+            routes,
+            dns: self.dns_servers(),
+            managed: None,
+        })
+    }
+
+    fn iface_names(&self) -> std::io::Result<Vec<String>> {
+        Ok(fs::read_dir(self.class_net())?
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| name != "lo")
+            .collect())
+    }
+}
+
+fn read_to_string(path: impl AsRef<Path>) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+fn read_bool(path: impl AsRef<Path>) -> Option<bool> {
+    read_to_string(path).and_then(|s| match s.as_str() {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    })
+}
+
+fn read_u32(path: impl AsRef<Path>) -> Option<u32> {
+    read_to_string(path).and_then(|s| s.parse::<u32>().ok())
+}
+
+fn parse_resolv_conf(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.starts_with("nameserver ") {
+                line.split_whitespace().nth(1).map(|s| s.to_string())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn parse_routes_v4_for_iface(content: &str, iface: &str) -> Vec<EthernetRoute> {
+    let mut routes = Vec::new();
+    for line in content.lines().skip(1) {
+        let cols: Vec<&str> = line.split_whitespace().collect();
+        if cols.len() < 8 || cols[0] != iface {
+            continue;
+        }
+
+        let (Some(destination), Some(gateway), Some(mask)) =
+            (hex_ipv4(cols[1]), hex_ipv4(cols[2]), hex_ipv4(cols[7]))
+        else {
+            continue;
+        };
+        let prefix = v4_netmask_to_prefix(mask);
+
+        routes.push(EthernetRoute {
+            destination: if prefix == 0 {
+                "default".to_string()
+            } else {
+                format!("{destination}/{prefix}")
+            },
+            gateway: (!gateway.is_unspecified()).then(|| gateway.to_string()),
+            metric: cols[6].parse().ok(),
+        });
+    }
+
+    routes
+}
+
+fn hex_ipv4(hex: &str) -> Option<Ipv4Addr> {
+    let b = u32::from_str_radix(hex, 16).ok()?.to_le_bytes();
+    Some(Ipv4Addr::new(b[0], b[1], b[2], b[3]))
+}
+
+fn parse_link_flags(hex: &str) -> Vec<String> {
+    let Ok(bits) = u32::from_str_radix(hex.trim_start_matches("0x"), 16) else {
+        return Vec::new();
+    };
+    netlink::flag_labels(netlink_packet_route::link::LinkFlags::from_bits_retain(
+        bits,
+    ))
+}
+
+fn list_ip_addrs_for_iface(iface: &str) -> Result<(Vec<String>, Vec<String>)> {
+    let ifas = if_addrs::get_if_addrs().context("get_if_addrs failed")?;
+    let mut v4 = Vec::new();
+    let mut v6 = Vec::new();
+
+    for ifa in ifas {
+        if ifa.name != iface {
+            continue;
+        }
+        match ifa.addr {
+            IfAddr::V4(a) => {
+                let prefix = v4_netmask_to_prefix(a.netmask);
+                v4.push(format!("{}/{}", a.ip, prefix));
+            }
+            IfAddr::V6(a) => {
+                let prefix = v6_netmask_to_prefix(a.netmask);
+                v6.push(format!("{}/{}", a.ip, prefix));
+            }
+        }
+    }
+
+    Ok((v4, v6))
+}
+
+fn v4_netmask_to_prefix(mask: Ipv4Addr) -> u8 {
+    let bits = u32::from_be_bytes(mask.octets());
+    bits.count_ones() as u8
+}
+
+fn v6_netmask_to_prefix(mask: std::net::Ipv6Addr) -> u8 {
+    mask.octets()
+        .into_iter()
+        .map(|b| b.count_ones() as u16)
+        .sum::<u16>() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_proc_net_route_for_iface() {
+        let content = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+enp3s0\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0
+enp3s0\t0001A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0
+wlan0\t00000000\t0100000A\t0003\t0\t0\t600\t00000000\t0\t0\t0
+";
+        let routes = parse_routes_v4_for_iface(content, "enp3s0");
+        assert_eq!(
+            routes,
+            vec![
+                EthernetRoute {
+                    destination: "default".to_string(),
+                    gateway: Some("192.168.1.1".to_string()),
+                    metric: Some(100),
+                },
+                EthernetRoute {
+                    destination: "192.168.1.0/24".to_string(),
+                    gateway: None,
+                    metric: Some(100),
+                },
+            ]
+        );
+        assert_eq!(
+            parse_link_flags("0x11043"),
+            vec!["UP", "BROADCAST", "RUNNING", "MULTICAST", "LOWER_UP"]
+        );
+    }
+}
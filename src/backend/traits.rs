@@ -1,16 +1,109 @@
-use crate::domain::ethernet::EthernetIface;
+use crate::{
+    backend::privilege::{self, Escalation},
+    domain::{
+        enterprise::EnterpriseConfig,
+        ethernet::EthernetIface,
+        profile::KnownProfile,
+        wifi::{WifiSecret, WifiState},
+    },
+    event::Event,
+};
 use anyhow::Result;
+use async_trait::async_trait;
+use tokio::sync::mpsc::UnboundedSender;
 
 #[derive(Debug, Clone)]
 pub struct CommandResult {
     pub program: String,
     pub args: Vec<String>,
-    pub used_sudo: bool,
+    pub escalation: Escalation,
+    pub dry_run: bool,
     pub status: i32,
     pub stdout: String,
     pub stderr: String,
 }
 
-pub trait EthernetBackend {
-    fn list_ifaces(&self) -> Result<Vec<EthernetIface>>;
+impl CommandResult {
+    pub fn command_line(&self) -> String {
+        privilege::command_line(self.escalation, &self.program, &self.args)
+    }
+
+    pub fn dry_run_summary(&self) -> String {
+        if self.stdout.is_empty() {
+            self.command_line()
+        } else {
+            self.stdout.clone()
+        }
+    }
+}
+
+#[async_trait]
+pub trait EthernetBackend: Send + Sync {
+    async fn list_ifaces(&self) -> Result<Vec<EthernetIface>>;
+    async fn iface_details(&self, iface: &str) -> Result<EthernetIface>;
+    async fn renew_dhcp(&self, iface: &str) -> Result<CommandResult>;
+    async fn set_link_admin_state(&self, iface: &str, up: bool) -> Result<CommandResult>;
+
+    async fn reconfigure(&self, _iface: &str) -> Result<CommandResult> {
+        Err(std::io::Error::other("reconfigure is not supported by this backend").into())
+    }
+
+    async fn force_renew(&self, _iface: &str) -> Result<CommandResult> {
+        Err(std::io::Error::other("force renew is not supported by this backend").into())
+    }
+
+    fn watch(&self, _events: UnboundedSender<Event>) -> bool {
+        false
+    }
+}
+
+#[async_trait]
+pub trait WifiBackend: Send + Sync {
+    async fn query_state(&self, iface: Option<&str>) -> Result<WifiState>;
+    async fn scan(&self, iface: &str) -> Result<()>;
+    async fn connect(&self, iface: &str, ssid: &str) -> Result<()>;
+    async fn connect_hidden(&self, iface: &str, ssid: &str) -> Result<()>;
+    async fn connect_with_passphrase(
+        &self,
+        iface: &str,
+        ssid: &str,
+        passphrase: &str,
+    ) -> Result<()>;
+    async fn disconnect(&self, iface: &str) -> Result<()>;
+    async fn cancel_connect(&self, iface: &str) -> Result<()> {
+        self.disconnect(iface).await
+    }
+    async fn forget_known(&self, iface: &str, ssid: &str) -> Result<()>;
+    async fn toggle_autoconnect(&self, iface: &str, ssid: &str) -> Result<bool>;
+
+    async fn provision_enterprise(
+        &self,
+        _ssid: &str,
+        _config: &EnterpriseConfig,
+    ) -> Result<CommandResult> {
+        Err(std::io::Error::other("802.1X provisioning is only supported with iwd").into())
+    }
+
+    async fn read_known_profile(&self, _ssid: &str, _security: &str) -> Result<KnownProfile> {
+        Err(std::io::Error::other("Network profiles can only be edited with iwd").into())
+    }
+
+    async fn write_known_profile(
+        &self,
+        _ssid: &str,
+        _security: &str,
+        _profile: &KnownProfile,
+    ) -> Result<CommandResult> {
+        Err(std::io::Error::other("Network profiles can only be edited with iwd").into())
+    }
+
+    async fn read_passphrase(&self, _ssid: &str, _security: &str) -> Result<Option<String>> {
+        Err(std::io::Error::other("Reading stored passphrases is only supported with iwd").into())
+    }
+
+    fn watch(&self, _events: UnboundedSender<Event>) -> bool {
+        false
+    }
+
+    fn answer_secret(&self, _id: u64, _secret: Option<WifiSecret>) {}
 }
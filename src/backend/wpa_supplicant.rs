@@ -0,0 +1,964 @@
+use crate::{
+    backend::traits::WifiBackend,
+    domain::wifi::{WifiDeviceInfo, WifiNetwork, WifiState, pick_iface, signal_label},
+    error::NettuiError,
+};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+use tokio::net::UnixDatagram;
+
+const DEFAULT_CTRL_DIR: &str = "/run/wpa_supplicant";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+static CLIENT_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+pub struct WpaSupplicantBackend {
+    ctrl_dir: PathBuf,
+}
+
+impl WpaSupplicantBackend {
+    pub fn new() -> Self {
+        Self::with_ctrl_dir(DEFAULT_CTRL_DIR)
+    }
+
+    pub fn with_ctrl_dir(ctrl_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            ctrl_dir: ctrl_dir.into(),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.ifaces().is_empty()
+    }
+
+    fn ifaces(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        let Ok(entries) = fs::read_dir(&self.ctrl_dir) else {
+            return out;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with("p2p-dev-") {
+                continue;
+            }
+            out.push(name);
+        }
+        out.sort();
+        out
+    }
+
+    async fn ctrl(&self, iface: &str) -> Result<WpaCtrl> {
+        if !self.ifaces().iter().any(|i| i == iface) {
+            return Err(std::io::Error::other(format!(
+                "no wpa_supplicant control interface found for {iface}"
+            ))
+            .into());
+        }
+        WpaCtrl::open(&self.ctrl_dir.join(iface)).await
+    }
+
+    async fn find_known(&self, ctrl: &WpaCtrl, ssid: &str) -> Result<Option<ListedNetwork>> {
+        let listed = parse_list_networks(&ctrl.request("LIST_NETWORKS").await?);
+        Ok(listed.into_iter().find(|n| n.ssid == ssid))
+    }
+}
+
+impl Default for WpaSupplicantBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl WifiBackend for WpaSupplicantBackend {
+    async fn query_state(&self, iface: Option<&str>) -> Result<WifiState> {
+        let ifaces = self.ifaces();
+        let Some(selected) = pick_iface(&ifaces, iface) else {
+            return Ok(WifiState::empty());
+        };
+
+        let mut adapters = Vec::new();
+        for name in &ifaces {
+            if *name == selected {
+                continue;
+            }
+            let status = match self.ctrl(name).await {
+                Ok(ctrl) => parse_key_values(&ctrl.request("STATUS").await.unwrap_or_default()),
+                Err(_) => HashMap::new(),
+            };
+            adapters.push(adapter_info(name, &status));
+        }
+
+        let ctrl = self.ctrl(&selected).await?;
+        let status = parse_key_values(&ctrl.request("STATUS").await?);
+        adapters.push(adapter_info(&selected, &status));
+        adapters.sort_by(|a, b| a.iface.cmp(&b.iface));
+
+        let connected_ssid = if status.get("wpa_state").map(String::as_str) == Some("COMPLETED") {
+            status.get("ssid").cloned()
+        } else {
+            None
+        };
+
+        let listed = parse_list_networks(&ctrl.request("LIST_NETWORKS").await?);
+        let mut known_meta = HashMap::new();
+        for net in &listed {
+            let key_mgmt = ctrl
+                .request(&format!("GET_NETWORK {} key_mgmt", net.id))
+                .await
+                .unwrap_or_default();
+            let scan_ssid = ctrl
+                .request(&format!("GET_NETWORK {} scan_ssid", net.id))
+                .await
+                .unwrap_or_default();
+            known_meta.insert(
+                net.ssid.clone(),
+                KnownMeta {
+                    security: key_mgmt_security(key_mgmt.trim()).to_string(),
+                    hidden: scan_ssid.trim() == "1",
+                    autoconnect: !net.disabled,
+                },
+            );
+        }
+
+        let results = parse_scan_results(&ctrl.request("SCAN_RESULTS").await?);
+        let mut best: HashMap<String, ScanResult> = HashMap::new();
+        let mut hidden_networks = Vec::new();
+        for r in results {
+            if r.ssid.is_empty() {
+                hidden_networks.push(WifiNetwork {
+                    ssid: r.bssid.clone(),
+                    security: r.security.clone(),
+                    signal: signal_label(dbm_to_percent(r.signal_dbm)),
+                    connected: false,
+                    hidden: Some(true),
+                    autoconnect: None,
+                    available: false,
+                    last_connected: None,
+                });
+                continue;
+            }
+            match best.get(&r.ssid) {
+                Some(current) if current.signal_dbm >= r.signal_dbm => {}
+                _ => {
+                    best.insert(r.ssid.clone(), r);
+                }
+            }
+        }
+        hidden_networks.sort_by(|a, b| a.ssid.cmp(&b.ssid));
+
+        let mut known_networks = Vec::new();
+        let mut new_networks = Vec::new();
+        let mut available_names = HashSet::new();
+        for (ssid, r) in best {
+            let connected = connected_ssid.as_deref() == Some(ssid.as_str());
+            let signal = signal_label(dbm_to_percent(r.signal_dbm));
+            if let Some(meta) = known_meta.get(&ssid) {
+                available_names.insert(ssid.clone());
+                known_networks.push(WifiNetwork {
+                    ssid,
+                    security: r.security,
+                    signal,
+                    connected,
+                    hidden: Some(meta.hidden),
+                    autoconnect: Some(meta.autoconnect),
+                    available: true,
+                    last_connected: None,
+                });
+            } else {
+                new_networks.push(WifiNetwork {
+                    ssid,
+                    security: r.security,
+                    signal,
+                    connected,
+                    hidden: None,
+                    autoconnect: None,
+                    available: true,
+                    last_connected: None,
+                });
+            }
+        }
+        known_networks.sort_by(|a, b| a.ssid.cmp(&b.ssid));
+        new_networks.sort_by(|a, b| a.ssid.cmp(&b.ssid));
+
+        let mut unavailable_known_networks = Vec::new();
+        for (name, meta) in &known_meta {
+            if available_names.contains(name) {
+                continue;
+            }
+            unavailable_known_networks.push(WifiNetwork {
+                ssid: name.clone(),
+                security: meta.security.clone(),
+                signal: "-".to_string(),
+                connected: false,
+                hidden: Some(meta.hidden),
+                autoconnect: Some(meta.autoconnect),
+                available: false,
+                last_connected: None,
+            });
+        }
+        unavailable_known_networks.sort_by(|a, b| a.ssid.cmp(&b.ssid));
+
+        Ok(WifiState {
+            ifaces,
+            selected_iface: Some(selected),
+            connected_ssid,
+            known_networks,
+            unavailable_known_networks,
+            new_networks,
+            hidden_networks,
+            adapters,
+        })
+    }
+
+    async fn scan(&self, iface: &str) -> Result<()> {
+        self.ctrl(iface).await?.request_ok("SCAN").await
+    }
+
+    async fn connect(&self, iface: &str, ssid: &str) -> Result<()> {
+        let ctrl = self.ctrl(iface).await?;
+        if let Some(net) = self.find_known(&ctrl, ssid).await? {
+            return ctrl.select(&net.id, ssid, false).await;
+        }
+
+        let result = parse_scan_results(&ctrl.request("SCAN_RESULTS").await?)
+            .into_iter()
+            .find(|r| r.ssid == ssid)
+            .ok_or_else(|| NettuiError::NetworkNotFound(ssid.to_string()))?;
+        if result.security != "open" {
+            return Err(NettuiError::PassphraseRequired(ssid.to_string()).into());
+        }
+
+        let id = ctrl.add_network(ssid).await?;
+        ctrl.request_ok(&format!("SET_NETWORK {id} key_mgmt NONE"))
+            .await?;
+        ctrl.try_new_network(&id, ssid, None, false).await
+    }
+
+    async fn connect_hidden(&self, iface: &str, ssid: &str) -> Result<()> {
+        let ctrl = self.ctrl(iface).await?;
+        let id = ctrl.add_network(ssid).await?;
+        ctrl.request_ok(&format!("SET_NETWORK {id} scan_ssid 1"))
+            .await?;
+        ctrl.request_ok(&format!("SET_NETWORK {id} key_mgmt NONE"))
+            .await?;
+        ctrl.try_new_network(&id, ssid, None, false).await
+    }
+
+    async fn connect_with_passphrase(
+        &self,
+        iface: &str,
+        ssid: &str,
+        passphrase: &str,
+    ) -> Result<()> {
+        if passphrase.contains('\n') {
+            return Err(std::io::Error::other("passphrase cannot contain newlines").into());
+        }
+
+        // The passphrase is tried on a fresh network entry so a typo cannot overwrite a
+        // working one; the old entry is only replaced once the handshake succeeds.
+        let ctrl = self.ctrl(iface).await?;
+        let known = self.find_known(&ctrl, ssid).await?;
+        let id = ctrl.add_network(ssid).await?;
+        if let Some(known) = &known {
+            for key in ["scan_ssid", "priority"] {
+                let value = ctrl
+                    .request(&format!("GET_NETWORK {} {key}", known.id))
+                    .await?;
+                if !value.trim().is_empty() && !value.starts_with("FAIL") {
+                    ctrl.request_ok(&format!("SET_NETWORK {id} {key} {}", value.trim()))
+                        .await?;
+                }
+            }
+        }
+        ctrl.request_ok(&format!("SET_NETWORK {id} psk \"{passphrase}\""))
+            .await?;
+        let replaces = known.as_ref().map(|n| n.id.as_str());
+        ctrl.try_new_network(&id, ssid, replaces, true).await
+    }
+
+    async fn disconnect(&self, iface: &str) -> Result<()> {
+        self.ctrl(iface).await?.request_ok("DISCONNECT").await
+    }
+
+    async fn forget_known(&self, iface: &str, ssid: &str) -> Result<()> {
+        let ctrl = self.ctrl(iface).await?;
+        let net = self
+            .find_known(&ctrl, ssid)
+            .await?
+            .ok_or_else(|| NettuiError::KnownNetworkNotFound(ssid.to_string()))?;
+        ctrl.request_ok(&format!("REMOVE_NETWORK {}", net.id))
+            .await?;
+        ctrl.save_config().await;
+        Ok(())
+    }
+
+    async fn toggle_autoconnect(&self, iface: &str, ssid: &str) -> Result<bool> {
+        let ctrl = self.ctrl(iface).await?;
+        let net = self
+            .find_known(&ctrl, ssid)
+            .await?
+            .ok_or_else(|| NettuiError::KnownNetworkNotFound(ssid.to_string()))?;
+        let next = net.disabled;
+        let cmd = if next {
+            "ENABLE_NETWORK"
+        } else {
+            "DISABLE_NETWORK"
+        };
+        ctrl.request_ok(&format!("{cmd} {}", net.id)).await?;
+        ctrl.save_config().await;
+        Ok(next)
+    }
+}
+
+fn adapter_info(iface: &str, status: &HashMap<String, String>) -> WifiDeviceInfo {
+    let wpa_state = status.get("wpa_state").cloned().unwrap_or_default();
+    let connected = wpa_state == "COMPLETED";
+    let frequency = status
+        .get("freq")
+        .and_then(|f| f.parse::<u32>().ok())
+        .filter(|_| connected)
+        .map(|f| format!("{:.2} GHz", f as f32 / 1000.0))
+        .unwrap_or_else(|| "-".to_string());
+    let security = status
+        .get("key_mgmt")
+        .filter(|_| connected)
+        .cloned()
+        .unwrap_or_else(|| "-".to_string());
+
+    WifiDeviceInfo {
+        iface: iface.to_string(),
+        mode: "station".to_string(),
+        powered: match wpa_state.as_str() {
+            "" => "-".to_string(),
+            "INTERFACE_DISABLED" => "Off".to_string(),
+            _ => "On".to_string(),
+        },
+        state: wpa_state_label(&wpa_state).to_string(),
+        scanning: if wpa_state == "SCANNING" {
+            "Yes".to_string()
+        } else {
+            "No".to_string()
+        },
+        frequency,
+        security,
+    }
+}
+
+struct WpaCtrl {
+    socket: UnixDatagram,
+    local_path: PathBuf,
+    ctrl_path: PathBuf,
+}
+
+impl WpaCtrl {
+    async fn open(ctrl_path: &Path) -> Result<Self> {
+        let local_path = std::env::temp_dir().join(format!(
+            "nettui-wpa-{}-{}",
+            std::process::id(),
+            CLIENT_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_file(&local_path);
+        let socket = UnixDatagram::bind(&local_path)
+            .with_context(|| format!("cannot bind {}", local_path.display()))?;
+        let ctrl = Self {
+            socket,
+            local_path,
+            ctrl_path: ctrl_path.to_path_buf(),
+        };
+        if ctrl.socket.connect(ctrl_path).is_err() {
+            return Err(NettuiError::ServiceUnavailable("wpa_supplicant".to_string()).into());
+        }
+        Ok(ctrl)
+    }
+
+    async fn request(&self, cmd: &str) -> Result<String> {
+        self.socket.send(cmd.as_bytes()).await?;
+        let mut buf = vec![0u8; 16 * 1024];
+        let n = tokio::time::timeout(REQUEST_TIMEOUT, self.socket.recv(&mut buf))
+            .await
+            .map_err(|_| std::io::Error::other(format!("wpa_supplicant timed out on {cmd}")))??;
+        Ok(String::from_utf8_lossy(&buf[..n]).to_string())
+    }
+
+    async fn request_ok(&self, cmd: &str) -> Result<()> {
+        let reply = self.request(cmd).await?;
+        if reply.trim() == "OK" {
+            return Ok(());
+        }
+        let verb = cmd.split_whitespace().next().unwrap_or(cmd);
+        Err(std::io::Error::other(format!("wpa_supplicant {verb} failed: {}", reply.trim())).into())
+    }
+
+    async fn add_network(&self, ssid: &str) -> Result<String> {
+        let reply = self.request("ADD_NETWORK").await?;
+        let id = reply.trim().to_string();
+        if id.parse::<u32>().is_err() {
+            return Err(
+                std::io::Error::other(format!("wpa_supplicant ADD_NETWORK failed: {id}")).into(),
+            );
+        }
+        self.request_ok(&format!(
+            "SET_NETWORK {id} ssid {}",
+            hex_encode(ssid.as_bytes())
+        ))
+        .await?;
+        Ok(id)
+    }
+
+    // SELECT_NETWORK disables every other network, which SAVE_CONFIG would persist as
+    // autoconnect off. The ones that were enabled before come back once the attempt is
+    // over; doing it earlier would let wpa_supplicant roam to one of them instead.
+    async fn select(&self, id: &str, ssid: &str, passphrase_tried: bool) -> Result<()> {
+        let events = Self::open(&self.ctrl_path).await?;
+        events.request_ok("ATTACH").await?;
+        let listed = parse_list_networks(&self.request("LIST_NETWORKS").await?);
+        self.request_ok(&format!("SELECT_NETWORK {id}")).await?;
+
+        let outcome = events.wait_for_connection(ssid, passphrase_tried).await;
+        let _ = events.socket.send(b"DETACH").await;
+        for net in listed.iter().filter(|n| n.id != id && !n.disabled) {
+            self.request_ok(&format!("ENABLE_NETWORK {}", net.id))
+                .await?;
+        }
+        outcome
+    }
+
+    // A new entry is only written to the config once it has connected; a failed attempt
+    // removes it again.
+    async fn try_new_network(
+        &self,
+        id: &str,
+        ssid: &str,
+        replaces: Option<&str>,
+        passphrase_tried: bool,
+    ) -> Result<()> {
+        if let Err(e) = self.select(id, ssid, passphrase_tried).await {
+            let _ = self.request(&format!("REMOVE_NETWORK {id}")).await;
+            return Err(e);
+        }
+        if let Some(old) = replaces {
+            self.request_ok(&format!("REMOVE_NETWORK {old}")).await?;
+        }
+        self.save_config().await;
+        Ok(())
+    }
+
+    async fn wait_for_connection(&self, ssid: &str, passphrase_tried: bool) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + CONNECT_TIMEOUT;
+        let mut buf = vec![0u8; 4096];
+        loop {
+            let Ok(received) = tokio::time::timeout_at(deadline, self.socket.recv(&mut buf)).await
+            else {
+                return Err(NettuiError::ConnectFailed.into());
+            };
+            let msg = String::from_utf8_lossy(&buf[..received?]).to_string();
+            if let Some(outcome) = connection_event(&msg, ssid, passphrase_tried) {
+                return outcome.map_err(Into::into);
+            }
+        }
+    }
+
+    async fn save_config(&self) {
+        // SAVE_CONFIG fails when update_config=0; the change still applies to the running daemon.
+        let _ = self.request("SAVE_CONFIG").await;
+    }
+}
+
+impl Drop for WpaCtrl {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.local_path);
+    }
+}
+
+#[derive(Debug, Clone)]
+struct KnownMeta {
+    security: String,
+    hidden: bool,
+    autoconnect: bool,
+}
+
+#[derive(Debug, Clone)]
+struct ListedNetwork {
+    id: String,
+    ssid: String,
+    disabled: bool,
+}
+
+#[derive(Debug, Clone)]
+struct ScanResult {
+    bssid: String,
+    signal_dbm: i32,
+    security: String,
+    ssid: String,
+}
+
+fn connection_event(
+    msg: &str,
+    ssid: &str,
+    passphrase_tried: bool,
+) -> Option<Result<(), NettuiError>> {
+    // Unsolicited messages carry their log level as a "<N>" prefix.
+    let event = match msg.trim().split_once('>') {
+        Some((level, event)) if level.starts_with('<') => event,
+        _ => msg.trim(),
+    };
+    let temp_disabled = event.starts_with("CTRL-EVENT-SSID-TEMP-DISABLED");
+    if event.starts_with("CTRL-EVENT-CONNECTED") {
+        Some(Ok(()))
+    } else if event.starts_with("WPA: 4-Way Handshake failed")
+        || (temp_disabled && event.contains("reason=WRONG_KEY"))
+    {
+        Some(Err(if passphrase_tried {
+            NettuiError::WrongPassphrase(ssid.to_string())
+        } else {
+            NettuiError::PassphraseRequired(ssid.to_string())
+        }))
+    } else if temp_disabled {
+        Some(Err(NettuiError::ConnectFailed))
+    } else if event.starts_with("CTRL-EVENT-NETWORK-NOT-FOUND") {
+        Some(Err(NettuiError::NotAvailable))
+    } else {
+        None
+    }
+}
+
+fn parse_key_values(raw: &str) -> HashMap<String, String> {
+    raw.lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn parse_list_networks(raw: &str) -> Vec<ListedNetwork> {
+    raw.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split('\t').collect();
+            if cols.len() < 2 {
+                return None;
+            }
+            let flags = cols.get(3).copied().unwrap_or("");
+            Some(ListedNetwork {
+                id: cols[0].to_string(),
+                ssid: unescape_ssid(cols[1]),
+                disabled: flags.contains("[DISABLED]"),
+            })
+        })
+        .collect()
+}
+
+fn parse_scan_results(raw: &str) -> Vec<ScanResult> {
+    raw.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split('\t').collect();
+            if cols.len() < 4 {
+                return None;
+            }
+            let ssid = unescape_ssid(cols.get(4).copied().unwrap_or(""));
+            Some(ScanResult {
+                bssid: cols[0].to_string(),
+                signal_dbm: cols[2].parse().unwrap_or(-100),
+                security: flags_security(cols[3]).to_string(),
+                ssid: ssid.trim_matches('\0').to_string(),
+            })
+        })
+        .collect()
+}
+
+fn flags_security(flags: &str) -> &'static str {
+    if flags.contains("EAP") {
+        "8021x"
+    } else if flags.contains("PSK") || flags.contains("SAE") {
+        "psk"
+    } else if flags.contains("WEP") {
+        "wep"
+    } else {
+        "open"
+    }
+}
+
+fn key_mgmt_security(key_mgmt: &str) -> &'static str {
+    if key_mgmt.contains("EAP") {
+        "8021x"
+    } else if key_mgmt.contains("PSK") || key_mgmt.contains("SAE") {
+        "psk"
+    } else {
+        "open"
+    }
+}
+
+fn wpa_state_label(state: &str) -> &'static str {
+    match state {
+        "COMPLETED" => "connected",
+        "DISCONNECTED" | "INACTIVE" => "disconnected",
+        "SCANNING" => "scanning",
+        "AUTHENTICATING" | "ASSOCIATING" | "ASSOCIATED" | "4WAY_HANDSHAKE" | "GROUP_HANDSHAKE" => {
+            "connecting"
+        }
+        "INTERFACE_DISABLED" => "disabled",
+        _ => "-",
+    }
+}
+
+fn dbm_to_percent(dbm: i32) -> i16 {
+    if dbm >= -50 {
+        100
+    } else {
+        (2 * (100 + dbm)).clamp(0, 100) as i16
+    }
+}
+
+fn unescape_ssid(raw: &str) -> String {
+    let bytes = raw.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 1 < bytes.len() {
+            match bytes[i + 1] {
+                b'x' if i + 3 < bytes.len() => {
+                    let hex = std::str::from_utf8(&bytes[i + 2..i + 4]).unwrap_or("");
+                    if let Ok(b) = u8::from_str_radix(hex, 16) {
+                        out.push(b);
+                        i += 4;
+                        continue;
+                    }
+                    out.push(bytes[i]);
+                    i += 1;
+                }
+                b'n' => {
+                    out.push(b'\n');
+                    i += 2;
+                }
+                b'r' => {
+                    out.push(b'\r');
+                    i += 2;
+                }
+                b't' => {
+                    out.push(b'\t');
+                    i += 2;
+                }
+                b'e' => {
+                    out.push(0x1b);
+                    i += 2;
+                }
+                c => {
+                    out.push(c);
+                    i += 2;
+                }
+            }
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    const CONNECTED: &str = "<3>CTRL-EVENT-CONNECTED - Connection to aa:bb:cc:dd:ee:01 completed";
+
+    fn spawn_stand_in(
+        dir: &Path,
+        iface: &str,
+        replies: Vec<(&'static str, &'static str)>,
+    ) -> Arc<Mutex<Vec<String>>> {
+        spawn_stand_in_with_event(dir, iface, replies, CONNECTED)
+    }
+
+    // Answers each request from `replies` (default "OK") and sends `on_select` to the
+    // attached monitor whenever a network is selected.
+    fn spawn_stand_in_with_event(
+        dir: &Path,
+        iface: &str,
+        replies: Vec<(&'static str, &'static str)>,
+        on_select: &'static str,
+    ) -> Arc<Mutex<Vec<String>>> {
+        let socket = std::os::unix::net::UnixDatagram::bind(dir.join(iface)).unwrap();
+        socket.set_nonblocking(true).unwrap();
+        let socket = UnixDatagram::from_std(socket).unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_task = seen.clone();
+
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; 4096];
+            let mut monitor = None;
+            loop {
+                let Ok((n, peer)) = socket.recv_from(&mut buf).await else {
+                    return;
+                };
+                let cmd = String::from_utf8_lossy(&buf[..n]).to_string();
+                seen_task.lock().unwrap().push(cmd.clone());
+                let reply = replies
+                    .iter()
+                    .find(|(prefix, _)| cmd.starts_with(prefix))
+                    .map(|(_, reply)| *reply)
+                    .unwrap_or("OK\n");
+                let Some(path) = peer.as_pathname() else {
+                    continue;
+                };
+                let _ = socket.send_to(reply.as_bytes(), path).await;
+                match cmd.split_whitespace().next() {
+                    Some("ATTACH") => monitor = Some(path.to_path_buf()),
+                    Some("DETACH") => monitor = None,
+                    Some("SELECT_NETWORK") => {
+                        if let Some(monitor) = &monitor {
+                            let _ = socket.send_to(on_select.as_bytes(), monitor).await;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        seen
+    }
+
+    #[tokio::test]
+    async fn query_state_classifies_known_new_and_hidden() {
+        let dir = tempfile::tempdir().unwrap();
+        spawn_stand_in(
+            dir.path(),
+            "wlan0",
+            vec![
+                (
+                    "STATUS",
+                    "bssid=aa:bb:cc:dd:ee:01\nfreq=5180\nssid=Home\nkey_mgmt=WPA2-PSK\nwpa_state=COMPLETED\n",
+                ),
+                (
+                    "LIST_NETWORKS",
+                    "network id / ssid / bssid / flags\n0\tHome\tany\t[CURRENT]\n1\tCafe\tany\t[DISABLED]\n",
+                ),
+                ("GET_NETWORK 0 key_mgmt", "WPA-PSK\n"),
+                ("GET_NETWORK 1 key_mgmt", "NONE\n"),
+                ("GET_NETWORK 0 scan_ssid", "0\n"),
+                ("GET_NETWORK 1 scan_ssid", "1\n"),
+                (
+                    "SCAN_RESULTS",
+                    "bssid / frequency / signal level / flags / ssid\n\
+                     aa:bb:cc:dd:ee:01\t5180\t-48\t[WPA2-PSK-CCMP][ESS]\tHome\n\
+                     aa:bb:cc:dd:ee:02\t2412\t-70\t[WPA2-EAP-CCMP][ESS]\tOffice\\x20Net\n\
+                     aa:bb:cc:dd:ee:03\t2437\t-80\t[ESS]\t\n",
+                ),
+            ],
+        );
+
+        let state = WpaSupplicantBackend::with_ctrl_dir(dir.path())
+            .query_state(None)
+            .await
+            .unwrap();
+
+        assert_eq!(state.ifaces, vec!["wlan0".to_string()]);
+        assert_eq!(state.connected_ssid.as_deref(), Some("Home"));
+        assert_eq!(state.known_networks.len(), 1);
+        assert!(state.known_networks[0].connected);
+        assert_eq!(state.known_networks[0].autoconnect, Some(true));
+        assert_eq!(state.new_networks[0].ssid, "Office Net");
+        assert_eq!(state.new_networks[0].security, "8021x");
+        assert_eq!(state.unavailable_known_networks[0].ssid, "Cafe");
+        assert_eq!(state.unavailable_known_networks[0].hidden, Some(true));
+        assert_eq!(state.unavailable_known_networks[0].autoconnect, Some(false));
+        assert_eq!(state.hidden_networks[0].ssid, "aa:bb:cc:dd:ee:03");
+        assert_eq!(state.selected_iface.as_deref(), Some("wlan0"));
+        let device = state.selected_adapter().unwrap();
+        assert_eq!(device.state, "connected");
+        assert_eq!(device.frequency, "5.18 GHz");
+    }
+
+    #[tokio::test]
+    async fn connect_with_passphrase_adds_and_selects_network() {
+        let dir = tempfile::tempdir().unwrap();
+        let seen = spawn_stand_in(
+            dir.path(),
+            "wlan0",
+            vec![
+                ("LIST_NETWORKS", "network id / ssid / bssid / flags\n"),
+                ("ADD_NETWORK", "3\n"),
+            ],
+        );
+
+        WpaSupplicantBackend::with_ctrl_dir(dir.path())
+            .connect_with_passphrase("wlan0", "Guest", "hunter22")
+            .await
+            .unwrap();
+
+        let seen = seen.lock().unwrap().clone();
+        assert_eq!(
+            seen,
+            vec![
+                "LIST_NETWORKS".to_string(),
+                "ADD_NETWORK".to_string(),
+                "SET_NETWORK 3 ssid 4775657374".to_string(),
+                "SET_NETWORK 3 psk \"hunter22\"".to_string(),
+                "ATTACH".to_string(),
+                "LIST_NETWORKS".to_string(),
+                "SELECT_NETWORK 3".to_string(),
+                "DETACH".to_string(),
+                "SAVE_CONFIG".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn connect_keeps_other_networks_enabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let seen = spawn_stand_in(
+            dir.path(),
+            "wlan0",
+            vec![
+                (
+                    "LIST_NETWORKS",
+                    "network id / ssid / bssid / flags\n\
+                     0\tHome\tany\t[CURRENT]\n\
+                     1\tCafe\tany\t[DISABLED]\n\
+                     2\tWork\tany\t\n",
+                ),
+                ("ADD_NETWORK", "3\n"),
+                ("GET_NETWORK 0 scan_ssid", "0\n"),
+                ("GET_NETWORK 0 priority", "5\n"),
+            ],
+        );
+        let backend = WpaSupplicantBackend::with_ctrl_dir(dir.path());
+
+        // Other networks come back only after the selected one has connected.
+        backend.connect("wlan0", "Work").await.unwrap();
+        let replay = seen.lock().unwrap().split_off(0);
+        assert_eq!(
+            replay,
+            [
+                "LIST_NETWORKS",
+                "ATTACH",
+                "LIST_NETWORKS",
+                "SELECT_NETWORK 2",
+                "DETACH",
+                "ENABLE_NETWORK 0"
+            ]
+        );
+
+        backend
+            .connect_with_passphrase("wlan0", "Home", "hunter22")
+            .await
+            .unwrap();
+        let replay = seen.lock().unwrap().split_off(0);
+        assert_eq!(
+            replay[1..],
+            [
+                "ADD_NETWORK",
+                "SET_NETWORK 3 ssid 486f6d65",
+                "GET_NETWORK 0 scan_ssid",
+                "SET_NETWORK 3 scan_ssid 0",
+                "GET_NETWORK 0 priority",
+                "SET_NETWORK 3 priority 5",
+                "SET_NETWORK 3 psk \"hunter22\"",
+                "ATTACH",
+                "LIST_NETWORKS",
+                "SELECT_NETWORK 3",
+                "DETACH",
+                "ENABLE_NETWORK 0",
+                "ENABLE_NETWORK 2",
+                "REMOVE_NETWORK 0",
+                "SAVE_CONFIG"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn wrong_passphrase_keeps_the_stored_network() {
+        let dir = tempfile::tempdir().unwrap();
+        let seen = spawn_stand_in_with_event(
+            dir.path(),
+            "wlan0",
+            vec![
+                (
+                    "LIST_NETWORKS",
+                    "network id / ssid / bssid / flags\n0\tHome\tany\t\n",
+                ),
+                ("ADD_NETWORK", "1\n"),
+                ("GET_NETWORK", "FAIL\n"),
+            ],
+            "<3>CTRL-EVENT-SSID-TEMP-DISABLED id=1 ssid=\"Home\" auth_failures=1 duration=10 reason=WRONG_KEY",
+        );
+
+        let err = WpaSupplicantBackend::with_ctrl_dir(dir.path())
+            .connect_with_passphrase("wlan0", "Home", "hunter2")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            NettuiError::of(&err),
+            Some(&NettuiError::WrongPassphrase("Home".to_string()))
+        );
+
+        let seen = seen.lock().unwrap().clone();
+        assert!(!seen.iter().any(|c| c.starts_with("SET_NETWORK 0")));
+        assert!(!seen.contains(&"SAVE_CONFIG".to_string()));
+        assert_eq!(
+            seen[seen.len() - 3..],
+            ["DETACH", "ENABLE_NETWORK 0", "REMOVE_NETWORK 1"]
+        );
+    }
+
+    #[test]
+    fn connection_events_map_to_outcomes() {
+        let event = |msg, tried| connection_event(msg, "Home", tried);
+        assert_eq!(event(CONNECTED, false), Some(Ok(())));
+        assert_eq!(event("<3>CTRL-EVENT-SCAN-RESULTS ", false), None);
+        assert_eq!(
+            event(
+                "<3>WPA: 4-Way Handshake failed - pre-shared key may be incorrect",
+                false
+            ),
+            Some(Err(NettuiError::PassphraseRequired("Home".to_string())))
+        );
+        assert_eq!(
+            event(
+                "<3>CTRL-EVENT-SSID-TEMP-DISABLED id=0 ssid=\"Home\" reason=CONN_FAILED",
+                true
+            ),
+            Some(Err(NettuiError::ConnectFailed))
+        );
+        assert_eq!(
+            event("<3>CTRL-EVENT-NETWORK-NOT-FOUND", true),
+            Some(Err(NettuiError::NotAvailable))
+        );
+    }
+
+    #[tokio::test]
+    async fn connect_to_secured_new_network_requires_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        spawn_stand_in(
+            dir.path(),
+            "wlan0",
+            vec![
+                ("LIST_NETWORKS", "network id / ssid / bssid / flags\n"),
+                (
+                    "SCAN_RESULTS",
+                    "bssid / frequency / signal level / flags / ssid\n\
+                     aa:bb:cc:dd:ee:04\t2412\t-60\t[WPA2-PSK-CCMP][ESS]\tGuest\n",
+                ),
+            ],
+        );
+
+        let err = WpaSupplicantBackend::with_ctrl_dir(dir.path())
+            .connect("wlan0", "Guest")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("passphrase required"));
+    }
+}
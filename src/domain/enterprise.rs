@@ -0,0 +1,238 @@
+use crate::error::NettuiError;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EapMethod {
+    PeapMschapv2,
+    TtlsPap,
+    TtlsMschapv2,
+}
+
+impl EapMethod {
+    pub const ALL: [Self; 3] = [Self::PeapMschapv2, Self::TtlsPap, Self::TtlsMschapv2];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::PeapMschapv2 => "PEAP / MSCHAPv2",
+            Self::TtlsPap => "TTLS / PAP",
+            Self::TtlsMschapv2 => "TTLS / MSCHAPv2",
+        }
+    }
+
+    pub fn outer(self) -> &'static str {
+        match self {
+            Self::PeapMschapv2 => "PEAP",
+            Self::TtlsPap | Self::TtlsMschapv2 => "TTLS",
+        }
+    }
+
+    pub fn phase2(self) -> &'static str {
+        match self {
+            Self::PeapMschapv2 => "MSCHAPV2",
+            Self::TtlsPap => "Tunneled-PAP",
+            Self::TtlsMschapv2 => "Tunneled-MSCHAPv2",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnterpriseConfig {
+    pub method: EapMethod,
+    pub identity: String,
+    pub anonymous_identity: String,
+    pub password: String,
+    pub ca_cert: String,
+    pub domain_mask: String,
+}
+
+impl Default for EnterpriseConfig {
+    fn default() -> Self {
+        Self {
+            method: EapMethod::PeapMschapv2,
+            identity: String::new(),
+            anonymous_identity: String::new(),
+            password: String::new(),
+            ca_cert: String::new(),
+            domain_mask: String::new(),
+        }
+    }
+}
+
+impl EnterpriseConfig {
+    pub fn validate(&self) -> Result<(), NettuiError> {
+        let invalid = |msg: &str| Err(NettuiError::InvalidInput(msg.to_string()));
+        let fields = [
+            &self.identity,
+            &self.anonymous_identity,
+            &self.password,
+            &self.ca_cert,
+            &self.domain_mask,
+        ];
+        if fields.iter().any(|f| f.contains(['\n', '\r'])) {
+            return invalid("Fields cannot contain line breaks");
+        }
+        if self.identity.trim().is_empty() {
+            return invalid("Identity cannot be empty");
+        }
+        if self.password.is_empty() {
+            return invalid("Password cannot be empty");
+        }
+        if !self.ca_cert.is_empty() {
+            let path = Path::new(&self.ca_cert);
+            if !path.is_absolute() {
+                return invalid("CA certificate path must be absolute");
+            }
+            if !path.is_file() {
+                return invalid("CA certificate file does not exist");
+            }
+        }
+        if self.domain_mask.contains(char::is_whitespace) {
+            return invalid("Server domain mask cannot contain spaces");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnterpriseField {
+    Method,
+    Identity,
+    AnonymousIdentity,
+    Password,
+    CaCert,
+    DomainMask,
+}
+
+impl EnterpriseField {
+    pub const ALL: [Self; 6] = [
+        Self::Method,
+        Self::Identity,
+        Self::AnonymousIdentity,
+        Self::Password,
+        Self::CaCert,
+        Self::DomainMask,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Method => "EAP method",
+            Self::Identity => "Identity",
+            Self::AnonymousIdentity => "Anonymous identity",
+            Self::Password => "Password",
+            Self::CaCert => "CA certificate",
+            Self::DomainMask => "Server domain mask",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnterpriseForm {
+    pub ssid: String,
+    pub config: EnterpriseConfig,
+    pub field: EnterpriseField,
+}
+
+impl EnterpriseForm {
+    pub fn new(ssid: String) -> Self {
+        Self {
+            ssid,
+            config: EnterpriseConfig::default(),
+            field: EnterpriseField::Method,
+        }
+    }
+
+    pub fn next_field(&mut self) {
+        self.move_field(1);
+    }
+
+    pub fn prev_field(&mut self) {
+        self.move_field(EnterpriseField::ALL.len() - 1);
+    }
+
+    fn move_field(&mut self, by: usize) {
+        let all = EnterpriseField::ALL;
+        let pos = all.iter().position(|f| *f == self.field).unwrap_or(0);
+        self.field = all[(pos + by) % all.len()];
+    }
+
+    pub fn cycle_method(&mut self, forward: bool) {
+        let all = EapMethod::ALL;
+        let pos = all
+            .iter()
+            .position(|m| *m == self.config.method)
+            .unwrap_or(0);
+        let by = if forward { 1 } else { all.len() - 1 };
+        self.config.method = all[(pos + by) % all.len()];
+    }
+
+    pub fn push(&mut self, c: char) {
+        if let Some(value) = self.text_mut() {
+            value.push(c);
+        } else if c == ' ' {
+            self.cycle_method(true);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(value) = self.text_mut() {
+            value.pop();
+        }
+    }
+
+    pub fn display_value(&self, field: EnterpriseField) -> String {
+        let c = &self.config;
+        match field {
+            EnterpriseField::Method => format!("◂ {} ▸", c.method.label()),
+            EnterpriseField::Identity => c.identity.clone(),
+            EnterpriseField::AnonymousIdentity => c.anonymous_identity.clone(),
+            EnterpriseField::Password => "*".repeat(c.password.chars().count()),
+            EnterpriseField::CaCert => c.ca_cert.clone(),
+            EnterpriseField::DomainMask => c.domain_mask.clone(),
+        }
+    }
+
+    fn text_mut(&mut self) -> Option<&mut String> {
+        let c = &mut self.config;
+        match self.field {
+            EnterpriseField::Method => None,
+            EnterpriseField::Identity => Some(&mut c.identity),
+            EnterpriseField::AnonymousIdentity => Some(&mut c.anonymous_identity),
+            EnterpriseField::Password => Some(&mut c.password),
+            EnterpriseField::CaCert => Some(&mut c.ca_cert),
+            EnterpriseField::DomainMask => Some(&mut c.domain_mask),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn form_edits_fields_and_validates() {
+        let mut form = EnterpriseForm::new("Campus".to_string());
+        form.push(' ');
+        assert_eq!(form.config.method, EapMethod::TtlsPap);
+        form.prev_field();
+        assert_eq!(form.field, EnterpriseField::DomainMask);
+        form.next_field();
+        form.next_field();
+        "alice@example.org".chars().for_each(|c| form.push(c));
+        assert_eq!(
+            form.config.validate(),
+            Err(NettuiError::InvalidInput(
+                "Password cannot be empty".to_string()
+            ))
+        );
+
+        form.config.password = "s3cret".to_string();
+        form.config.ca_cert = "certs/ca.pem".to_string();
+        assert!(form.config.validate().is_err());
+        form.config.ca_cert.clear();
+        assert_eq!(form.config.validate(), Ok(()));
+        assert_eq!(form.display_value(EnterpriseField::Password), "******");
+
+        form.config.identity.push('\n');
+        assert!(form.config.validate().is_err());
+    }
+}
@@ -1,14 +1,34 @@
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthernetRoute {
+    pub destination: String,
+    pub gateway: Option<String>,
+    pub metric: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManagedLinkState {
+    pub operational: String,
+    pub address: String,
+    pub setup: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EthernetIface {
     pub name: String,
+    pub ifindex: u32,
     pub operstate: String,
     pub carrier: Option<bool>,
     pub mac: Option<String>,
     pub speed_mbps: Option<u32>,
+    pub mtu: Option<u32>,
+    pub flags: Vec<String>,
     pub ipv4: Vec<String>,
     pub ipv6: Vec<String>,
     pub gateway_v4: Option<String>,
+    pub gateway_v6: Option<String>,
+    pub routes: Vec<EthernetRoute>,
     pub dns: Vec<String>,
+    pub managed: Option<ManagedLinkState>,
 }
 
 impl EthernetIface {
@@ -34,4 +54,208 @@ impl EthernetState {
     pub fn has_active(&self) -> bool {
         self.ifaces.iter().any(EthernetIface::is_active)
     }
+
+    pub fn knows(&self, ifindex: u32) -> bool {
+        self.ifaces.iter().any(|i| i.ifindex == ifindex)
+    }
+
+    pub fn apply(&mut self, change: EthernetChange) -> Option<String> {
+        let pos = self
+            .ifaces
+            .iter()
+            .position(|i| i.ifindex == change.ifindex())?;
+        let iface = &mut self.ifaces[pos];
+
+        match change {
+            EthernetChange::Link(link) => {
+                let notice = match (iface.carrier, link.carrier) {
+                    (Some(true), Some(false)) => Some(format!("{}: carrier lost", link.name)),
+                    (Some(false), Some(true)) => Some(format!("{}: carrier detected", link.name)),
+                    _ => None,
+                };
+                iface.name = link.name;
+                iface.operstate = link.operstate;
+                iface.carrier = link.carrier;
+                iface.mac = link.mac.or(iface.mac.take());
+                iface.mtu = link.mtu.or(iface.mtu);
+                iface.flags = link.flags;
+                notice
+            }
+            EthernetChange::LinkRemoved(_) => {
+                let removed = self.ifaces.remove(pos);
+                Some(format!("{}: removed", removed.name))
+            }
+            EthernetChange::AddressAdded { v6, cidr, .. } => {
+                let list = if v6 { &mut iface.ipv6 } else { &mut iface.ipv4 };
+                if list.contains(&cidr) {
+                    return None;
+                }
+                list.push(cidr.clone());
+                (!v6).then(|| format!("{}: got {cidr}", iface.name))
+            }
+            EthernetChange::AddressRemoved { v6, cidr, .. } => {
+                let list = if v6 { &mut iface.ipv6 } else { &mut iface.ipv4 };
+                let before = list.len();
+                list.retain(|a| *a != cidr);
+                (!v6 && list.len() != before).then(|| format!("{}: lost {cidr}", iface.name))
+            }
+            EthernetChange::RouteAdded { v6, route, .. } => {
+                if route.destination == "default" {
+                    let gateway = if v6 {
+                        &mut iface.gateway_v6
+                    } else {
+                        &mut iface.gateway_v4
+                    };
+                    if gateway.is_none() {
+                        *gateway = route.gateway.clone();
+                    }
+                }
+                if !iface.routes.contains(&route) {
+                    iface.routes.push(route);
+                }
+                None
+            }
+            EthernetChange::RouteRemoved { v6, route, .. } => {
+                iface.routes.retain(|r| *r != route);
+                if route.destination == "default" {
+                    let gateway = if v6 {
+                        &mut iface.gateway_v6
+                    } else {
+                        &mut iface.gateway_v4
+                    };
+                    if *gateway == route.gateway {
+                        *gateway = iface
+                            .routes
+                            .iter()
+                            .filter(|r| r.destination == "default")
+                            .filter_map(|r| r.gateway.clone())
+                            .find(|g| g.contains(':') == v6);
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EthernetChange {
+    Link(Box<EthernetIface>),
+    LinkRemoved(u32),
+    AddressAdded {
+        ifindex: u32,
+        v6: bool,
+        cidr: String,
+    },
+    AddressRemoved {
+        ifindex: u32,
+        v6: bool,
+        cidr: String,
+    },
+    RouteAdded {
+        ifindex: u32,
+        v6: bool,
+        route: EthernetRoute,
+    },
+    RouteRemoved {
+        ifindex: u32,
+        v6: bool,
+        route: EthernetRoute,
+    },
+}
+
+impl EthernetChange {
+    pub fn ifindex(&self) -> u32 {
+        match self {
+            Self::Link(link) => link.ifindex,
+            Self::LinkRemoved(ifindex)
+            | Self::AddressAdded { ifindex, .. }
+            | Self::AddressRemoved { ifindex, .. }
+            | Self::RouteAdded { ifindex, .. }
+            | Self::RouteRemoved { ifindex, .. } => *ifindex,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iface(name: &str, ifindex: u32) -> EthernetIface {
+        EthernetIface {
+            name: name.to_string(),
+            ifindex,
+            operstate: "up".to_string(),
+            carrier: Some(true),
+            mac: None,
+            speed_mbps: Some(1000),
+            mtu: Some(1500),
+            flags: vec!["UP".to_string(), "LOWER_UP".to_string()],
+            ipv4: vec!["192.168.1.20/24".to_string()],
+            ipv6: vec![],
+            gateway_v4: Some("192.168.1.1".to_string()),
+            gateway_v6: None,
+            routes: vec![EthernetRoute {
+                destination: "default".to_string(),
+                gateway: Some("192.168.1.1".to_string()),
+                metric: Some(100),
+            }],
+            dns: vec![],
+            managed: None,
+        }
+    }
+
+    #[test]
+    fn apply_updates_known_iface_and_reports_changes() {
+        let mut state = EthernetState {
+            ifaces: vec![iface("enp3s0", 2)],
+        };
+
+        let mut unplugged = iface("enp3s0", 2);
+        unplugged.operstate = "down".to_string();
+        unplugged.carrier = Some(false);
+        unplugged.speed_mbps = None;
+        unplugged.flags = vec!["UP".to_string()];
+        assert_eq!(
+            state.apply(EthernetChange::Link(Box::new(unplugged))),
+            Some("enp3s0: carrier lost".to_string())
+        );
+        assert_eq!(state.ifaces[0].operstate, "down");
+        assert_eq!(state.ifaces[0].speed_mbps, Some(1000));
+
+        assert_eq!(
+            state.apply(EthernetChange::RouteRemoved {
+                ifindex: 2,
+                v6: false,
+                route: state.ifaces[0].routes[0].clone(),
+            }),
+            None
+        );
+        assert!(state.ifaces[0].routes.is_empty());
+        assert_eq!(state.ifaces[0].gateway_v4, None);
+
+        assert_eq!(
+            state.apply(EthernetChange::AddressRemoved {
+                ifindex: 2,
+                v6: false,
+                cidr: "192.168.1.20/24".to_string(),
+            }),
+            Some("enp3s0: lost 192.168.1.20/24".to_string())
+        );
+        assert!(!state.has_active());
+
+        assert_eq!(
+            state.apply(EthernetChange::AddressAdded {
+                ifindex: 7,
+                v6: false,
+                cidr: "10.0.0.2/8".to_string(),
+            }),
+            None
+        );
+        assert_eq!(
+            state.apply(EthernetChange::LinkRemoved(2)),
+            Some("enp3s0: removed".to_string())
+        );
+        assert!(!state.has_adapter());
+    }
 }
@@ -1,3 +1,6 @@
 pub mod common;
+pub mod enterprise;
 pub mod ethernet;
+pub mod profile;
+pub mod share;
 pub mod wifi;
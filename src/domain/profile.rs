@@ -0,0 +1,325 @@
+use crate::error::NettuiError;
+use std::net::Ipv4Addr;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MacPolicy {
+    #[default]
+    Default,
+    Randomize,
+    Override,
+}
+
+impl MacPolicy {
+    pub const ALL: [Self; 3] = [Self::Default, Self::Randomize, Self::Override];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Default => "Default",
+            Self::Randomize => "Always randomize",
+            Self::Override => "Fixed address",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownProfile {
+    pub autoconnect: bool,
+    pub hidden: bool,
+    pub mac_policy: MacPolicy,
+    pub address_override: String,
+    pub transition_disable: bool,
+    pub ipv4_address: String,
+    pub ipv4_netmask: String,
+    pub ipv4_gateway: String,
+    pub ipv4_dns: String,
+    // The file as read, so saving keeps [Security] and keys nettui does not edit.
+    pub source: String,
+}
+
+impl Default for KnownProfile {
+    fn default() -> Self {
+        Self {
+            autoconnect: true,
+            hidden: false,
+            mac_policy: MacPolicy::Default,
+            address_override: String::new(),
+            transition_disable: false,
+            ipv4_address: String::new(),
+            ipv4_netmask: String::new(),
+            ipv4_gateway: String::new(),
+            ipv4_dns: String::new(),
+            source: String::new(),
+        }
+    }
+}
+
+impl KnownProfile {
+    pub fn is_static_ipv4(&self) -> bool {
+        !self.ipv4_address.is_empty()
+    }
+
+    pub fn dns_servers(&self) -> Vec<&str> {
+        self.ipv4_dns
+            .split([' ', ','])
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<(), NettuiError> {
+        let invalid = |msg: &str| Err(NettuiError::InvalidInput(msg.to_string()));
+        let fields = [
+            &self.address_override,
+            &self.ipv4_address,
+            &self.ipv4_netmask,
+            &self.ipv4_gateway,
+            &self.ipv4_dns,
+        ];
+        if fields.iter().any(|f| f.contains(['\n', '\r'])) {
+            return invalid("Fields cannot contain line breaks");
+        }
+
+        if self.mac_policy == MacPolicy::Override {
+            let Some(mac) = parse_mac(&self.address_override) else {
+                return invalid("MAC address must look like 02:00:00:00:00:01");
+            };
+            if mac[0] & 1 == 1 {
+                return invalid("MAC address must be unicast");
+            }
+        }
+
+        let ipv4_rest = [&self.ipv4_netmask, &self.ipv4_gateway, &self.ipv4_dns];
+        if !self.is_static_ipv4() {
+            if ipv4_rest.iter().any(|f| !f.is_empty()) {
+                return invalid("Static IPv4 needs an address");
+            }
+            return Ok(());
+        }
+        let Ok(address) = self.ipv4_address.parse::<Ipv4Addr>() else {
+            return invalid("IPv4 address is not valid");
+        };
+        let netmask = match self.ipv4_netmask.as_str() {
+            "" => None,
+            raw => match raw.parse::<Ipv4Addr>().map(u32::from) {
+                Ok(m) if m != 0 && m.leading_ones() + m.trailing_zeros() == 32 => Some(m),
+                _ => return invalid("Netmask is not valid"),
+            },
+        };
+        if !self.ipv4_gateway.is_empty() {
+            let Ok(gateway) = self.ipv4_gateway.parse::<Ipv4Addr>() else {
+                return invalid("Gateway is not valid");
+            };
+            if let Some(mask) = netmask
+                && u32::from(gateway) & mask != u32::from(address) & mask
+            {
+                return invalid("Gateway is outside the IPv4 subnet");
+            }
+        }
+        if self
+            .dns_servers()
+            .iter()
+            .any(|s| s.parse::<Ipv4Addr>().is_err())
+        {
+            return invalid("DNS servers must be IPv4 addresses");
+        }
+        Ok(())
+    }
+}
+
+fn parse_mac(raw: &str) -> Option<[u8; 6]> {
+    let mut out = [0u8; 6];
+    let mut parts = raw.split(':');
+    for byte in &mut out {
+        let part = parts.next().filter(|p| p.len() == 2)?;
+        *byte = u8::from_str_radix(part, 16).ok()?;
+    }
+    parts.next().is_none().then_some(out)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileField {
+    AutoConnect,
+    Hidden,
+    MacPolicy,
+    AddressOverride,
+    TransitionDisable,
+    Ipv4Address,
+    Ipv4Netmask,
+    Ipv4Gateway,
+    Ipv4Dns,
+}
+
+impl ProfileField {
+    pub const ALL: [Self; 9] = [
+        Self::AutoConnect,
+        Self::Hidden,
+        Self::MacPolicy,
+        Self::AddressOverride,
+        Self::TransitionDisable,
+        Self::Ipv4Address,
+        Self::Ipv4Netmask,
+        Self::Ipv4Gateway,
+        Self::Ipv4Dns,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::AutoConnect => "Autoconnect",
+            Self::Hidden => "Hidden",
+            Self::MacPolicy => "MAC address",
+            Self::AddressOverride => "Fixed MAC",
+            Self::TransitionDisable => "Transition disable",
+            Self::Ipv4Address => "IPv4 address",
+            Self::Ipv4Netmask => "Netmask",
+            Self::Ipv4Gateway => "Gateway",
+            Self::Ipv4Dns => "DNS",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileForm {
+    pub ssid: String,
+    pub security: String,
+    pub profile: KnownProfile,
+    pub field: ProfileField,
+}
+
+impl ProfileForm {
+    pub fn new(ssid: String, security: String, profile: KnownProfile) -> Self {
+        Self {
+            ssid,
+            security,
+            profile,
+            field: ProfileField::AutoConnect,
+        }
+    }
+
+    pub fn next_field(&mut self) {
+        self.move_field(1);
+    }
+
+    pub fn prev_field(&mut self) {
+        self.move_field(ProfileField::ALL.len() - 1);
+    }
+
+    fn move_field(&mut self, by: usize) {
+        let all = ProfileField::ALL;
+        let pos = all.iter().position(|f| *f == self.field).unwrap_or(0);
+        self.field = all[(pos + by) % all.len()];
+    }
+
+    pub fn cycle(&mut self, forward: bool) {
+        let p = &mut self.profile;
+        match self.field {
+            ProfileField::AutoConnect => p.autoconnect = !p.autoconnect,
+            ProfileField::Hidden => p.hidden = !p.hidden,
+            ProfileField::TransitionDisable => p.transition_disable = !p.transition_disable,
+            ProfileField::MacPolicy => {
+                let all = MacPolicy::ALL;
+                let pos = all.iter().position(|m| *m == p.mac_policy).unwrap_or(0);
+                let by = if forward { 1 } else { all.len() - 1 };
+                p.mac_policy = all[(pos + by) % all.len()];
+            }
+            _ => {}
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        if let Some(value) = self.text_mut() {
+            value.push(c);
+        } else if c == ' ' {
+            self.cycle(true);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(value) = self.text_mut() {
+            value.pop();
+        }
+    }
+
+    pub fn display_value(&self, field: ProfileField) -> String {
+        let p = &self.profile;
+        let flag = |on: bool| if on { "[x]" } else { "[ ]" }.to_string();
+        match field {
+            ProfileField::AutoConnect => flag(p.autoconnect),
+            ProfileField::Hidden => flag(p.hidden),
+            ProfileField::TransitionDisable => flag(p.transition_disable),
+            ProfileField::MacPolicy => format!("◂ {} ▸", p.mac_policy.label()),
+            ProfileField::AddressOverride if p.mac_policy != MacPolicy::Override => String::new(),
+            ProfileField::AddressOverride => p.address_override.clone(),
+            ProfileField::Ipv4Address => p.ipv4_address.clone(),
+            ProfileField::Ipv4Netmask => p.ipv4_netmask.clone(),
+            ProfileField::Ipv4Gateway => p.ipv4_gateway.clone(),
+            ProfileField::Ipv4Dns => p.ipv4_dns.clone(),
+        }
+    }
+
+    fn text_mut(&mut self) -> Option<&mut String> {
+        let p = &mut self.profile;
+        match self.field {
+            ProfileField::AddressOverride if p.mac_policy == MacPolicy::Override => {
+                Some(&mut p.address_override)
+            }
+            ProfileField::Ipv4Address => Some(&mut p.ipv4_address),
+            ProfileField::Ipv4Netmask => Some(&mut p.ipv4_netmask),
+            ProfileField::Ipv4Gateway => Some(&mut p.ipv4_gateway),
+            ProfileField::Ipv4Dns => Some(&mut p.ipv4_dns),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid(msg: &str) -> Result<(), NettuiError> {
+        Err(NettuiError::InvalidInput(msg.to_string()))
+    }
+
+    #[test]
+    fn validates_mac_and_static_ipv4() {
+        let mut form = ProfileForm::new(
+            "Home".to_string(),
+            "psk".to_string(),
+            KnownProfile::default(),
+        );
+        form.push(' ');
+        assert!(!form.profile.autoconnect);
+
+        form.field = ProfileField::AddressOverride;
+        form.push('x');
+        assert!(form.profile.address_override.is_empty());
+        form.field = ProfileField::MacPolicy;
+        form.cycle(false);
+        assert_eq!(form.profile.mac_policy, MacPolicy::Override);
+        assert_eq!(
+            form.profile.validate(),
+            invalid("MAC address must look like 02:00:00:00:00:01")
+        );
+        form.profile.address_override = "03:00:00:00:00:01".to_string();
+        assert_eq!(
+            form.profile.validate(),
+            invalid("MAC address must be unicast")
+        );
+        form.profile.address_override = "02:00:00:00:00:01".to_string();
+        assert_eq!(form.profile.validate(), Ok(()));
+
+        let p = &mut form.profile;
+        p.ipv4_gateway = "192.168.1.1".to_string();
+        assert_eq!(p.validate(), invalid("Static IPv4 needs an address"));
+        p.ipv4_address = "192.168.1.20".to_string();
+        p.ipv4_netmask = "255.255.0.255".to_string();
+        assert_eq!(p.validate(), invalid("Netmask is not valid"));
+        p.ipv4_netmask = "255.255.255.0".to_string();
+        p.ipv4_gateway = "192.168.2.1".to_string();
+        assert_eq!(p.validate(), invalid("Gateway is outside the IPv4 subnet"));
+        p.ipv4_gateway = "192.168.1.1".to_string();
+        p.ipv4_dns = "1.1.1.1, dns.example".to_string();
+        assert_eq!(p.validate(), invalid("DNS servers must be IPv4 addresses"));
+        p.ipv4_dns = "1.1.1.1, 9.9.9.9".to_string();
+        assert_eq!(p.validate(), Ok(()));
+        assert_eq!(p.dns_servers(), ["1.1.1.1", "9.9.9.9"]);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::error::NettuiError;
+use qrcode::{Color, EcLevel, QrCode};
+
+// The QR spec asks for four light modules around the symbol; scanners miss smaller margins.
+const QUIET_ZONE: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WifiShare {
+    pub ssid: String,
+    pub passphrase: Option<String>,
+    pub lines: Vec<String>,
+}
+
+impl WifiShare {
+    pub fn new(
+        ssid: &str,
+        security: &str,
+        hidden: bool,
+        passphrase: Option<String>,
+    ) -> Result<Self, NettuiError> {
+        let payload = wifi_payload(ssid, security, hidden, passphrase.as_deref())?;
+        let code = QrCode::with_error_correction_level(payload.as_bytes(), EcLevel::L)
+            .map_err(|e| NettuiError::InvalidInput(format!("Cannot encode QR code: {e}")))?;
+        Ok(Self {
+            ssid: ssid.to_string(),
+            passphrase,
+            lines: half_block_lines(&code),
+        })
+    }
+
+    pub fn width(&self) -> u16 {
+        self.lines.first().map_or(0, |l| l.chars().count() as u16)
+    }
+}
+
+pub fn wifi_payload(
+    ssid: &str,
+    security: &str,
+    hidden: bool,
+    passphrase: Option<&str>,
+) -> Result<String, NettuiError> {
+    let kind = match security.to_ascii_lowercase().as_str() {
+        "open" => "nopass",
+        "psk" | "wpa" | "wpa2" | "sae" => "WPA",
+        "wep" => "WEP",
+        _ => {
+            let msg = format!("{security} networks cannot be shared as a QR code");
+            return Err(NettuiError::InvalidInput(msg));
+        }
+    };
+    let mut out = format!("WIFI:T:{kind};S:{};", escape(ssid));
+    if kind != "nopass" {
+        let Some(passphrase) = passphrase.filter(|p| !p.is_empty()) else {
+            return Err(NettuiError::InvalidInput(format!(
+                "No passphrase stored for {ssid}"
+            )));
+        };
+        out.push_str(&format!("P:{};", escape(passphrase)));
+    }
+    if hidden {
+        out.push_str("H:true;");
+    }
+    out.push(';');
+    Ok(out)
+}
+
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+// Two module rows per text row. Light modules are the drawn ones, so the popup must paint
+// them white on black for the code to scan on light terminal themes too.
+fn half_block_lines(code: &QrCode) -> Vec<String> {
+    let width = code.width();
+    let colors = code.to_colors();
+    let size = width + 2 * QUIET_ZONE;
+    let light = |x: usize, y: usize| {
+        if x < QUIET_ZONE || y < QUIET_ZONE || x >= width + QUIET_ZONE || y >= width + QUIET_ZONE {
+            return true;
+        }
+        colors[(y - QUIET_ZONE) * width + (x - QUIET_ZONE)] == Color::Light
+    };
+
+    (0..size)
+        .step_by(2)
+        .map(|y| {
+            (0..size)
+                .map(|x| match (light(x, y), y + 1 < size && light(x, y + 1)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_escaped_payload_and_square_code() {
+        assert_eq!(
+            wifi_payload("Cafe;Bar", "Psk", true, Some(r#"a:b\c"#)).unwrap(),
+            r#"WIFI:T:WPA;S:Cafe\;Bar;P:a\:b\\c;H:true;;"#
+        );
+        assert_eq!(
+            wifi_payload("Guest", "open", false, None).unwrap(),
+            "WIFI:T:nopass;S:Guest;;"
+        );
+        assert!(wifi_payload("Home", "psk", false, None).is_err());
+        assert!(wifi_payload("Campus", "8021x", false, Some("x")).is_err());
+
+        let share = WifiShare::new("Guest", "open", false, None).unwrap();
+        let modules = 25 + 2 * 4;
+        assert_eq!(share.width() as usize, modules);
+        assert_eq!(share.lines.len(), modules.div_ceil(2));
+        let margin = |line: &String| line.chars().take(4).all(|c| c == '█');
+        assert!(share.lines[..2].iter().all(|l| l.chars().all(|c| c == '█')));
+        let (last, rows) = share.lines.split_last().unwrap();
+        assert!(rows.iter().all(margin));
+        assert!(last.chars().all(|c| c == '▀'));
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 #[derive(Debug, Clone)]
 pub struct WifiNetwork {
     pub ssid: String,
@@ -7,6 +9,50 @@ pub struct WifiNetwork {
     pub hidden: Option<bool>,
     pub autoconnect: Option<bool>,
     pub available: bool,
+    pub last_connected: Option<SystemTime>,
+}
+
+impl WifiNetwork {
+    pub fn signal_percent(&self) -> Option<i16> {
+        self.signal.trim().split('%').next()?.trim().parse().ok()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KnownSort {
+    #[default]
+    LastUsed,
+    Name,
+    Signal,
+}
+
+impl KnownSort {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::LastUsed => "last used",
+            Self::Name => "name",
+            Self::Signal => "signal",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::LastUsed => Self::Name,
+            Self::Name => Self::Signal,
+            Self::Signal => Self::LastUsed,
+        }
+    }
+
+    pub fn sort(self, networks: &mut [WifiNetwork]) {
+        networks.sort_by(|a, b| {
+            let by_name = a.ssid.cmp(&b.ssid);
+            match self {
+                Self::Name => by_name,
+                Self::LastUsed => b.last_connected.cmp(&a.last_connected).then(by_name),
+                Self::Signal => b.signal_percent().cmp(&a.signal_percent()).then(by_name),
+            }
+        });
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -23,24 +69,26 @@ pub struct WifiDeviceInfo {
 #[derive(Debug, Clone)]
 pub struct WifiState {
     pub ifaces: Vec<String>,
+    pub selected_iface: Option<String>,
     pub connected_ssid: Option<String>,
     pub known_networks: Vec<WifiNetwork>,
     pub unavailable_known_networks: Vec<WifiNetwork>,
     pub new_networks: Vec<WifiNetwork>,
     pub hidden_networks: Vec<WifiNetwork>,
-    pub device: Option<WifiDeviceInfo>,
+    pub adapters: Vec<WifiDeviceInfo>,
 }
 
 impl WifiState {
     pub fn empty() -> Self {
         Self {
             ifaces: Vec::new(),
+            selected_iface: None,
             connected_ssid: None,
             known_networks: Vec::new(),
             unavailable_known_networks: Vec::new(),
             new_networks: Vec::new(),
             hidden_networks: Vec::new(),
-            device: None,
+            adapters: Vec::new(),
         }
     }
 
@@ -51,4 +99,142 @@ impl WifiState {
     pub fn is_active(&self) -> bool {
         self.connected_ssid.is_some()
     }
+
+    pub fn selected_adapter(&self) -> Option<&WifiDeviceInfo> {
+        let iface = self.selected_iface.as_deref()?;
+        self.adapters.iter().find(|a| a.iface == iface)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecretKind {
+    Passphrase,
+    PrivateKeyPassphrase,
+    UserNameAndPassword,
+    UserPassword(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretRequest {
+    pub id: u64,
+    pub ssid: String,
+    pub kind: SecretKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct WifiSecret {
+    pub username: Option<String>,
+    pub password: String,
+}
+
+pub fn signal_label(percent: i16) -> String {
+    let signal = percent.clamp(0, 100);
+    match signal {
+        n if n >= 75 => format!("{signal:3}% 󰤨"),
+        n if (50..75).contains(&n) => format!("{signal:3}% 󰤥"),
+        n if (25..50).contains(&n) => format!("{signal:3}% 󰤢"),
+        _ => format!("{signal:3}% 󰤟"),
+    }
+}
+
+pub fn time_ago(then: SystemTime, now: SystemTime) -> String {
+    let secs = now.duration_since(then).map_or(0, |d| d.as_secs());
+    let plural = |n: u64, unit: &str| {
+        let s = if n == 1 { "" } else { "s" };
+        format!("{n} {unit}{s} ago")
+    };
+    match secs {
+        s if s < 60 => "just now".to_string(),
+        s if s < 3600 => format!("{} min ago", s / 60),
+        s if s < DAY => format!("{} h ago", s / 3600),
+        s if s < 30 * DAY => plural(s / DAY, "day"),
+        s if s < 365 * DAY => plural(s / (30 * DAY), "month"),
+        s => plural(s / (365 * DAY), "year"),
+    }
+}
+
+const DAY: u64 = 86_400;
+
+// Parses the `%Y-%m-%dT%H:%M:%SZ` timestamps iwd stores for known networks.
+pub fn parse_utc_timestamp(raw: &str) -> Option<SystemTime> {
+    let (date, time) = raw.trim().strip_suffix('Z')?.split_once('T')?;
+    let num = |s: Option<&str>| s?.parse::<i64>().ok();
+    let mut d = date.splitn(3, '-');
+    let (year, month, day) = (num(d.next())?, num(d.next())?, num(d.next())?);
+    let mut t = time.splitn(3, ':');
+    let (hour, min) = (num(t.next())?, num(t.next())?);
+    let sec = t.next()?.split('.').next()?.parse::<i64>().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || min > 59 || sec > 60 {
+        return None;
+    }
+
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    let secs = days * DAY as i64 + hour * 3600 + min * 60 + sec;
+    Some(UNIX_EPOCH + Duration::from_secs(u64::try_from(secs).ok()?))
+}
+
+pub fn pick_iface(ifaces: &[String], requested: Option<&str>) -> Option<String> {
+    requested
+        .filter(|r| ifaces.iter().any(|i| i == r))
+        .map(|r| r.to_string())
+        .or_else(|| ifaces.first().cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn net(ssid: &str, signal: i16, last_connected: Option<u64>) -> WifiNetwork {
+        WifiNetwork {
+            ssid: ssid.to_string(),
+            security: "psk".to_string(),
+            signal: signal_label(signal),
+            connected: false,
+            hidden: Some(false),
+            autoconnect: Some(true),
+            available: true,
+            last_connected: last_connected.map(|s| UNIX_EPOCH + Duration::from_secs(s)),
+        }
+    }
+
+    #[test]
+    fn sorts_known_networks_and_formats_last_used() {
+        let mut list = vec![
+            net("Cafe", 90, None),
+            net("Home", 40, Some(200)),
+            net("Work", 60, Some(100)),
+        ];
+        let order = |list: &[WifiNetwork]| list.iter().map(|n| n.ssid.clone()).collect::<Vec<_>>();
+        KnownSort::LastUsed.sort(&mut list);
+        assert_eq!(order(&list), ["Home", "Work", "Cafe"]);
+        KnownSort::Signal.sort(&mut list);
+        assert_eq!(order(&list), ["Cafe", "Work", "Home"]);
+        KnownSort::Name.sort(&mut list);
+        assert_eq!(order(&list), ["Cafe", "Home", "Work"]);
+
+        let then = parse_utc_timestamp("2026-10-18T08:30:00Z").unwrap();
+        assert_eq!(then, UNIX_EPOCH + Duration::from_secs(1_792_312_200));
+        assert_eq!(parse_utc_timestamp("2026-13-01T00:00:00Z"), None);
+        let ago = |secs| time_ago(then, then + Duration::from_secs(secs));
+        assert_eq!(ago(30), "just now");
+        assert_eq!(ago(2 * 3600 + 59), "2 h ago");
+        assert_eq!(ago(DAY), "1 day ago");
+        assert_eq!(ago(95 * DAY), "3 months ago");
+        assert_eq!(ago(800 * DAY), "2 years ago");
+    }
+
+    #[test]
+    fn pick_iface_falls_back_to_the_first_adapter() {
+        let ifaces = ["wlan0".to_string(), "wlan1".to_string()];
+        assert_eq!(pick_iface(&ifaces, Some("wlan1")).as_deref(), Some("wlan1"));
+        assert_eq!(pick_iface(&ifaces, Some("wlan7")).as_deref(), Some("wlan0"));
+        assert_eq!(pick_iface(&ifaces, None).as_deref(), Some("wlan0"));
+        assert_eq!(pick_iface(&[], Some("wlan1")), None);
+    }
 }
@@ -0,0 +1,206 @@
+use std::fmt;
+use zbus::DBusError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NettuiError {
+    PassphraseRequired(String),
+    WrongPassphrase(String),
+    InvalidFormat,
+    InvalidInput(String),
+    NetworkNotFound(String),
+    KnownNetworkNotFound(String),
+    NoSuchLink(String),
+    NotConfigured(String),
+    NotAvailable,
+    Aborted,
+    Busy,
+    ConnectFailed,
+    NotConnected,
+    PermissionDenied,
+    ServiceUnavailable(String),
+    CommandFailed { command: String, stderr: String },
+}
+
+impl NettuiError {
+    pub fn of(err: &anyhow::Error) -> Option<&Self> {
+        err.downcast_ref::<Self>()
+    }
+
+    // `service` names the daemon the call went to; bus errors such as ServiceUnknown come
+    // from the bus itself and do not say which one that was. `ssid` is the Wi-Fi network,
+    // or the interface name for systemd-networkd link calls.
+    pub fn from_dbus(err: &zbus::Error, service: &str, ssid: &str) -> Option<Self> {
+        let name = match err {
+            zbus::Error::MethodError(name, _, _) => name.as_str().to_string(),
+            zbus::Error::FDO(e) => e.name().as_str().to_string(),
+            _ => return None,
+        };
+        Some(match name.as_str() {
+            "net.connman.iwd.NoAgent" => Self::PassphraseRequired(ssid.to_string()),
+            "net.connman.iwd.Failed" => Self::ConnectFailed,
+            "net.connman.iwd.InvalidFormat" => Self::InvalidFormat,
+            "net.connman.iwd.NotAvailable" => Self::NotAvailable,
+            "net.connman.iwd.NotFound" => Self::NetworkNotFound(ssid.to_string()),
+            "net.connman.iwd.Aborted" => Self::Aborted,
+            "net.connman.iwd.NotConnected" => Self::NotConnected,
+            "net.connman.iwd.InProgress" | "net.connman.iwd.Busy" => Self::Busy,
+            "net.connman.iwd.NotConfigured" => Self::NotConfigured(ssid.to_string()),
+            "org.freedesktop.network1.NoSuchLink" => Self::NoSuchLink(ssid.to_string()),
+            "net.connman.iwd.PermissionDenied"
+            | "org.freedesktop.DBus.Error.AccessDenied"
+            | "org.freedesktop.DBus.Error.InteractiveAuthorizationRequired"
+            | "org.freedesktop.NetworkManager.PermissionDenied" => Self::PermissionDenied,
+            "org.freedesktop.DBus.Error.ServiceUnknown"
+            | "org.freedesktop.DBus.Error.NameHasNoOwner" => {
+                Self::ServiceUnavailable(service.to_string())
+            }
+            _ => return None,
+        })
+    }
+
+    pub fn wrap_dbus(err: zbus::Error, service: &str, ssid: &str) -> anyhow::Error {
+        match Self::from_dbus(&err, service, ssid) {
+            Some(typed) => typed.into(),
+            None => err.into(),
+        }
+    }
+
+    pub fn prompts_again(&self, passphrase_tried: bool) -> bool {
+        match self {
+            Self::PassphraseRequired(_) => !passphrase_tried,
+            Self::WrongPassphrase(_) | Self::InvalidFormat => passphrase_tried,
+            _ => false,
+        }
+    }
+
+    pub fn user_message(&self, action: &str) -> String {
+        match self {
+            Self::PassphraseRequired(_) => {
+                format!("{action} needs Wi-Fi credentials. Use connect again and enter passphrase.")
+            }
+            Self::WrongPassphrase(ssid) => format!("Wrong passphrase for {ssid}. Try again."),
+            Self::InvalidFormat => {
+                "Passphrase must be 8-63 characters or 64 hex digits.".to_string()
+            }
+            Self::InvalidInput(msg) => msg.clone(),
+            Self::NotConfigured(ssid) => {
+                format!("{action} failed: {ssid} has no saved configuration.")
+            }
+            Self::NoSuchLink(iface) => {
+                format!("{action} failed: systemd-networkd does not manage {iface}.")
+            }
+            Self::NotAvailable => format!("{action} failed: network is out of range."),
+            Self::Aborted => format!("{action} was aborted."),
+            Self::Busy => format!("{action}: another Wi-Fi operation is still running."),
+            Self::PermissionDenied => {
+                format!(
+                    "{action} requires elevated permissions. Run with proper privileges and retry."
+                )
+            }
+            Self::ServiceUnavailable(service) => {
+                format!("{action} failed: {service} is not running.")
+            }
+            _ => format!("{action} failed: {self}"),
+        }
+    }
+}
+
+impl fmt::Display for NettuiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PassphraseRequired(ssid) => write!(f, "passphrase required for {ssid}"),
+            Self::WrongPassphrase(ssid) => write!(f, "invalid passphrase for {ssid}"),
+            Self::InvalidFormat => write!(f, "passphrase has an invalid format"),
+            Self::InvalidInput(msg) => write!(f, "{msg}"),
+            Self::NetworkNotFound(ssid) => write!(f, "network not found: {ssid}"),
+            Self::KnownNetworkNotFound(ssid) => write!(f, "known network not found: {ssid}"),
+            Self::NoSuchLink(iface) => write!(f, "unknown link: {iface}"),
+            Self::NotConfigured(ssid) => write!(f, "network is not configured: {ssid}"),
+            Self::NotAvailable => write!(f, "network is not available"),
+            Self::Aborted => write!(f, "operation aborted"),
+            Self::Busy => write!(f, "another operation is in progress"),
+            Self::ConnectFailed => write!(f, "connection failed"),
+            Self::NotConnected => write!(f, "not connected"),
+            Self::PermissionDenied => write!(f, "permission denied"),
+            Self::ServiceUnavailable(service) => write!(f, "{service} is not running"),
+            Self::CommandFailed { command, stderr } if stderr.is_empty() => {
+                write!(f, "{command} failed")
+            }
+            Self::CommandFailed { command, stderr } => write!(f, "{command} failed: {stderr}"),
+        }
+    }
+}
+
+impl std::error::Error for NettuiError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zbus::{message::Message, names::OwnedErrorName};
+
+    fn method_error(name: &str) -> zbus::Error {
+        let reply = Message::method_call("/", "Connect")
+            .unwrap()
+            .build(&())
+            .unwrap();
+        zbus::Error::MethodError(
+            OwnedErrorName::try_from(name).unwrap(),
+            Some("details".to_string()),
+            reply,
+        )
+    }
+
+    #[test]
+    fn maps_dbus_error_names() {
+        let cases = [
+            (
+                "net.connman.iwd.NoAgent",
+                NettuiError::PassphraseRequired("Home".to_string()),
+            ),
+            ("net.connman.iwd.Failed", NettuiError::ConnectFailed),
+            ("net.connman.iwd.InvalidFormat", NettuiError::InvalidFormat),
+            ("net.connman.iwd.NotAvailable", NettuiError::NotAvailable),
+            ("net.connman.iwd.Aborted", NettuiError::Aborted),
+            (
+                "net.connman.iwd.PermissionDenied",
+                NettuiError::PermissionDenied,
+            ),
+            (
+                "net.connman.iwd.NotConfigured",
+                NettuiError::NotConfigured("Home".to_string()),
+            ),
+            (
+                "org.freedesktop.DBus.Error.ServiceUnknown",
+                NettuiError::ServiceUnavailable("iwd".to_string()),
+            ),
+            (
+                "org.freedesktop.network1.NoSuchLink",
+                NettuiError::NoSuchLink("Home".to_string()),
+            ),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(
+                NettuiError::from_dbus(&method_error(name), "iwd", "Home"),
+                Some(expected)
+            );
+        }
+        assert_eq!(
+            NettuiError::from_dbus(&method_error("net.connman.iwd.Unknown"), "iwd", "Home"),
+            None
+        );
+
+        let denied = zbus::Error::FDO(Box::new(zbus::fdo::Error::AccessDenied(
+            "polkit".to_string(),
+        )));
+        assert_eq!(
+            NettuiError::from_dbus(&denied, "NetworkManager", "Home"),
+            Some(NettuiError::PermissionDenied)
+        );
+
+        let err = NettuiError::wrap_dbus(method_error("net.connman.iwd.NoAgent"), "iwd", "Home");
+        let typed = NettuiError::of(&err).unwrap();
+        assert!(typed.prompts_again(false));
+        assert!(!typed.prompts_again(true));
+        assert!(NettuiError::WrongPassphrase("Home".to_string()).prompts_again(true));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::domain::{ethernet::EthernetChange, wifi::SecretRequest};
 use anyhow::Result;
 use crossterm::event::{Event as CrosstermEvent, KeyEvent};
 use std::time::Duration;
@@ -8,6 +9,15 @@ pub enum Event {
     Tick,
     Key(KeyEvent),
     Resize(u16, u16),
+    Backend(BackendEvent),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BackendEvent {
+    WifiChanged,
+    SecretRequested(SecretRequest),
+    SecretCanceled,
+    EthernetChanged(EthernetChange),
 }
 
 #[derive(Debug)]
@@ -37,10 +47,10 @@ impl EventHandler {
                     Ok(true) => {
                         if let Ok(evt) = crossterm::event::read() {
                             match evt {
-                                CrosstermEvent::Key(key) => {
-                                    if key.kind == crossterm::event::KeyEventKind::Press {
-                                        let _ = sender_cloned.send(Event::Key(key));
-                                    }
+                                CrosstermEvent::Key(key)
+                                    if key.kind == crossterm::event::KeyEventKind::Press =>
+                                {
+                                    let _ = sender_cloned.send(Event::Key(key));
                                 }
                                 CrosstermEvent::Resize(x, y) => {
                                     let _ = sender_cloned.send(Event::Resize(x, y));
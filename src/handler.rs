@@ -1,6 +1,10 @@
 use crate::{
     app::App,
-    domain::common::{ActiveTab, WifiFocus},
+    domain::{
+        common::{ActiveTab, WifiFocus},
+        enterprise::EnterpriseField,
+        profile::ProfileField,
+    },
 };
 use anyhow::Result;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
@@ -10,6 +14,7 @@ pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> Result<()>
         match key_event.code {
             KeyCode::Esc => app.close_wifi_passphrase_prompt(),
             KeyCode::Enter => app.submit_wifi_passphrase_connect().await,
+            KeyCode::Tab | KeyCode::BackTab => app.toggle_prompt_field(),
             KeyCode::Backspace => app.passphrase_input_backspace(),
             KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
                 app.passphrase_input_push(c)
@@ -19,6 +24,51 @@ pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> Result<()>
         return Ok(());
     }
 
+    if let Some(form) = app.enterprise_form.as_mut() {
+        match key_event.code {
+            KeyCode::Esc => app.close_enterprise_form(),
+            KeyCode::Enter => app.submit_enterprise_form(),
+            KeyCode::Tab | KeyCode::Down => form.next_field(),
+            KeyCode::BackTab | KeyCode::Up => form.prev_field(),
+            KeyCode::Left if form.field == EnterpriseField::Method => form.cycle_method(false),
+            KeyCode::Right if form.field == EnterpriseField::Method => form.cycle_method(true),
+            KeyCode::Backspace => form.backspace(),
+            KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                form.push(c)
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if app.wifi_share.is_some() {
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Enter => app.close_wifi_share(),
+            KeyCode::Char(c) if c.eq_ignore_ascii_case(&app.keybinds.wifi_share) => {
+                app.close_wifi_share()
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if let Some(form) = app.profile_form.as_mut() {
+        match key_event.code {
+            KeyCode::Esc => app.close_profile_form(),
+            KeyCode::Enter => app.submit_profile_form(),
+            KeyCode::Tab | KeyCode::Down => form.next_field(),
+            KeyCode::BackTab | KeyCode::Up => form.prev_field(),
+            KeyCode::Left if form.field == ProfileField::MacPolicy => form.cycle(false),
+            KeyCode::Right if form.field == ProfileField::MacPolicy => form.cycle(true),
+            KeyCode::Backspace => form.backspace(),
+            KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                form.push(c)
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
+
     if app.hidden_connect_prompt {
         match key_event.code {
             KeyCode::Esc => app.close_hidden_connect_prompt(),
@@ -53,9 +103,11 @@ pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> Result<()>
         KeyCode::Up => app.select_prev(),
         KeyCode::Char(c) if c.eq_ignore_ascii_case(&app.keybinds.up) => app.select_prev(),
 
+        KeyCode::Char(c) if c.eq_ignore_ascii_case(&app.keybinds.cancel) => app.cancel_wifi_job(),
+
         KeyCode::Char(c) if c.eq_ignore_ascii_case(&app.keybinds.refresh) => {
             app.clear_error();
-            app.refresh_current().await;
+            app.refresh_current();
         }
 
         KeyCode::Char(c)
@@ -83,7 +135,7 @@ pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> Result<()>
                 && c.eq_ignore_ascii_case(&app.keybinds.wifi_forget) =>
         {
             app.clear_error();
-            app.wifi_forget_selected().await?;
+            app.wifi_forget_selected();
         }
 
         KeyCode::Char(c)
@@ -92,7 +144,32 @@ pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> Result<()>
                 && c.eq_ignore_ascii_case(&app.keybinds.wifi_autoconnect) =>
         {
             app.clear_error();
-            app.wifi_toggle_autoconnect_selected().await?;
+            app.wifi_toggle_autoconnect_selected();
+        }
+
+        KeyCode::Char(c)
+            if app.active_tab == ActiveTab::Wifi
+                && app.wifi_focus == WifiFocus::KnownNetworks
+                && c.eq_ignore_ascii_case(&app.keybinds.wifi_edit) =>
+        {
+            app.clear_error();
+            app.wifi_edit_selected_profile();
+        }
+
+        KeyCode::Char(c)
+            if app.active_tab == ActiveTab::Wifi
+                && app.wifi_focus == WifiFocus::KnownNetworks
+                && c.eq_ignore_ascii_case(&app.keybinds.wifi_sort) =>
+        {
+            app.cycle_known_sort();
+        }
+
+        KeyCode::Char(c)
+            if app.active_tab == ActiveTab::Wifi
+                && c.eq_ignore_ascii_case(&app.keybinds.wifi_share) =>
+        {
+            app.clear_error();
+            app.wifi_share_network();
         }
 
         KeyCode::Char(c)
@@ -114,6 +191,13 @@ pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> Result<()>
             app.wifi_connect_or_disconnect().await?;
         }
 
+        KeyCode::Enter | KeyCode::Char(' ')
+            if app.active_tab == ActiveTab::Wifi && app.wifi_focus == WifiFocus::Adapter =>
+        {
+            app.clear_error();
+            app.wifi_use_selected_adapter();
+        }
+
         KeyCode::Char(c)
             if app.active_tab == ActiveTab::Wifi
                 && c.eq_ignore_ascii_case(&app.keybinds.wifi_details) =>
@@ -126,14 +210,34 @@ pub async fn handle_key_events(key_event: KeyEvent, app: &mut App) -> Result<()>
                 && c.eq_ignore_ascii_case(&app.keybinds.ethernet_renew) =>
         {
             app.clear_error();
-            if let Err(e) = app.ethernet_renew_dhcp().await {
+            if let Err(e) = app.ethernet_renew_dhcp() {
+                app.last_error = Some(e.to_string());
+            }
+        }
+
+        KeyCode::Char(c)
+            if app.active_tab == ActiveTab::Ethernet
+                && c.eq_ignore_ascii_case(&app.keybinds.ethernet_reconfigure) =>
+        {
+            app.clear_error();
+            if let Err(e) = app.ethernet_reconfigure() {
+                app.last_error = Some(e.to_string());
+            }
+        }
+
+        KeyCode::Char(c)
+            if app.active_tab == ActiveTab::Ethernet
+                && c.eq_ignore_ascii_case(&app.keybinds.ethernet_force_renew) =>
+        {
+            app.clear_error();
+            if let Err(e) = app.ethernet_force_renew() {
                 app.last_error = Some(e.to_string());
             }
         }
 
         KeyCode::Enter if app.active_tab == ActiveTab::Ethernet => {
             app.clear_error();
-            if let Err(e) = app.ethernet_toggle_link().await {
+            if let Err(e) = app.ethernet_toggle_link() {
                 app.last_error = Some(e.to_string());
             }
         }
@@ -0,0 +1,257 @@
+use anyhow::{Result, anyhow};
+use std::{future::Future, time::Duration};
+use tokio::{
+    sync::oneshot::{self, error::TryRecvError},
+    task::JoinHandle,
+    time::Instant,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobKind {
+    WifiScan,
+    WifiConnect,
+    WifiAbort,
+    WifiProfile,
+    Ethernet,
+}
+
+#[derive(Debug)]
+pub enum JobOutcome<T> {
+    Finished(Result<T>),
+    TimedOut,
+    Canceled,
+}
+
+#[derive(Debug, Clone)]
+pub struct JobInfo {
+    pub id: u64,
+    pub kind: JobKind,
+    pub label: String,
+    pub started_at: Instant,
+    pub timeout: Duration,
+}
+
+impl JobInfo {
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.started_at)
+    }
+
+    fn expired(&self, now: Instant) -> bool {
+        self.elapsed(now) > self.timeout
+    }
+}
+
+pub type Completion<C> = Box<dyn FnOnce(&mut C)>;
+
+type Callback<C, T> = Box<dyn FnOnce(&mut C, JobOutcome<T>)>;
+
+trait Pending<C> {
+    fn try_finish(&mut self) -> Option<Completion<C>>;
+    fn stop(self: Box<Self>, outcome: Stop) -> Completion<C>;
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Stop {
+    TimedOut,
+    Canceled,
+}
+
+struct Task<C, T> {
+    handle: JoinHandle<()>,
+    result: oneshot::Receiver<Result<T>>,
+    done: Option<Callback<C, T>>,
+}
+
+impl<C: 'static, T: 'static> Pending<C> for Task<C, T> {
+    fn try_finish(&mut self) -> Option<Completion<C>> {
+        let result = match self.result.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Closed) => Err(anyhow!("job task failed")),
+        };
+        let done = self.done.take()?;
+        Some(Box::new(move |ctx| done(ctx, JobOutcome::Finished(result))))
+    }
+
+    fn stop(mut self: Box<Self>, outcome: Stop) -> Completion<C> {
+        self.handle.abort();
+        let done = self.done.take();
+        Box::new(move |ctx| {
+            if let Some(done) = done {
+                done(
+                    ctx,
+                    match outcome {
+                        Stop::TimedOut => JobOutcome::TimedOut,
+                        Stop::Canceled => JobOutcome::Canceled,
+                    },
+                );
+            }
+        })
+    }
+}
+
+struct Job<C> {
+    info: JobInfo,
+    task: Box<dyn Pending<C>>,
+}
+
+pub struct JobManager<C> {
+    next_id: u64,
+    jobs: Vec<Job<C>>,
+}
+
+impl<C: 'static> Default for JobManager<C> {
+    fn default() -> Self {
+        Self {
+            next_id: 1,
+            jobs: Vec::new(),
+        }
+    }
+}
+
+impl<C: 'static> JobManager<C> {
+    pub fn spawn<T, F, D>(
+        &mut self,
+        kind: JobKind,
+        label: impl Into<String>,
+        timeout: Duration,
+        future: F,
+        done: D,
+    ) -> u64
+    where
+        T: Send + 'static,
+        F: Future<Output = Result<T>> + Send + 'static,
+        D: FnOnce(&mut C, JobOutcome<T>) + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let handle = tokio::spawn(async move {
+            let _ = tx.send(future.await);
+        });
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.jobs.push(Job {
+            info: JobInfo {
+                id,
+                kind,
+                label: label.into(),
+                started_at: Instant::now(),
+                timeout,
+            },
+            task: Box::new(Task {
+                handle,
+                result: rx,
+                done: Some(Box::new(done)),
+            }),
+        });
+        id
+    }
+
+    pub fn poll(&mut self, now: Instant, exempt: impl Fn(&JobInfo) -> bool) -> Vec<Completion<C>> {
+        let mut completions = Vec::new();
+        let mut i = 0;
+        while i < self.jobs.len() {
+            if let Some(done) = self.jobs[i].task.try_finish() {
+                self.jobs.remove(i);
+                completions.push(done);
+            } else if self.jobs[i].info.expired(now) && !exempt(&self.jobs[i].info) {
+                let job = self.jobs.remove(i);
+                completions.push(job.task.stop(Stop::TimedOut));
+            } else {
+                i += 1;
+            }
+        }
+        completions
+    }
+
+    pub fn cancel(&mut self, kind: JobKind) -> Option<Completion<C>> {
+        let pos = self.jobs.iter().position(|j| j.info.kind == kind)?;
+        Some(self.jobs.remove(pos).task.stop(Stop::Canceled))
+    }
+
+    pub fn restart_timer(&mut self, kind: JobKind) {
+        let now = Instant::now();
+        for job in self.jobs.iter_mut().filter(|j| j.info.kind == kind) {
+            job.info.started_at = now;
+        }
+    }
+
+    pub fn get(&self, kind: JobKind) -> Option<&JobInfo> {
+        self.running().find(|j| j.kind == kind)
+    }
+
+    pub fn is_running(&self, kind: JobKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    pub fn running(&self) -> impl Iterator<Item = &JobInfo> {
+        self.jobs.iter().map(|j| &j.info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn completes_times_out_and_cancels() {
+        let mut jobs: JobManager<Vec<String>> = JobManager::default();
+        let mut log = Vec::new();
+        let record = |label: &'static str| {
+            move |log: &mut Vec<String>, outcome: JobOutcome<u32>| {
+                log.push(match outcome {
+                    JobOutcome::Finished(Ok(v)) => format!("{label} ok {v}"),
+                    JobOutcome::Finished(Err(e)) => format!("{label} err {e}"),
+                    JobOutcome::TimedOut => format!("{label} timed out"),
+                    JobOutcome::Canceled => format!("{label} canceled"),
+                })
+            }
+        };
+
+        jobs.spawn(
+            JobKind::WifiScan,
+            "quick",
+            Duration::from_secs(5),
+            async { Ok(7) },
+            record("quick"),
+        );
+        jobs.spawn(
+            JobKind::WifiConnect,
+            "slow",
+            Duration::from_secs(5),
+            std::future::pending(),
+            record("slow"),
+        );
+        tokio::task::yield_now().await;
+
+        for done in jobs.poll(Instant::now(), |_| false) {
+            done(&mut log);
+        }
+        assert_eq!(log, ["quick ok 7"]);
+        assert!(jobs.is_running(JobKind::WifiConnect));
+
+        tokio::time::advance(Duration::from_secs(6)).await;
+        assert!(jobs.poll(Instant::now(), |_| true).is_empty());
+        for done in jobs.poll(Instant::now(), |_| false) {
+            done(&mut log);
+        }
+        assert_eq!(log, ["quick ok 7", "slow timed out"]);
+        assert!(jobs.is_empty());
+
+        jobs.spawn(
+            JobKind::WifiConnect,
+            "again",
+            Duration::from_secs(5),
+            std::future::pending(),
+            record("again"),
+        );
+        assert!(jobs.cancel(JobKind::WifiScan).is_none());
+        (jobs.cancel(JobKind::WifiConnect).unwrap())(&mut log);
+        assert_eq!(log.last().unwrap(), "again canceled");
+        assert!(jobs.is_empty());
+    }
+}
@@ -18,7 +18,13 @@ pub struct Keybinds {
     pub wifi_autoconnect: char,
     pub wifi_hidden: char,
     pub wifi_details: char,
+    pub wifi_edit: char,
+    pub wifi_sort: char,
+    pub wifi_share: char,
+    pub cancel: char,
     pub ethernet_renew: char,
+    pub ethernet_reconfigure: char,
+    pub ethernet_force_renew: char,
 }
 
 impl Default for Keybinds {
@@ -36,7 +42,13 @@ impl Default for Keybinds {
             wifi_autoconnect: 't',
             wifi_hidden: 'n',
             wifi_details: 'i',
+            wifi_edit: 'e',
+            wifi_sort: 'o',
+            wifi_share: 'p',
+            cancel: 'x',
             ethernet_renew: 'n',
+            ethernet_reconfigure: 'c',
+            ethernet_force_renew: 'f',
         }
     }
 }
@@ -72,7 +84,13 @@ impl Keybinds {
         apply_override(&mut out.wifi_autoconnect, keys.wifi_autoconnect);
         apply_override(&mut out.wifi_hidden, keys.wifi_hidden);
         apply_override(&mut out.wifi_details, keys.wifi_details);
+        apply_override(&mut out.wifi_edit, keys.wifi_edit);
+        apply_override(&mut out.wifi_sort, keys.wifi_sort);
+        apply_override(&mut out.wifi_share, keys.wifi_share);
+        apply_override(&mut out.cancel, keys.cancel);
         apply_override(&mut out.ethernet_renew, keys.ethernet_renew);
+        apply_override(&mut out.ethernet_reconfigure, keys.ethernet_reconfigure);
+        apply_override(&mut out.ethernet_force_renew, keys.ethernet_force_renew);
 
         out
     }
@@ -97,7 +115,13 @@ struct KeybindsPartial {
     wifi_autoconnect: Option<String>,
     wifi_hidden: Option<String>,
     wifi_details: Option<String>,
+    wifi_edit: Option<String>,
+    wifi_sort: Option<String>,
+    wifi_share: Option<String>,
+    cancel: Option<String>,
     ethernet_renew: Option<String>,
+    ethernet_reconfigure: Option<String>,
+    ethernet_force_renew: Option<String>,
 }
 
 fn keybinds_path() -> Option<PathBuf> {
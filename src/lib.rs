@@ -1,8 +1,10 @@
 pub mod app;
 pub mod backend;
 pub mod domain;
+pub mod error;
 pub mod event;
 pub mod handler;
+pub mod jobs;
 pub mod keybinds;
 pub mod tui;
 pub mod ui;
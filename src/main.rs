@@ -1,15 +1,19 @@
 use anyhow::Result;
 use nettui::{
     app::{App, AppConfig},
+    backend::Backends,
     event::{Event, EventHandler},
     handler::handle_key_events,
     tui::Tui,
 };
 use ratatui::{Terminal, backend::CrosstermBackend};
-use std::io;
+use std::{env, io, path::PathBuf};
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let demo = parse_args(env::args().skip(1))?;
+    let demo_backends = demo.as_deref().map(Backends::demo).transpose()?;
+
     let backend = CrosstermBackend::new(io::stdout());
     let terminal = Terminal::new(backend)?;
 
@@ -18,7 +22,11 @@ async fn main() -> Result<()> {
 
     let config = AppConfig::default();
     let mut events = EventHandler::new(config.tick_ms);
-    let mut app = App::new(config).await?;
+    let mut app = match demo_backends {
+        Some(backends) => App::with_backends(config, backends).await?,
+        None => App::new(config).await?,
+    };
+    app.watch_backend_events(events.sender.clone());
 
     while app.running {
         tui.draw(&mut app)?;
@@ -29,9 +37,27 @@ async fn main() -> Result<()> {
                 handle_key_events(key_event, &mut app).await?;
             }
             Event::Resize(_, _) => {}
+            Event::Backend(event) => app.handle_backend_event(event),
         }
     }
 
     tui.exit()?;
     Ok(())
 }
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<PathBuf>> {
+    let mut demo = None;
+    while let Some(arg) = args.next() {
+        if let Some(path) = arg.strip_prefix("--demo=") {
+            demo = Some(PathBuf::from(path));
+        } else if arg == "--demo" {
+            let path = args
+                .next()
+                .ok_or_else(|| io::Error::other("--demo needs a fixture file"))?;
+            demo = Some(PathBuf::from(path));
+        } else {
+            return Err(io::Error::other(format!("unknown argument: {arg}")).into());
+        }
+    }
+    Ok(demo)
+}
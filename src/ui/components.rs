@@ -1,6 +1,7 @@
 use crate::{
     app::App,
     domain::common::{ActiveTab, ToastKind, WifiFocus},
+    jobs::JobInfo,
 };
 use ratatui::{
     Frame,
@@ -9,6 +10,7 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Clear, Paragraph, Tabs},
 };
+use tokio::time::Instant;
 
 pub fn render_tabs(app: &App, frame: &mut Frame, area: Rect) {
     let titles = [" Wi-Fi ", " Ethernet "];
@@ -45,7 +47,12 @@ pub fn render_footer(app: &App, frame: &mut Frame, area: Rect) {
     let wifi_autoconnect = app.keybinds.wifi_autoconnect.to_string();
     let wifi_hidden = app.keybinds.wifi_hidden.to_string();
     let wifi_details = app.keybinds.wifi_details.to_string();
+    let wifi_edit = app.keybinds.wifi_edit.to_string();
+    let wifi_sort = app.keybinds.wifi_sort.to_string();
+    let wifi_share = app.keybinds.wifi_share.to_string();
     let ethernet_renew = app.keybinds.ethernet_renew.to_string();
+    let ethernet_reconfigure = app.keybinds.ethernet_reconfigure.to_string();
+    let ethernet_force_renew = app.keybinds.ethernet_force_renew.to_string();
 
     let mut line1 = vec![
         Span::from(format!("{prev_tab},←")).bold(),
@@ -87,6 +94,15 @@ pub fn render_footer(app: &App, frame: &mut Frame, area: Rect) {
                 Span::from(wifi_autoconnect).bold(),
                 Span::from(" autoconnect"),
                 Span::from(" | "),
+                Span::from(wifi_edit).bold(),
+                Span::from(" edit"),
+                Span::from(" | "),
+                Span::from(wifi_sort).bold(),
+                Span::from(" sort"),
+                Span::from(" | "),
+                Span::from(wifi_share).bold(),
+                Span::from(" share"),
+                Span::from(" | "),
                 Span::from(wifi_scan.clone()).bold(),
                 Span::from(" scan"),
             ]),
@@ -104,6 +120,9 @@ pub fn render_footer(app: &App, frame: &mut Frame, area: Rect) {
                 Span::from(" scan"),
             ]),
             WifiFocus::Adapter => line2.extend([
+                Span::from("↵").bold(),
+                Span::from(" use adapter"),
+                Span::from(" | "),
                 Span::from(wifi_scan).bold(),
                 Span::from(" scan"),
                 Span::from(" | "),
@@ -118,10 +137,24 @@ pub fn render_footer(app: &App, frame: &mut Frame, area: Rect) {
                 Span::from(" | "),
                 Span::from(ethernet_renew).bold(),
                 Span::from(" renew DHCP"),
+                Span::from(" | "),
+                Span::from(ethernet_reconfigure).bold(),
+                Span::from(" reconfigure"),
+                Span::from(" | "),
+                Span::from(ethernet_force_renew).bold(),
+                Span::from(" force renew"),
             ]);
         }
     }
 
+    if app.wifi_scanning_active() || app.wifi_connect_active() {
+        line2.extend([
+            Span::from(" | "),
+            Span::from(app.keybinds.cancel.to_string()).bold(),
+            Span::from(" cancel"),
+        ]);
+    }
+
     line2.extend([
         Span::from(" | "),
         Span::from(quit).bold(),
@@ -162,9 +195,13 @@ pub fn render_toast_popup(frame: &mut Frame, kind: ToastKind, msg: &str) {
         ToastKind::Info => (" Info ", Color::Cyan),
     };
 
-    let lines = msg.lines().count().max(1) as u16;
     let width = frame.area().width.saturating_sub(2).clamp(24, 58);
-    let height = (lines + 2).clamp(4, 8);
+    let inner_width = usize::from(width - 2);
+    let lines: usize = msg
+        .lines()
+        .map(|l| l.chars().count().div_ceil(inner_width).max(1))
+        .sum();
+    let height = (lines.max(1) as u16 + 2).clamp(4, 10);
     let area = top_right_rect(width, height, frame.area());
     frame.render_widget(Clear, area);
 
@@ -185,6 +222,42 @@ pub fn render_toast_popup(frame: &mut Frame, kind: ToastKind, msg: &str) {
     frame.render_widget(p, inner);
 }
 
+pub fn render_jobs_panel(frame: &mut Frame, jobs: &[&JobInfo], now: Instant) {
+    let lines: Vec<Line> = jobs
+        .iter()
+        .map(|job| {
+            Line::from(vec![
+                Span::from(job.label.clone()),
+                Span::from(format!(
+                    " {}s/{}s",
+                    job.elapsed(now).as_secs(),
+                    job.timeout.as_secs()
+                ))
+                .fg(Color::DarkGray),
+            ])
+        })
+        .collect();
+
+    let longest = lines.iter().map(Line::width).max().unwrap_or(0) as u16;
+    let width = (longest + 2).clamp(24, 46);
+    let height = (lines.len() as u16 + 2).clamp(3, 8);
+    let area = bottom_right_rect(width, height, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Jobs ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Thick)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    frame.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(Color::White)),
+        inner,
+    );
+}
+
 pub fn render_too_small(frame: &mut Frame, area: Rect, min_w: u16, min_h: u16) {
     let block = Block::default()
         .title(" nettui ")
@@ -195,7 +268,7 @@ pub fn render_too_small(frame: &mut Frame, area: Rect, min_w: u16, min_h: u16) {
     frame.render_widget(block, area);
 
     let msg = format!(
-        "Terminal is too small.\\n\\nMinimum size: {}x{}\\nCurrent size:  {}x{}",
+        "Terminal is too small.\n\nMinimum size: {}x{}\nCurrent size:  {}x{}",
         min_w, min_h, area.width, area.height
     );
 
@@ -237,3 +310,17 @@ fn top_right_rect(width: u16, height: u16, area: Rect) -> Rect {
     let y = area.y.saturating_add(margin_y);
     Rect::new(x, y, width, height)
 }
+
+fn bottom_right_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let margin_x: u16 = 1;
+    let margin_y: u16 = 5;
+    let width = width.min(area.width.saturating_sub(margin_x.saturating_mul(2)));
+    let height = height.min(area.height.saturating_sub(margin_y.saturating_mul(2)));
+    let x = area
+        .x
+        .saturating_add(area.width.saturating_sub(width + margin_x));
+    let y = area
+        .y
+        .saturating_add(area.height.saturating_sub(height + margin_y));
+    Rect::new(x, y, width, height)
+}
@@ -1,4 +1,4 @@
-use crate::app::App;
+use crate::{app::App, jobs::JobKind};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
@@ -6,6 +6,9 @@ use ratatui::{
     text::{Line, Span, Text},
     widgets::{Block, BorderType, Borders, Cell, Paragraph, Row, Table},
 };
+use tokio::time::Instant;
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 
 pub fn render(app: &mut App, frame: &mut Frame, area: Rect) {
     let chunks = Layout::default()
@@ -55,22 +58,69 @@ fn render_details(app: &mut App, frame: &mut Frame, area: Rect) {
                     .unwrap_or_else(|| "-".into()),
             ),
         ]));
+        if let Some(managed) = &d.managed {
+            lines.push(Line::from(vec![
+                Span::from("networkd: ").bold(),
+                Span::from(managed.setup.clone()).fg(setup_color(&managed.setup)),
+                Span::from(format!(
+                    " (oper {}, addr {})",
+                    managed.operational, managed.address
+                )),
+            ]));
+        }
         lines.push(Line::from(vec![
             Span::from("MAC: ").bold(),
             Span::from(d.mac.clone().unwrap_or_else(|| "-".into())),
         ]));
+        lines.push(Line::from(vec![
+            Span::from("MTU: ").bold(),
+            Span::from(d.mtu.map(|m| m.to_string()).unwrap_or_else(|| "-".into())),
+            Span::from("  Index: ").bold(),
+            Span::from(d.ifindex.to_string()),
+        ]));
+        lines.push(Line::from(vec![
+            Span::from("Flags: ").bold(),
+            Span::from(if d.flags.is_empty() {
+                "-".to_string()
+            } else {
+                d.flags.join(",")
+            }),
+        ]));
         lines.push(Line::from(vec![
             Span::from("Gateway v4: ").bold(),
             Span::from(d.gateway_v4.clone().unwrap_or_else(|| "-".into())),
         ]));
+        lines.push(Line::from(vec![
+            Span::from("Gateway v6: ").bold(),
+            Span::from(d.gateway_v6.clone().unwrap_or_else(|| "-".into())),
+        ]));
+
+        for (label, ips) in [("IPv4:", &d.ipv4), ("IPv6:", &d.ipv6)] {
+            lines.push(Line::from(""));
+            lines.push(Line::from(label));
+            if ips.is_empty() {
+                lines.push(Line::from("  -"));
+            } else {
+                for ip in ips {
+                    lines.push(Line::from(format!("  {ip}")));
+                }
+            }
+        }
 
         lines.push(Line::from(""));
-        lines.push(Line::from("IPv4:"));
-        if d.ipv4.is_empty() {
+        lines.push(Line::from("Routes:"));
+        if d.routes.is_empty() {
             lines.push(Line::from("  -"));
         } else {
-            for ip in &d.ipv4 {
-                lines.push(Line::from(format!("  {ip}")));
+            for route in &d.routes {
+                let mut line = format!("  {}", route.destination);
+                if let Some(gw) = &route.gateway {
+                    line.push_str(&format!(" via {gw}"));
+                }
+                if let Some(metric) = route.metric {
+                    line.push_str(&format!(" metric {metric}"));
+                }
+                lines.push(Line::from(line));
             }
         }
 
@@ -107,6 +157,11 @@ fn render_ifaces(app: &mut App, frame: &mut Frame, area: Rect) {
                 .map(|s| s.to_string())
                 .unwrap_or_else(|| "-".to_string());
             let active = if d.is_active() { "󰀂" } else { "" };
+            let setup = d
+                .managed
+                .as_ref()
+                .map(|m| m.setup.clone())
+                .unwrap_or_else(|| "-".to_string());
 
             Row::new(vec![
                 Cell::from(active),
@@ -114,11 +169,21 @@ fn render_ifaces(app: &mut App, frame: &mut Frame, area: Rect) {
                 Cell::from(d.operstate.clone()),
                 Cell::from(carrier),
                 Cell::from(speed),
+                Cell::from(setup.clone()).style(Style::default().fg(setup_color(&setup))),
                 Cell::from(d.ipv4.first().cloned().unwrap_or_else(|| "-".into())),
             ])
         })
         .collect();
 
+    let title = match app.jobs.get(JobKind::Ethernet) {
+        Some(job) => {
+            let frame =
+                SPINNER[(job.elapsed(Instant::now()).as_millis() / 100) as usize % SPINNER.len()];
+            format!(" Interfaces ({frame} {}) ", job.label)
+        }
+        None => " Interfaces ".to_string(),
+    };
+
     let table = Table::new(
         rows,
         [
@@ -127,17 +192,20 @@ fn render_ifaces(app: &mut App, frame: &mut Frame, area: Rect) {
             Constraint::Length(9),
             Constraint::Length(7),
             Constraint::Length(8),
+            Constraint::Length(12),
             Constraint::Min(12),
         ],
     )
     .header(
-        Row::new(vec!["", "Iface", "State", "Carrier", "Speed", "IPv4"])
-            .style(Style::default().fg(Color::Yellow).bold())
-            .bottom_margin(1),
+        Row::new(vec![
+            "", "Iface", "State", "Carrier", "Speed", "Setup", "IPv4",
+        ])
+        .style(Style::default().fg(Color::Yellow).bold())
+        .bottom_margin(1),
     )
     .block(
         Block::default()
-            .title(" Interfaces ")
+            .title(title)
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::Green))
             .border_type(BorderType::Thick),
@@ -146,3 +214,12 @@ fn render_ifaces(app: &mut App, frame: &mut Frame, area: Rect) {
 
     frame.render_stateful_widget(table, area, &mut app.ethernet_state);
 }
+
+fn setup_color(state: &str) -> Color {
+    match state {
+        "configured" => Color::Green,
+        "configuring" | "pending" | "initialized" => Color::Yellow,
+        "failed" | "linger" => Color::Red,
+        _ => Color::Reset,
+    }
+}
@@ -7,6 +7,7 @@ use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout},
 };
+use tokio::time::Instant;
 
 pub fn render(app: &mut App, frame: &mut Frame) {
     const MIN_W: u16 = 119;
@@ -29,19 +30,24 @@ pub fn render(app: &mut App, frame: &mut Frame) {
         .split(area);
 
     components::render_tabs(app, frame, chunks[0]);
+    // Before the tab content, whose popups may need the full height.
+    components::render_footer(app, frame, chunks[2]);
 
     match app.active_tab {
         ActiveTab::Wifi => wifi::render(app, frame, chunks[1]),
         ActiveTab::Ethernet => ethernet::render(app, frame, chunks[1]),
     }
 
-    components::render_footer(app, frame, chunks[2]);
-
     if let Some(err) = &app.last_error {
         components::render_error_popup(frame, err);
         return;
     }
 
+    if !app.jobs.is_empty() {
+        let jobs: Vec<_> = app.jobs.running().collect();
+        components::render_jobs_panel(frame, &jobs, Instant::now());
+    }
+
     if let Some(t) = &app.toast {
         components::render_toast_popup(frame, t.kind, &t.msg);
     }
@@ -1,22 +1,31 @@
 // Copyright (C) 2026 skibidiandulka
 // Clean-room implementation inspired by Impala UX by pythops.
 
-use crate::{app::App, domain::common::WifiFocus, domain::wifi::WifiDeviceInfo};
+use crate::{
+    app::App,
+    domain::common::WifiFocus,
+    domain::enterprise::{EnterpriseField, EnterpriseForm},
+    domain::profile::{ProfileField, ProfileForm},
+    domain::share::WifiShare,
+    domain::wifi::{KnownSort, SecretKind, WifiDeviceInfo, WifiNetwork, time_ago},
+};
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Style, Stylize},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Cell, Clear, Paragraph, Row, Table},
 };
+use std::time::SystemTime;
 
 pub fn render(app: &mut App, frame: &mut Frame, area: Rect) {
+    let device_rows = app.wifi.adapters.len().clamp(1, 3) as u16;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Min(7),
             Constraint::Min(6),
-            Constraint::Length(5),
+            Constraint::Length(4 + device_rows),
         ])
         .split(area);
 
@@ -33,15 +42,27 @@ pub fn render(app: &mut App, frame: &mut Frame, area: Rect) {
     if app.hidden_connect_prompt {
         render_hidden_connect_popup(app, frame);
     }
+    if let Some(form) = &app.enterprise_form {
+        render_enterprise_popup(form, frame);
+    }
+    if let Some(form) = &app.profile_form {
+        render_profile_popup(form, frame);
+    }
+    if let Some(share) = &app.wifi_share {
+        render_share_popup(share, frame);
+    }
 }
 
 fn render_known_networks(app: &mut App, frame: &mut Frame, area: Rect) {
     let focused = app.wifi_focus == WifiFocus::KnownNetworks;
-    let title = if app.wifi_connect_active() {
+    let title = if app.wifi_service_down {
+        format!(" Known Networks ({} unavailable) ", app.wifi_service_name())
+    } else if app.wifi_connect_active() {
         " Known Networks (Connecting) ".to_string()
     } else {
         " Known Networks ".to_string()
     };
+    let now = SystemTime::now();
     let mut rows: Vec<Row> = app
         .wifi
         .known_networks
@@ -61,6 +82,7 @@ fn render_known_networks(app: &mut App, frame: &mut Frame, area: Rect) {
                         .map(|v| if v { "Yes" } else { "No" })
                         .unwrap_or("-"),
                 ),
+                Cell::from(last_used(n, now)),
                 Cell::from(n.signal.clone()),
             ])
         })
@@ -83,6 +105,7 @@ fn render_known_networks(app: &mut App, frame: &mut Frame, area: Rect) {
                             .map(|v| if v { "Yes" } else { "No" })
                             .unwrap_or("-"),
                     ),
+                    Cell::from(last_used(n, now)),
                     Cell::from("-"),
                 ])
                 .dark_gray(),
@@ -90,6 +113,13 @@ fn render_known_networks(app: &mut App, frame: &mut Frame, area: Rect) {
         }
     }
 
+    if rows.is_empty() && app.wifi_service_down {
+        rows.push(Row::new(vec![
+            Cell::from(""),
+            Cell::from(format!("- {} is not running -", app.wifi_service_name())).dark_gray(),
+        ]));
+    }
+
     let table = Table::new(
         rows,
         [
@@ -98,17 +128,19 @@ fn render_known_networks(app: &mut App, frame: &mut Frame, area: Rect) {
             Constraint::Length(10),
             Constraint::Length(8),
             Constraint::Length(14),
+            Constraint::Length(14),
             Constraint::Length(10),
         ],
     )
     .header(
         Row::new(vec![
-            "",
-            "Name",
-            "Security",
-            "Hidden",
-            "Auto Connect",
-            "Signal",
+            String::new(),
+            sort_header("Name", app.known_sort == KnownSort::Name),
+            "Security".to_string(),
+            "Hidden".to_string(),
+            "Auto Connect".to_string(),
+            sort_header("Last Used", app.known_sort == KnownSort::LastUsed),
+            sort_header("Signal", app.known_sort == KnownSort::Signal),
         ])
         .style(Style::default().fg(Color::Yellow).bold())
         .bottom_margin(1),
@@ -123,6 +155,20 @@ fn render_known_networks(app: &mut App, frame: &mut Frame, area: Rect) {
     frame.render_stateful_widget(table, area, &mut app.wifi_known_state);
 }
 
+fn last_used(net: &WifiNetwork, now: SystemTime) -> String {
+    net.last_connected
+        .map(|t| time_ago(t, now))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn sort_header(label: &str, active: bool) -> String {
+    if active {
+        format!("{label} ▼")
+    } else {
+        label.to_string()
+    }
+}
+
 fn render_new_networks(app: &mut App, frame: &mut Frame, area: Rect) {
     let focused = app.wifi_focus == WifiFocus::NewNetworks;
     let title = if app.wifi_scanning_active() {
@@ -159,8 +205,13 @@ fn render_new_networks(app: &mut App, frame: &mut Frame, area: Rect) {
     }
 
     if rows.is_empty() {
+        let placeholder = if app.wifi_service_down {
+            format!("- {} is not running -", app.wifi_service_name())
+        } else {
+            "- no new networks -".to_string()
+        };
         rows.push(Row::new(vec![
-            Cell::from("- no new networks -").dark_gray(),
+            Cell::from(placeholder).dark_gray(),
             Cell::from(""),
             Cell::from(""),
         ]));
@@ -191,34 +242,41 @@ fn render_new_networks(app: &mut App, frame: &mut Frame, area: Rect) {
 
 fn render_device(app: &mut App, frame: &mut Frame, area: Rect) {
     let focused = app.wifi_focus == WifiFocus::Adapter;
-    let dev = app.wifi.device.clone().unwrap_or_else(|| WifiDeviceInfo {
-        iface: app
-            .wifi
-            .ifaces
-            .first()
-            .cloned()
-            .unwrap_or_else(|| "-".to_string()),
-        mode: "station".to_string(),
-        powered: "-".to_string(),
-        state: "-".to_string(),
-        scanning: "-".to_string(),
-        frequency: "-".to_string(),
-        security: "-".to_string(),
-    });
+    let mut adapters = app.wifi.adapters.clone();
+    if adapters.is_empty() {
+        adapters.push(WifiDeviceInfo {
+            iface: "-".to_string(),
+            mode: "station".to_string(),
+            powered: "-".to_string(),
+            state: "-".to_string(),
+            scanning: "-".to_string(),
+            frequency: "-".to_string(),
+            security: "-".to_string(),
+        });
+    }
+    let selected = app.wifi.selected_iface.clone();
 
-    let rows = vec![Row::new(vec![
-        Cell::from(dev.iface),
-        Cell::from(dev.mode),
-        Cell::from(dev.powered),
-        Cell::from(dev.state),
-        Cell::from(dev.scanning),
-        Cell::from(dev.frequency),
-        Cell::from(dev.security),
-    ])];
+    let rows: Vec<Row> = adapters
+        .into_iter()
+        .map(|dev| {
+            let active = selected.as_deref() == Some(dev.iface.as_str());
+            Row::new(vec![
+                Cell::from(if active { "●" } else { "" }),
+                Cell::from(dev.iface),
+                Cell::from(dev.mode),
+                Cell::from(dev.powered),
+                Cell::from(dev.state),
+                Cell::from(dev.scanning),
+                Cell::from(dev.frequency),
+                Cell::from(dev.security),
+            ])
+        })
+        .collect();
 
     let table = Table::new(
         rows,
         [
+            Constraint::Length(2),
             Constraint::Percentage(14),
             Constraint::Percentage(11),
             Constraint::Percentage(11),
@@ -230,6 +288,7 @@ fn render_device(app: &mut App, frame: &mut Frame, area: Rect) {
     )
     .header(
         Row::new(vec![
+            "",
             "Name",
             "Mode",
             "Powered",
@@ -297,16 +356,246 @@ fn render_hidden_connect_popup(app: &App, frame: &mut Frame) {
     frame.render_widget(Paragraph::new(content), inner);
 }
 
+fn render_enterprise_popup(form: &EnterpriseForm, frame: &mut Frame) {
+    let area = centered_rect(62, 50, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Enterprise Wi-Fi (802.1X) ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Thick)
+        .border_style(Style::default().fg(Color::Blue));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::from("SSID: ").bold(),
+            Span::from(form.ssid.clone()).fg(Color::Cyan),
+        ]),
+        Line::from(""),
+    ];
+    for field in EnterpriseField::ALL {
+        let active = field == form.field;
+        let marker = if active { "▶ " } else { "  " };
+        let value = form.display_value(field);
+        let value = if value.is_empty() && !active {
+            Span::from("-").fg(Color::DarkGray)
+        } else if active {
+            Span::from(value).fg(Color::Cyan)
+        } else {
+            Span::from(value)
+        };
+        lines.push(Line::from(vec![
+            Span::from(marker).fg(Color::Cyan),
+            Span::from(format!("{:<20}", field.label())).bold(),
+            value,
+        ]));
+    }
+    lines.extend([
+        Line::from(""),
+        Line::from("Optional: anonymous identity, CA certificate, server domain mask")
+            .fg(Color::DarkGray),
+        Line::from(""),
+        Line::from(vec![
+            Span::from("↵").bold(),
+            Span::from(" save & connect"),
+            Span::from(" | "),
+            Span::from("Tab/↑↓").bold(),
+            Span::from(" field"),
+            Span::from(" | "),
+            Span::from("←/→").bold(),
+            Span::from(" method"),
+            Span::from(" | "),
+            Span::from("Esc").bold(),
+            Span::from(" cancel"),
+        ]),
+    ]);
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn render_profile_popup(form: &ProfileForm, frame: &mut Frame) {
+    let area = centered_rect(62, 50, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Network Profile ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Thick)
+        .border_style(Style::default().fg(Color::Blue));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::from("SSID: ").bold(),
+            Span::from(form.ssid.clone()).fg(Color::Cyan),
+            Span::from(format!(" ({})", form.security)).fg(Color::DarkGray),
+        ]),
+        Line::from(""),
+    ];
+    for field in ProfileField::ALL {
+        if field == ProfileField::Ipv4Address {
+            lines.push(Line::from("Static IPv4 (empty address = DHCP)").fg(Color::DarkGray));
+        }
+        let active = field == form.field;
+        let marker = if active { "▶ " } else { "  " };
+        let value = form.display_value(field);
+        let value = if value.is_empty() && !active {
+            Span::from("-").fg(Color::DarkGray)
+        } else if active {
+            Span::from(value).fg(Color::Cyan)
+        } else {
+            Span::from(value)
+        };
+        lines.push(Line::from(vec![
+            Span::from(marker).fg(Color::Cyan),
+            Span::from(format!("{:<20}", field.label())).bold(),
+            value,
+        ]));
+    }
+    lines.extend([
+        Line::from(""),
+        Line::from(vec![
+            Span::from("↵").bold(),
+            Span::from(" save"),
+            Span::from(" | "),
+            Span::from("Tab/↑↓").bold(),
+            Span::from(" field"),
+            Span::from(" | "),
+            Span::from("Space").bold(),
+            Span::from(" toggle"),
+            Span::from(" | "),
+            Span::from("Esc").bold(),
+            Span::from(" cancel"),
+        ]),
+    ]);
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn render_share_popup(share: &WifiShare, frame: &mut Frame) {
+    let screen = frame.area();
+    let qr_height = share.lines.len() as u16;
+    // Border, banner and the Esc hint take four rows and the SSID/password line with its
+    // spacer two more. That line goes first when space runs out; a clipped QR code does
+    // not scan, so below that the code gives way to a message.
+    let show_qr = qr_height + 4 <= screen.height && share.width() + 2 <= screen.width;
+    let show_info = !show_qr || qr_height + 6 <= screen.height;
+    let body_height = if show_qr { qr_height } else { 1 };
+    let info_height = if show_info { 2 } else { 0 };
+
+    let mut info = vec![
+        Span::from("SSID: ").bold(),
+        Span::from(share.ssid.clone()).fg(Color::Cyan),
+    ];
+    if let Some(passphrase) = &share.passphrase {
+        info.extend([
+            Span::from("  Password: ").bold(),
+            Span::from(passphrase.clone()).fg(Color::Cyan),
+        ]);
+    }
+    let info = Line::from(info);
+
+    let info_width = if show_info {
+        info.width() as u16 + 4
+    } else {
+        0
+    };
+    let width = (share.width() + 4)
+        .max(info_width)
+        .max(56)
+        .min(screen.width);
+    let height = (body_height + info_height + 4).min(screen.height);
+    let area = Rect::new(
+        screen.width.saturating_sub(width) / 2,
+        screen.height.saturating_sub(height) / 2,
+        width,
+        height,
+    );
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Share Wi-Fi ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Thick)
+        .border_style(Style::default().fg(Color::Red));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(body_height),
+            Constraint::Length(info_height.saturating_sub(1)),
+            Constraint::Length(info_height.min(1)),
+            Constraint::Min(1),
+        ])
+        .split(inner);
+
+    frame.render_widget(
+        Paragraph::new(Line::from("⚠ The Wi-Fi password is visible on screen"))
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::White).bg(Color::Red).bold()),
+        chunks[0],
+    );
+
+    if show_qr {
+        let qr: Vec<Line> = share.lines.iter().map(|l| Line::from(l.as_str())).collect();
+        let qr_area = Rect {
+            x: chunks[1].x + chunks[1].width.saturating_sub(share.width()) / 2,
+            width: share.width().min(chunks[1].width),
+            ..chunks[1]
+        };
+        frame.render_widget(
+            Paragraph::new(qr).style(Style::default().fg(Color::White).bg(Color::Black)),
+            qr_area,
+        );
+    } else {
+        frame.render_widget(
+            Paragraph::new(Line::from("Terminal too small to show the QR code"))
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::Yellow)),
+            chunks[1],
+        );
+    }
+
+    if show_info {
+        frame.render_widget(Paragraph::new(info).alignment(Alignment::Center), chunks[3]);
+    }
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::from("Esc").bold(),
+            Span::from(" close"),
+        ]))
+        .alignment(Alignment::Center),
+        chunks[4],
+    );
+}
+
 fn render_wifi_passphrase_popup(app: &App, frame: &mut Frame) {
     let Some(ssid) = app.wifi_passphrase_prompt_ssid.clone() else {
         return;
     };
 
-    let area = centered_rect(62, 32, frame.area());
+    let kind = app.wifi_secret_request.as_ref().map(|r| r.kind.clone());
+    let with_username = kind == Some(SecretKind::UserNameAndPassword);
+    let (title, label) = match &kind {
+        Some(SecretKind::PrivateKeyPassphrase) => {
+            (" Wi-Fi Credentials ", "Private key passphrase:".to_string())
+        }
+        Some(SecretKind::UserNameAndPassword) => (" Wi-Fi Credentials ", "Password:".to_string()),
+        Some(SecretKind::UserPassword(user)) => {
+            (" Wi-Fi Credentials ", format!("Password for {user}:"))
+        }
+        _ => (" Wi-Fi Passphrase ", "Passphrase:".to_string()),
+    };
+
+    let area = centered_rect(62, if with_username { 46 } else { 32 }, frame.area());
     frame.render_widget(Clear, area);
 
     let block = Block::default()
-        .title(" Wi-Fi Passphrase ")
+        .title(title)
         .borders(Borders::ALL)
         .border_type(BorderType::Thick)
         .border_style(Style::default().fg(Color::Blue));
@@ -314,15 +603,19 @@ fn render_wifi_passphrase_popup(app: &App, frame: &mut Frame) {
     frame.render_widget(block, area);
 
     let masked = "*".repeat(app.wifi_passphrase_input.chars().count());
+    let mut constraints = vec![Constraint::Length(1)];
+    if with_username {
+        constraints.extend([Constraint::Length(1), Constraint::Length(3)]);
+    }
+    constraints.extend([
+        Constraint::Length(1),
+        Constraint::Length(3),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ]);
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(1),
-            Constraint::Length(1),
-            Constraint::Length(3),
-            Constraint::Length(1),
-            Constraint::Length(1),
-        ])
+        .constraints(constraints)
         .split(inner);
 
     let content = vec![Line::from(vec![
@@ -331,29 +624,65 @@ fn render_wifi_passphrase_popup(app: &App, frame: &mut Frame) {
     ])];
     frame.render_widget(Paragraph::new(content), chunks[0]);
 
+    let mut next = 1;
+    if with_username {
+        render_prompt_field(
+            frame,
+            "Username:",
+            app.wifi_username_input.clone(),
+            app.wifi_username_editing,
+            chunks[1],
+            chunks[2],
+        );
+        next = 3;
+    }
+    render_prompt_field(
+        frame,
+        &label,
+        masked,
+        !app.wifi_username_editing,
+        chunks[next],
+        chunks[next + 1],
+    );
+
+    let mut hints = vec![
+        Span::from("↵").bold(),
+        Span::from(" connect"),
+        Span::from(" | "),
+        Span::from("Esc").bold(),
+        Span::from(" cancel"),
+    ];
+    if with_username {
+        hints.extend([
+            Span::from(" | "),
+            Span::from("Tab").bold(),
+            Span::from(" switch field"),
+        ]);
+    }
+    frame.render_widget(Paragraph::new(Line::from(hints)), chunks[next + 3]);
+}
+
+fn render_prompt_field(
+    frame: &mut Frame,
+    label: &str,
+    value: String,
+    active: bool,
+    label_area: Rect,
+    field_area: Rect,
+) {
     frame.render_widget(
-        Paragraph::new(Line::from("Passphrase:").style(Style::default().bold())),
-        chunks[1],
+        Paragraph::new(Line::from(label.to_string()).style(Style::default().bold())),
+        label_area,
     );
 
+    let border = if active { Color::Cyan } else { Color::DarkGray };
     let field_block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan))
+        .border_style(Style::default().fg(border))
         .border_type(BorderType::Rounded);
-    let field_inner = field_block.inner(chunks[2]);
-    frame.render_widget(field_block, chunks[2]);
-    frame.render_widget(Paragraph::new(Line::from(Span::from(masked))), field_inner);
-
-    frame.render_widget(
-        Paragraph::new(Line::from(vec![
-            Span::from("↵").bold(),
-            Span::from(" connect"),
-            Span::from(" | "),
-            Span::from("Esc").bold(),
-            Span::from(" cancel"),
-        ])),
-        chunks[4],
-    );
+    let field_inner = field_block.inner(field_area);
+    frame.render_widget(field_block, field_area);
+    frame.render_widget(Paragraph::new(Line::from(Span::from(value))), field_inner);
 }
 
 fn render_details_popup(app: &App, frame: &mut Frame) {
@@ -362,8 +691,8 @@ fn render_details_popup(app: &App, frame: &mut Frame) {
 
     let title = app
         .wifi
-        .ifaces
-        .first()
+        .selected_iface
+        .as_ref()
         .map(|i| format!(" Wi-Fi Details ({i}) "))
         .unwrap_or_else(|| " Wi-Fi Details ".to_string());
 
@@ -0,0 +1,286 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use nettui::{
+    app::{App, AppConfig},
+    backend::{
+        BackendKind, Backends,
+        demo::{DemoBackend, Fixture},
+        traits::{CommandResult, EthernetBackend, WifiBackend},
+    },
+    domain::{
+        common::StartupTabPolicy,
+        enterprise::EnterpriseConfig,
+        ethernet::EthernetIface,
+        profile::KnownProfile,
+        wifi::{WifiSecret, WifiState},
+    },
+    event::Event,
+    handler::handle_key_events,
+    keybinds::Keybinds,
+};
+use ratatui::{Terminal, backend::TestBackend};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+pub struct Recorder {
+    demo: DemoBackend,
+    calls: Mutex<Vec<String>>,
+}
+
+impl Recorder {
+    fn record(&self, call: String) {
+        self.calls.lock().unwrap().push(call);
+    }
+}
+
+#[async_trait]
+impl WifiBackend for Recorder {
+    async fn query_state(&self, iface: Option<&str>) -> Result<WifiState> {
+        WifiBackend::query_state(&self.demo, iface).await
+    }
+
+    async fn scan(&self, iface: &str) -> Result<()> {
+        self.record(format!("scan {iface}"));
+        self.demo.scan(iface).await
+    }
+
+    async fn connect(&self, iface: &str, ssid: &str) -> Result<()> {
+        self.record(format!("connect {iface} {ssid}"));
+        self.demo.connect(iface, ssid).await
+    }
+
+    async fn connect_hidden(&self, iface: &str, ssid: &str) -> Result<()> {
+        self.record(format!("connect_hidden {iface} {ssid}"));
+        self.demo.connect_hidden(iface, ssid).await
+    }
+
+    async fn connect_with_passphrase(
+        &self,
+        iface: &str,
+        ssid: &str,
+        passphrase: &str,
+    ) -> Result<()> {
+        self.record(format!(
+            "connect_with_passphrase {iface} {ssid} {passphrase}"
+        ));
+        self.demo
+            .connect_with_passphrase(iface, ssid, passphrase)
+            .await
+    }
+
+    async fn disconnect(&self, iface: &str) -> Result<()> {
+        self.record(format!("disconnect {iface}"));
+        self.demo.disconnect(iface).await
+    }
+
+    async fn forget_known(&self, iface: &str, ssid: &str) -> Result<()> {
+        self.record(format!("forget_known {iface} {ssid}"));
+        self.demo.forget_known(iface, ssid).await
+    }
+
+    async fn toggle_autoconnect(&self, iface: &str, ssid: &str) -> Result<bool> {
+        self.record(format!("toggle_autoconnect {iface} {ssid}"));
+        self.demo.toggle_autoconnect(iface, ssid).await
+    }
+
+    async fn provision_enterprise(
+        &self,
+        ssid: &str,
+        config: &EnterpriseConfig,
+    ) -> Result<CommandResult> {
+        self.record(format!(
+            "provision_enterprise {ssid} {} {}",
+            config.method.outer(),
+            config.identity
+        ));
+        self.demo.provision_enterprise(ssid, config).await
+    }
+
+    async fn read_known_profile(&self, ssid: &str, security: &str) -> Result<KnownProfile> {
+        self.demo.read_known_profile(ssid, security).await
+    }
+
+    async fn read_passphrase(&self, ssid: &str, security: &str) -> Result<Option<String>> {
+        self.record(format!("read_passphrase {ssid}"));
+        self.demo.read_passphrase(ssid, security).await
+    }
+
+    async fn write_known_profile(
+        &self,
+        ssid: &str,
+        security: &str,
+        profile: &KnownProfile,
+    ) -> Result<CommandResult> {
+        self.record(format!(
+            "write_known_profile {ssid} autoconnect={} ipv4={}",
+            profile.autoconnect, profile.ipv4_address
+        ));
+        self.demo.write_known_profile(ssid, security, profile).await
+    }
+
+    fn watch(&self, events: UnboundedSender<Event>) -> bool {
+        WifiBackend::watch(&self.demo, events)
+    }
+
+    fn answer_secret(&self, id: u64, secret: Option<WifiSecret>) {
+        WifiBackend::answer_secret(&self.demo, id, secret)
+    }
+}
+
+#[async_trait]
+impl EthernetBackend for Recorder {
+    async fn list_ifaces(&self) -> Result<Vec<EthernetIface>> {
+        self.demo.list_ifaces().await
+    }
+
+    async fn iface_details(&self, iface: &str) -> Result<EthernetIface> {
+        self.demo.iface_details(iface).await
+    }
+
+    async fn renew_dhcp(&self, iface: &str) -> Result<CommandResult> {
+        self.record(format!("renew_dhcp {iface}"));
+        self.demo.renew_dhcp(iface).await
+    }
+
+    async fn reconfigure(&self, iface: &str) -> Result<CommandResult> {
+        self.record(format!("reconfigure {iface}"));
+        self.demo.reconfigure(iface).await
+    }
+
+    async fn force_renew(&self, iface: &str) -> Result<CommandResult> {
+        self.record(format!("force_renew {iface}"));
+        self.demo.force_renew(iface).await
+    }
+
+    async fn set_link_admin_state(&self, iface: &str, up: bool) -> Result<CommandResult> {
+        let state = if up { "up" } else { "down" };
+        self.record(format!("set_link_admin_state {iface} {state}"));
+        self.demo.set_link_admin_state(iface, up).await
+    }
+
+    fn watch(&self, events: UnboundedSender<Event>) -> bool {
+        EthernetBackend::watch(&self.demo, events)
+    }
+}
+
+pub struct Headless {
+    pub app: App,
+    backend: Arc<Recorder>,
+    events: UnboundedReceiver<Event>,
+}
+
+impl Headless {
+    pub async fn start(fixture: &str, tab: StartupTabPolicy) -> Self {
+        let backend = Arc::new(Recorder {
+            demo: DemoBackend::new(Fixture::parse(fixture).unwrap()),
+            calls: Mutex::new(Vec::new()),
+        });
+        let backends = Backends {
+            kind: BackendKind::Demo,
+            wifi: backend.clone(),
+            ethernet: backend.clone(),
+        };
+        let config = AppConfig {
+            startup_policy: tab,
+            ..AppConfig::default()
+        };
+        let mut app = App::with_backends(config, backends).await.unwrap();
+        app.keybinds = Keybinds::default();
+
+        let (sender, events) = mpsc::unbounded_channel();
+        app.watch_backend_events(sender);
+        Self {
+            app,
+            backend,
+            events,
+        }
+    }
+
+    // `<Tab>`, `<Enter>`, ... name special keys; every other character is typed as is.
+    pub async fn keys(&mut self, script: &str) {
+        let mut chars = script.chars();
+        while let Some(c) = chars.next() {
+            let code = if c == '<' {
+                let name: String = chars.by_ref().take_while(|c| *c != '>').collect();
+                special_key(&name)
+            } else {
+                KeyCode::Char(c)
+            };
+            self.press(code).await;
+        }
+    }
+
+    pub async fn press(&mut self, code: KeyCode) {
+        let modifiers = match code {
+            KeyCode::BackTab => KeyModifiers::SHIFT,
+            _ => KeyModifiers::NONE,
+        };
+        handle_key_events(KeyEvent::new(code, modifiers), &mut self.app)
+            .await
+            .unwrap();
+        self.step().await;
+    }
+
+    pub async fn advance(&mut self, ms: u64) {
+        let tick = self.app.config.tick_ms;
+        let mut left = ms;
+        while left > 0 {
+            let step = left.min(tick);
+            tokio::time::advance(Duration::from_millis(step)).await;
+            left -= step;
+            self.step().await;
+        }
+    }
+
+    pub fn screen(&mut self) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(119, 35)).unwrap();
+        terminal
+            .draw(|frame| nettui::ui::render(&mut self.app, frame))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let mut out = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                out.push_str(buffer[(x, y)].symbol());
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn calls(&self) -> Vec<String> {
+        self.backend.calls.lock().unwrap().clone()
+    }
+
+    async fn step(&mut self) {
+        for _ in 0..8 {
+            tokio::task::yield_now().await;
+        }
+        while let Ok(event) = self.events.try_recv() {
+            if let Event::Backend(event) = event {
+                self.app.handle_backend_event(event);
+            }
+        }
+        self.app.tick().await.unwrap();
+    }
+}
+
+fn special_key(name: &str) -> KeyCode {
+    match name {
+        "Tab" => KeyCode::Tab,
+        "BackTab" => KeyCode::BackTab,
+        "Enter" => KeyCode::Enter,
+        "Esc" => KeyCode::Esc,
+        "Backspace" => KeyCode::Backspace,
+        "Up" => KeyCode::Up,
+        "Down" => KeyCode::Down,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        _ => panic!("unknown key <{name}>"),
+    }
+}
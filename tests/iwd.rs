@@ -0,0 +1,484 @@
+mod support;
+
+use nettui::{
+    app::{App, AppConfig},
+    backend::{
+        BackendKind, Backends,
+        demo::{DemoBackend, Fixture},
+        iwd::IwdBackend,
+        privilege::{Escalation, PrivilegeConfig, PrivilegedRunner},
+        sysfs::Sysfs,
+        traits::WifiBackend,
+    },
+    domain::{
+        common::ToastKind, enterprise::EnterpriseConfig, profile::MacPolicy, wifi::signal_label,
+    },
+    error::NettuiError,
+    event::{BackendEvent, Event},
+    jobs::JobKind,
+};
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    sync::Arc,
+    time::{Duration, UNIX_EPOCH},
+};
+use support::{MockIwd, MockNetwork, MockState, PrivateBus, STATION_PATH, wifi_sysfs};
+use tempfile::TempDir;
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver},
+    time::timeout,
+};
+use zbus::zvariant::ObjectPath;
+
+struct Harness {
+    backend: IwdBackend,
+    iwd: MockIwd,
+    bus: PrivateBus,
+    root: TempDir,
+}
+
+async fn harness(state: MockState) -> Harness {
+    let bus = PrivateBus::start().expect("dbus-daemon is required to run the iwd tests");
+    let root = TempDir::new().unwrap();
+    wifi_sysfs(root.path(), &["wlan0"]);
+
+    let iwd = MockIwd::start(&bus.address, state).await;
+    let backend = IwdBackend::with_sysfs(Sysfs::under(root.path())).with_bus_address(&bus.address);
+    Harness {
+        backend,
+        iwd,
+        bus,
+        root,
+    }
+}
+
+fn networks() -> Vec<MockNetwork> {
+    vec![
+        MockNetwork::new("HomeNet", "psk", -4500)
+            .known(false, true)
+            .last_connected("2026-10-18T08:30:00Z"),
+        MockNetwork::new("Office", "8021x", -6000)
+            .known(true, false)
+            .out_of_range(),
+        MockNetwork::new("CoffeeShop", "open", -7000),
+        MockNetwork::new("Neighbor", "psk", -8000).passphrase("hunter22"),
+        MockNetwork::new("Flaky", "psk", -6500)
+            .known(false, true)
+            .failing_with("Failed"),
+    ]
+}
+
+#[tokio::test]
+async fn query_state_classifies_networks() {
+    let h = harness(MockState {
+        networks: networks(),
+        hidden_aps: vec![("aa:bb:cc:dd:ee:ff".to_string(), -5500, "psk".to_string())],
+        connected: Some("HomeNet".to_string()),
+        accept_agents: true,
+        ..MockState::default()
+    })
+    .await;
+
+    let wifi = h.backend.query_state(None).await.unwrap();
+    assert_eq!(wifi.selected_iface.as_deref(), Some("wlan0"));
+    assert_eq!(wifi.connected_ssid.as_deref(), Some("HomeNet"));
+
+    let known: Vec<&str> = wifi
+        .known_networks
+        .iter()
+        .map(|n| n.ssid.as_str())
+        .collect();
+    assert_eq!(known, vec!["Flaky", "HomeNet"]);
+    let home = &wifi.known_networks[1];
+    assert!(home.connected);
+    assert_eq!(home.security, "psk");
+    assert_eq!(home.signal, signal_label(100));
+    assert_eq!(home.autoconnect, Some(true));
+    assert_eq!(
+        home.last_connected,
+        Some(UNIX_EPOCH + Duration::from_secs(1_792_312_200))
+    );
+    assert_eq!(wifi.known_networks[0].last_connected, None);
+
+    assert_eq!(wifi.unavailable_known_networks.len(), 1);
+    let office = &wifi.unavailable_known_networks[0];
+    assert_eq!(office.ssid, "Office");
+    assert_eq!(office.signal, "-");
+    assert_eq!(office.hidden, Some(true));
+    assert_eq!(office.autoconnect, Some(false));
+    assert!(!office.available);
+
+    let new: Vec<&str> = wifi.new_networks.iter().map(|n| n.ssid.as_str()).collect();
+    assert_eq!(new, vec!["CoffeeShop", "Neighbor"]);
+    assert_eq!(wifi.new_networks[0].signal, signal_label(60));
+
+    assert_eq!(wifi.hidden_networks.len(), 1);
+    assert_eq!(wifi.hidden_networks[0].ssid, "aa:bb:cc:dd:ee:ff");
+    assert_eq!(wifi.hidden_networks[0].hidden, Some(true));
+
+    let adapter = &wifi.adapters[0];
+    assert_eq!(adapter.state, "Connected");
+    assert_eq!(adapter.frequency, "5.18 GHz");
+    assert_eq!(adapter.security, "WPA2-Personal");
+}
+
+#[tokio::test]
+async fn connect_surfaces_iwd_errors() {
+    let h = harness(MockState {
+        networks: networks(),
+        accept_agents: true,
+        ..MockState::default()
+    })
+    .await;
+
+    let err = h.backend.connect("wlan0", "Flaky").await.unwrap_err();
+    assert_eq!(NettuiError::of(&err), Some(&NettuiError::ConnectFailed));
+
+    let err = h.backend.connect("wlan0", "Nowhere").await.unwrap_err();
+    assert!(err.to_string().contains("network not found: Nowhere"));
+
+    let err = h
+        .backend
+        .connect_with_passphrase("wlan0", "Neighbor", "wrong")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        NettuiError::of(&err),
+        Some(&NettuiError::WrongPassphrase("Neighbor".to_string()))
+    );
+
+    // Flaky fails before iwd asks the agent, so the passphrase was never tried.
+    let err = h
+        .backend
+        .connect_with_passphrase("wlan0", "Flaky", "hunter22")
+        .await
+        .unwrap_err();
+    assert_eq!(NettuiError::of(&err), Some(&NettuiError::ConnectFailed));
+
+    h.backend.connect("wlan0", "CoffeeShop").await.unwrap();
+    let wifi = h.backend.query_state(None).await.unwrap();
+    assert_eq!(wifi.connected_ssid.as_deref(), Some("CoffeeShop"));
+
+    h.backend.cancel_connect("wlan0").await.unwrap();
+    let err = h.backend.disconnect("wlan0").await.unwrap_err();
+    assert_eq!(NettuiError::of(&err), Some(&NettuiError::NotConnected));
+    h.backend.cancel_connect("wlan0").await.unwrap();
+}
+
+#[tokio::test]
+async fn passphrase_falls_back_to_prompt_without_agent() {
+    let h = harness(MockState {
+        networks: networks(),
+        accept_agents: false,
+        ..MockState::default()
+    })
+    .await;
+
+    let err = h.backend.connect("wlan0", "Neighbor").await.unwrap_err();
+    assert!(err.to_string().contains("passphrase required for Neighbor"));
+
+    // Once the agent can register, the collected passphrase is handed over through it.
+    h.iwd.state.lock().unwrap().accept_agents = true;
+    let err = h.backend.connect("wlan0", "Neighbor").await.unwrap_err();
+    assert!(err.to_string().contains("passphrase required for Neighbor"));
+
+    h.backend
+        .connect_with_passphrase("wlan0", "Neighbor", "hunter22")
+        .await
+        .unwrap();
+    let wifi = h.backend.query_state(None).await.unwrap();
+    assert_eq!(wifi.connected_ssid.as_deref(), Some("Neighbor"));
+    assert!(wifi.known_networks.iter().any(|n| n.ssid == "Neighbor"));
+}
+
+#[tokio::test]
+async fn agent_only_answers_iwd() {
+    let h = harness(MockState {
+        networks: networks(),
+        accept_agents: true,
+        ..MockState::default()
+    })
+    .await;
+    let (tx, mut events) = mpsc::unbounded_channel();
+    assert!(h.backend.watch(tx));
+    h.backend.query_state(None).await.unwrap();
+    let (agent, path) = h.iwd.state.lock().unwrap().agent.clone().unwrap();
+
+    let intruder = zbus::connection::Builder::address(h.bus.address.as_str())
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    let network = ObjectPath::try_from(STATION_PATH).unwrap();
+    let request = intruder.call_method(
+        Some(agent.as_str()),
+        path.as_str(),
+        Some("net.connman.iwd.Agent"),
+        "RequestPassphrase",
+        &network,
+    );
+    let err = timeout(Duration::from_secs(2), request)
+        .await
+        .expect("the agent should refuse without prompting")
+        .unwrap_err();
+    let zbus::Error::MethodError(name, _, _) = err else {
+        panic!("expected a method error, got {err:?}");
+    };
+    assert_eq!(name.as_str(), "net.connman.iwd.Agent.Error.Canceled");
+    assert!(
+        timeout(Duration::from_millis(300), async {
+            loop {
+                if let Some(Event::Backend(BackendEvent::SecretRequested(_))) = events.recv().await
+                {
+                    break;
+                }
+            }
+        })
+        .await
+        .is_err()
+    );
+
+    // iwd itself still gets the passphrase handed to connect.
+    h.backend
+        .connect_with_passphrase("wlan0", "Neighbor", "hunter22")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn adapter_selection_falls_back_when_the_adapter_disappears() {
+    let h = harness(MockState {
+        networks: networks(),
+        connected: Some("HomeNet".to_string()),
+        ..MockState::default()
+    })
+    .await;
+    wifi_sysfs(h.root.path(), &["wlan1"]);
+    h.iwd.add_adapter("wlan1").await;
+
+    let wifi = h.backend.query_state(Some("wlan1")).await.unwrap();
+    assert_eq!(wifi.ifaces, ["wlan0", "wlan1"]);
+    assert_eq!(wifi.selected_iface.as_deref(), Some("wlan1"));
+    assert_eq!(wifi.connected_ssid, None);
+    assert!(wifi.known_networks.is_empty());
+    assert_eq!(wifi.selected_adapter().unwrap().state, "Disconnected");
+    assert_eq!(wifi.adapters[0].state, "Connected");
+    h.backend.scan("wlan1").await.unwrap();
+    assert_eq!(h.iwd.state.lock().unwrap().scans, ["wlan1"]);
+
+    // iwd dropped the adapter while sysfs still lists it: fall back to the first station.
+    h.iwd.remove_adapter("wlan1").await;
+    let wifi = h.backend.query_state(Some("wlan1")).await.unwrap();
+    assert_eq!(wifi.selected_iface.as_deref(), Some("wlan0"));
+    assert_eq!(wifi.connected_ssid.as_deref(), Some("HomeNet"));
+    let err = h.backend.scan("wlan1").await.unwrap_err();
+    assert_eq!(err.to_string(), "no wifi station found for wlan1");
+}
+
+async fn count_wifi_changes(events: &mut UnboundedReceiver<Event>) -> usize {
+    let mut count = 0;
+    while let Ok(Some(event)) = timeout(Duration::from_millis(500), events.recv()).await {
+        if matches!(event, Event::Backend(BackendEvent::WifiChanged)) {
+            count += 1;
+        }
+    }
+    count
+}
+
+#[tokio::test]
+async fn signal_bursts_become_one_refresh() {
+    let h = harness(MockState {
+        networks: networks(),
+        ..MockState::default()
+    })
+    .await;
+    let (tx, mut events) = mpsc::unbounded_channel();
+    assert!(h.backend.watch(tx));
+
+    // The watch subscribes in the background; wait until it sees a signal.
+    loop {
+        h.iwd
+            .emit_properties_changed(STATION_PATH, "net.connman.iwd.Station")
+            .await;
+        if timeout(Duration::from_secs(1), events.recv()).await.is_ok() {
+            break;
+        }
+    }
+    count_wifi_changes(&mut events).await;
+
+    h.iwd
+        .emit_properties_changed(STATION_PATH, "net.connman.iwd.BasicServiceSet")
+        .await;
+    assert_eq!(count_wifi_changes(&mut events).await, 0);
+
+    for _ in 0..3 {
+        h.backend
+            .toggle_autoconnect("wlan0", "HomeNet")
+            .await
+            .unwrap();
+    }
+    assert_eq!(count_wifi_changes(&mut events).await, 1);
+}
+
+async fn refresh_until(app: &mut App, done: impl Fn(&App) -> bool) {
+    for _ in 0..200 {
+        app.tick().await.unwrap();
+        if done(app) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("refresh did not settle");
+}
+
+#[tokio::test]
+async fn app_reports_iwd_going_away_and_coming_back() {
+    let h = harness(MockState {
+        networks: networks(),
+        ..MockState::default()
+    })
+    .await;
+    let backends = Backends {
+        kind: BackendKind::Iwd,
+        wifi: Arc::new(h.backend),
+        ethernet: Arc::new(DemoBackend::new(Fixture::parse("").unwrap())),
+    };
+    let config = AppConfig {
+        data_refresh_ms: 20,
+        ..AppConfig::default()
+    };
+    let mut app = App::with_backends(config, backends).await.unwrap();
+    assert!(!app.wifi_service_down);
+    assert_eq!(app.wifi.known_networks.len(), 2);
+
+    // A missing station is an ordinary error, not an outage.
+    let net = h.root.path().join("sys/class/net");
+    fs::rename(net.join("wlan0"), net.join("wlan1")).unwrap();
+    refresh_until(&mut app, |app| app.wifi.known_networks.is_empty()).await;
+    assert!(!app.wifi_service_down);
+    let toast = app.toast.as_ref().unwrap();
+    assert_eq!(toast.msg, "Wi-Fi refresh failed: no wifi station found");
+    fs::rename(net.join("wlan1"), net.join("wlan0")).unwrap();
+
+    h.iwd.stop().await;
+    refresh_until(&mut app, |app| app.wifi_service_down).await;
+    assert_eq!(app.last_action.as_deref(), Some("iwd went away"));
+    assert!(app.wifi.known_networks.is_empty());
+
+    let _iwd = MockIwd::start(
+        &h.bus.address,
+        MockState {
+            networks: networks(),
+            ..MockState::default()
+        },
+    )
+    .await;
+    refresh_until(&mut app, |app| !app.wifi_service_down).await;
+    assert_eq!(app.last_action.as_deref(), Some("iwd came back"));
+    assert_eq!(app.wifi.known_networks.len(), 2);
+}
+
+#[tokio::test]
+async fn enterprise_profile_lands_in_the_state_dir() {
+    let dir = TempDir::new().unwrap();
+    let runner = PrivilegedRunner::new(PrivilegeConfig {
+        order: vec![Escalation::None],
+        dry_run: false,
+    });
+    let backend = IwdBackend::new()
+        .with_runner(Arc::new(runner))
+        .with_state_dir(dir.path());
+    let config = EnterpriseConfig {
+        identity: "alice@example.org".to_string(),
+        password: "s3cret".to_string(),
+        ..EnterpriseConfig::default()
+    };
+
+    let out = backend
+        .provision_enterprise("Campus WiFi", &config)
+        .await
+        .unwrap();
+    assert_eq!(out.escalation, Escalation::None);
+
+    let path = dir.path().join("Campus WiFi.8021x");
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(written.contains("EAP-Method=PEAP\n"));
+    assert!(written.contains("EAP-PEAP-Phase2-Password=s3cret\n"));
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[tokio::test]
+async fn enterprise_dry_run_reports_the_plan_without_connecting() {
+    let h = harness(MockState {
+        networks: networks(),
+        ..MockState::default()
+    })
+    .await;
+    let runner = PrivilegedRunner::new(PrivilegeConfig {
+        order: vec![Escalation::None],
+        dry_run: true,
+    });
+    let state_dir = TempDir::new().unwrap();
+    let backends = Backends {
+        kind: BackendKind::Iwd,
+        wifi: Arc::new(
+            h.backend
+                .with_runner(Arc::new(runner))
+                .with_state_dir(state_dir.path()),
+        ),
+        ethernet: Arc::new(DemoBackend::new(Fixture::parse("").unwrap())),
+    };
+    let mut app = App::with_backends(AppConfig::default(), backends)
+        .await
+        .unwrap();
+
+    app.open_enterprise_form("Office".to_string());
+    let form = app.enterprise_form.as_mut().unwrap();
+    form.config.identity = "alice@example.org".to_string();
+    form.config.password = "s3cret".to_string();
+    app.submit_enterprise_form();
+    refresh_until(&mut app, |app| !app.jobs.is_running(JobKind::WifiConnect)).await;
+
+    let toast = app.toast.as_ref().unwrap();
+    assert!(matches!(toast.kind, ToastKind::Info));
+    assert!(toast.msg.starts_with("Dry run: "), "{}", toast.msg);
+    assert_eq!(app.last_action.as_deref(), Some(toast.msg.as_str()));
+    assert_ne!(app.wifi.connected_ssid.as_deref(), Some("Office"));
+    assert!(!state_dir.path().join("Office.8021x").exists());
+}
+
+#[tokio::test]
+async fn known_profile_edits_keep_the_passphrase() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("Home.psk");
+    fs::write(&path, "[Security]\nPassphrase=hunter22\n").unwrap();
+    let runner = PrivilegedRunner::new(PrivilegeConfig {
+        order: vec![Escalation::None],
+        dry_run: false,
+    });
+    let backend = IwdBackend::new()
+        .with_runner(Arc::new(runner))
+        .with_state_dir(dir.path());
+
+    let mut profile = backend.read_known_profile("Home", "Psk").await.unwrap();
+    assert!(profile.autoconnect);
+    profile.autoconnect = false;
+    profile.mac_policy = MacPolicy::Randomize;
+    profile.ipv4_address = "10.0.0.5".to_string();
+    profile.validate().unwrap();
+    backend
+        .write_known_profile("Home", "Psk", &profile)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "[Security]\nPassphrase=hunter22\n\n\
+         [Settings]\nAutoConnect=false\nAlwaysRandomizeAddress=true\n\n\
+         [IPv4]\nAddress=10.0.0.5\n"
+    );
+    let reread = backend.read_known_profile("Home", "Psk").await.unwrap();
+    assert_eq!(reread.mac_policy, MacPolicy::Randomize);
+}
@@ -0,0 +1,337 @@
+mod headless;
+
+use headless::Headless;
+use nettui::domain::common::StartupTabPolicy;
+
+const FIXTURE: &str = r#"
+[wifi]
+adapters = ["wlan0"]
+
+[[wifi.networks]]
+ssid = "HomeNet"
+security = "psk"
+signal = 82
+known = true
+passphrase = "correct horse"
+
+[[wifi.networks]]
+ssid = "CoffeeShop"
+signal = 67
+
+[[wifi.networks]]
+ssid = "Neighbor-5G"
+security = "psk"
+signal = 55
+passphrase = "hunter22"
+
+[[wifi.networks]]
+ssid = "UniCampus"
+security = "8021x"
+signal = 40
+
+[[wifi.networks]]
+ssid = "SlowAP"
+security = "psk"
+signal = 21
+known = true
+outcome = "timeout"
+
+[[ethernet]]
+name = "enp3s0"
+carrier = true
+ipv4 = ["192.168.1.42/24"]
+
+[[events]]
+after_ms = 5000
+kind = "carrier_lost"
+iface = "enp3s0"
+"#;
+
+#[tokio::test(start_paused = true)]
+async fn passphrase_prompt_connects_new_network() {
+    let mut h = Headless::start(FIXTURE, StartupTabPolicy::ForceWifi).await;
+
+    h.keys("<Tab>j<Enter>").await;
+    assert!(h.screen().contains("Wi-Fi Passphrase"));
+    assert!(h.screen().contains("SSID: Neighbor-5G"));
+
+    h.keys("hunter2<Backspace>22").await;
+    assert!(h.screen().contains("********"));
+    h.keys("<Enter>").await;
+    assert!(h.screen().contains("Connecting to Neighbor-5G..."));
+
+    h.advance(1000).await;
+    let screen = h.screen();
+    assert!(screen.contains("Connected to Neighbor-5G"));
+    assert!(!screen.contains("Wi-Fi Passphrase"));
+    assert_eq!(
+        h.calls(),
+        vec![
+            "connect wlan0 Neighbor-5G",
+            "connect_with_passphrase wlan0 Neighbor-5G hunter22",
+        ]
+    );
+    assert_eq!(h.app.wifi.connected_ssid.as_deref(), Some("Neighbor-5G"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn wrong_passphrase_reopens_the_prompt() {
+    let mut h = Headless::start(FIXTURE, StartupTabPolicy::ForceWifi).await;
+
+    h.keys("<Tab>j<Enter>letmein<Enter>").await;
+    h.advance(1000).await;
+    let screen = h.screen();
+    assert!(screen.contains("Wrong passphrase for Neighbor-5G"));
+    assert!(screen.contains("SSID: Neighbor-5G"));
+
+    h.keys("hunter22<Enter>").await;
+    h.advance(1000).await;
+    assert!(h.screen().contains("Connected to Neighbor-5G"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn enterprise_wizard_provisions_then_connects() {
+    let mut h = Headless::start(FIXTURE, StartupTabPolicy::ForceWifi).await;
+
+    h.keys("<Tab>jj<Enter>").await;
+    let screen = h.screen();
+    assert!(screen.contains("Enterprise Wi-Fi (802.1X)"));
+    assert!(screen.contains("SSID: UniCampus"));
+
+    h.keys("<Enter>").await;
+    assert!(h.screen().contains("Identity cannot be empty"));
+
+    h.keys("<Right><Down>alice@example.org<Down><Down>s3cret<Enter>")
+        .await;
+    assert!(!h.screen().contains("Enterprise Wi-Fi (802.1X)"));
+    h.advance(1000).await;
+    assert!(h.screen().contains("Connected to UniCampus"));
+    assert_eq!(
+        h.calls(),
+        vec![
+            "provision_enterprise UniCampus TTLS alice@example.org",
+            "connect wlan0 UniCampus",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn profile_editor_validates_then_saves() {
+    let mut h = Headless::start(FIXTURE, StartupTabPolicy::ForceWifi).await;
+
+    h.keys("e").await;
+    h.advance(250).await;
+    let screen = h.screen();
+    assert!(screen.contains("Network Profile"));
+    assert!(screen.contains("SSID: HomeNet"));
+
+    h.keys(" <Down><Down><Down><Down><Down>10.0.0.5<Down><Down><Down>dns.example<Enter>")
+        .await;
+    assert!(h.screen().contains("DNS servers must be IPv4 addresses"));
+
+    h.keys(&"<Backspace>".repeat(11)).await;
+    h.keys("1.1.1.1<Enter>").await;
+    assert!(!h.screen().contains("Network Profile"));
+    h.advance(1000).await;
+    assert!(h.screen().contains("Saved profile for HomeNet"));
+    assert_eq!(
+        h.calls(),
+        vec!["write_known_profile HomeNet autoconnect=false ipv4=10.0.0.5"]
+    );
+    assert_eq!(h.app.wifi.known_networks[0].autoconnect, Some(false));
+}
+
+#[tokio::test(start_paused = true)]
+async fn known_network_actions_run_as_jobs() {
+    let mut h = Headless::start(FIXTURE, StartupTabPolicy::ForceWifi).await;
+
+    h.keys("t").await;
+    h.advance(250).await;
+    assert!(h.screen().contains("Autoconnect disabled for HomeNet"));
+    assert_eq!(h.app.wifi.known_networks[0].autoconnect, Some(false));
+
+    h.keys("d").await;
+    h.advance(250).await;
+    assert!(h.screen().contains("Forgot network HomeNet"));
+    assert!(
+        h.app
+            .wifi
+            .known_networks
+            .iter()
+            .all(|n| n.ssid != "HomeNet")
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn known_sort_cycles_and_keeps_selection() {
+    const SORTED: &str = r#"
+[wifi]
+adapters = ["wlan0"]
+
+[[wifi.networks]]
+ssid = "Attic"
+security = "psk"
+signal = 90
+known = true
+
+[[wifi.networks]]
+ssid = "Library"
+security = "psk"
+signal = 30
+known = true
+last_connected_mins_ago = 3
+
+[[wifi.networks]]
+ssid = "Studio"
+security = "psk"
+signal = 60
+known = true
+last_connected_mins_ago = 4000
+"#;
+    let mut h = Headless::start(SORTED, StartupTabPolicy::ForceWifi).await;
+    let order = |h: &Headless| {
+        h.app
+            .wifi
+            .known_networks
+            .iter()
+            .map(|n| n.ssid.clone())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(order(&h), ["Library", "Studio", "Attic"]);
+    let screen = h.screen();
+    assert!(screen.contains("Last Used ▼"));
+    assert!(screen.contains("3 min ago"));
+    assert!(screen.contains("2 days ago"));
+
+    h.keys("jo").await;
+    assert_eq!(order(&h), ["Attic", "Library", "Studio"]);
+    assert!(h.screen().contains("Known: sorted by name"));
+    assert_eq!(h.app.wifi_known_state.selected(), Some(2));
+
+    h.keys("o").await;
+    assert_eq!(order(&h), ["Attic", "Studio", "Library"]);
+    assert!(h.screen().contains("Signal ▼"));
+    h.keys("r").await;
+    h.advance(500).await;
+    assert_eq!(order(&h), ["Attic", "Studio", "Library"]);
+    assert_eq!(h.app.wifi_known_state.selected(), Some(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn share_key_shows_the_network_as_a_qr_code() {
+    let mut h = Headless::start(FIXTURE, StartupTabPolicy::ForceWifi).await;
+
+    h.keys("p").await;
+    h.advance(250).await;
+    let screen = h.screen();
+    assert!(screen.contains("Share Wi-Fi"));
+    assert!(screen.contains("The Wi-Fi password is visible on screen"));
+    assert!(screen.contains("Password: correct horse"));
+    assert!(screen.contains("▀▀▀▀▀▀▀▀"));
+    assert_eq!(h.calls(), vec!["read_passphrase HomeNet"]);
+
+    h.keys("<Esc>").await;
+    assert!(!h.screen().contains("Share Wi-Fi"));
+
+    h.keys("jp").await;
+    h.advance(250).await;
+    let screen = h.screen();
+    assert!(!screen.contains("Share Wi-Fi"));
+    assert!(screen.contains("No passphrase stored for SlowAP"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn connect_times_out_and_repeated_scan_is_rejected() {
+    let mut h = Headless::start(FIXTURE, StartupTabPolicy::ForceWifi).await;
+
+    h.keys("j<Enter>").await;
+    assert!(h.app.wifi_connect_active());
+    h.advance(19_000).await;
+    assert!(h.app.wifi_connect_active());
+    h.advance(2_000).await;
+    assert!(!h.app.wifi_connect_active());
+    assert!(h.screen().contains("Wi-Fi connect/disconnect timed out"));
+
+    h.keys("s").await;
+    assert!(h.screen().contains("New Networks (Scanning)"));
+    h.keys("s").await;
+    assert!(h.screen().contains("Wi-Fi scan already running"));
+    h.advance(1000).await;
+    assert!(h.screen().contains("Wi-Fi scan completed"));
+    assert_eq!(h.calls(), vec!["connect wlan0 SlowAP", "scan wlan0"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancel_key_aborts_a_pending_connect() {
+    let mut h = Headless::start(FIXTURE, StartupTabPolicy::ForceWifi).await;
+
+    h.keys("j<Enter>").await;
+    assert!(h.screen().contains("x cancel"));
+    h.advance(2_000).await;
+    h.keys("x").await;
+    assert!(!h.app.wifi_connect_active());
+    assert!(h.screen().contains("Canceled connecting to SlowAP"));
+
+    h.advance(500).await;
+    assert!(h.app.jobs.is_empty());
+    assert!(!h.screen().contains("x cancel"));
+    assert_eq!(h.calls(), vec!["connect wlan0 SlowAP", "disconnect wlan0"]);
+
+    h.keys("x").await;
+    assert!(h.screen().contains("No Wi-Fi operation to cancel"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn manual_refresh_lands_in_the_background_and_keeps_selection() {
+    let mut h = Headless::start(FIXTURE, StartupTabPolicy::ForceWifi).await;
+
+    h.keys("<Tab>j").await;
+    assert_eq!(h.app.selected_wifi_network().unwrap().ssid, "Neighbor-5G");
+    h.keys("r").await;
+    assert!(!h.screen().contains("Refreshed"));
+
+    h.advance(250).await;
+    assert!(
+        h.screen()
+            .contains("Refreshed (Known: 2, New: 3, Ethernet: 1)")
+    );
+    assert_eq!(h.app.selected_wifi_network().unwrap().ssid, "Neighbor-5G");
+}
+
+#[tokio::test(start_paused = true)]
+async fn scripted_link_events_reach_the_ethernet_tab() {
+    let mut h = Headless::start(FIXTURE, StartupTabPolicy::ForceEthernet).await;
+    assert!(h.app.ethernet.has_active());
+
+    h.advance(5_250).await;
+    assert!(!h.app.ethernet.has_active());
+    assert!(h.screen().contains("enp3s0: carrier lost"));
+
+    h.keys("n").await;
+    assert_eq!(h.calls(), vec!["renew_dhcp enp3s0"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn link_toggle_runs_in_the_background() {
+    let mut h = Headless::start(FIXTURE, StartupTabPolicy::ForceEthernet).await;
+
+    h.keys("<Enter>").await;
+    assert!(
+        h.screen()
+            .contains("Interfaces (⠋ Setting enp3s0 link down)")
+    );
+    h.keys("n").await;
+    assert!(
+        h.screen()
+            .contains("Wait for \"Setting enp3s0 link down\" to finish")
+    );
+
+    h.advance(500).await;
+    let screen = h.screen();
+    assert!(!screen.contains("Setting enp3s0 link down"));
+    assert!(screen.contains("enp3s0: link set down"));
+    assert!(screen.contains("Before: state=up; carrier=1"));
+    assert!(screen.contains("After:  state=down; carrier=0"));
+    assert_eq!(h.calls(), vec!["set_link_admin_state enp3s0 down"]);
+}
@@ -0,0 +1,241 @@
+use nettui::{
+    app::{App, AppConfig},
+    backend::{
+        BackendKind, Backends,
+        demo::{DemoBackend, Fixture},
+    },
+    domain::{
+        common::{ActiveTab, StartupTabPolicy},
+        profile::{KnownProfile, MacPolicy, ProfileForm},
+        share::WifiShare,
+    },
+    jobs::{JobKind, JobOutcome},
+    keybinds::Keybinds,
+};
+use ratatui::{Terminal, backend::TestBackend};
+use std::{env, fs, path::PathBuf, sync::Arc, time::Duration};
+
+const FIXTURE: &str = r#"
+[wifi]
+adapters = ["wlan0"]
+connected = "HomeNet"
+
+[[wifi.networks]]
+ssid = "HomeNet"
+security = "psk"
+signal = 82
+known = true
+last_connected_mins_ago = 125
+
+[[wifi.networks]]
+ssid = "Office"
+security = "8021x"
+signal = 40
+known = true
+in_range = false
+last_connected_mins_ago = 136800
+
+[[wifi.networks]]
+ssid = "CoffeeShop"
+signal = 67
+
+[[wifi.networks]]
+ssid = "Neighbor-5G"
+security = "psk"
+signal = 31
+
+[[ethernet]]
+name = "enp3s0"
+carrier = true
+speed_mbps = 1000
+mac = "52:54:00:12:34:56"
+ipv4 = ["192.168.1.42/24"]
+gateway_v4 = "192.168.1.1"
+dns = ["192.168.1.1"]
+setup = "configured"
+
+[[ethernet]]
+name = "enp4s0"
+setup = "unmanaged"
+"#;
+
+async fn app(fixture: &str, tab: StartupTabPolicy) -> App {
+    let demo = Arc::new(DemoBackend::new(Fixture::parse(fixture).unwrap()));
+    let backends = Backends {
+        kind: BackendKind::Demo,
+        wifi: demo.clone(),
+        ethernet: demo,
+    };
+    let config = AppConfig {
+        startup_policy: tab,
+        ..AppConfig::default()
+    };
+    let mut app = App::with_backends(config, backends).await.unwrap();
+    app.keybinds = Keybinds::default();
+    app
+}
+
+fn render(app: &mut App, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal
+        .draw(|frame| nettui::ui::render(app, frame))
+        .unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let mut out = String::new();
+    for y in 0..buffer.area.height {
+        let mut line = String::new();
+        for x in 0..buffer.area.width {
+            line.push_str(buffer[(x, y)].symbol());
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.txt"));
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing snapshot {}; run with UPDATE_SNAPSHOTS=1 to create it",
+            path.display()
+        )
+    });
+    assert!(
+        expected == actual,
+        "snapshot {name} changed; run with UPDATE_SNAPSHOTS=1 to accept\n--- expected\n{expected}\n--- actual\n{actual}"
+    );
+}
+
+#[tokio::test]
+async fn empty() {
+    let mut app = app("", StartupTabPolicy::ForceWifi).await;
+    assert_snapshot("empty_wifi", &render(&mut app, 119, 35));
+    app.active_tab = ActiveTab::Ethernet;
+    assert_snapshot("empty_ethernet", &render(&mut app, 119, 35));
+}
+
+#[tokio::test]
+async fn connected() {
+    let mut app = app(FIXTURE, StartupTabPolicy::ForceWifi).await;
+    assert_snapshot("connected_wifi", &render(&mut app, 119, 35));
+    app.active_tab = ActiveTab::Ethernet;
+    assert_snapshot("connected_ethernet", &render(&mut app, 119, 35));
+}
+
+#[tokio::test(start_paused = true)]
+async fn scanning() {
+    let mut app = app(FIXTURE, StartupTabPolicy::ForceWifi).await;
+    app.jobs.spawn(
+        JobKind::WifiScan,
+        "Scanning on wlan0",
+        Duration::from_secs(12),
+        std::future::pending::<anyhow::Result<()>>(),
+        |_: &mut App, _: JobOutcome<()>| {},
+    );
+    assert_snapshot("scanning", &render(&mut app, 119, 35));
+}
+
+#[tokio::test]
+async fn passphrase_prompt() {
+    let mut app = app(FIXTURE, StartupTabPolicy::ForceWifi).await;
+    app.open_wifi_passphrase_prompt("Neighbor-5G".to_string());
+    app.wifi_passphrase_input = "hunter22".to_string();
+    assert_snapshot("passphrase_prompt", &render(&mut app, 119, 35));
+}
+
+#[tokio::test]
+async fn enterprise_form() {
+    let mut app = app(FIXTURE, StartupTabPolicy::ForceWifi).await;
+    app.open_enterprise_form("Office".to_string());
+    app.toast = None;
+    let form = app.enterprise_form.as_mut().unwrap();
+    form.cycle_method(true);
+    form.next_field();
+    "alice@example.org".chars().for_each(|c| form.push(c));
+    form.next_field();
+    form.next_field();
+    "s3cret".chars().for_each(|c| form.push(c));
+    assert_snapshot("enterprise_form", &render(&mut app, 119, 35));
+}
+
+#[tokio::test]
+async fn profile_form() {
+    let mut app = app(FIXTURE, StartupTabPolicy::ForceWifi).await;
+    let profile = KnownProfile {
+        mac_policy: MacPolicy::Randomize,
+        ipv4_address: "192.168.1.20".to_string(),
+        ipv4_netmask: "255.255.255.0".to_string(),
+        ipv4_gateway: "192.168.1.1".to_string(),
+        ipv4_dns: "1.1.1.1 9.9.9.9".to_string(),
+        ..KnownProfile::default()
+    };
+    let mut form = ProfileForm::new("HomeNet".to_string(), "psk".to_string(), profile);
+    form.next_field();
+    form.next_field();
+    app.profile_form = Some(form);
+    assert_snapshot("profile_form", &render(&mut app, 119, 35));
+}
+
+#[tokio::test]
+async fn share_popup() {
+    let mut app = app(FIXTURE, StartupTabPolicy::ForceWifi).await;
+    let share = WifiShare::new("HomeNet", "psk", false, Some("hunter22".to_string())).unwrap();
+    app.wifi_share = Some(share);
+    assert_snapshot("share_popup", &render(&mut app, 119, 35));
+}
+
+#[tokio::test]
+async fn share_popup_long_passphrase() {
+    let mut app = app(FIXTURE, StartupTabPolicy::ForceWifi).await;
+    let passphrase = "correct-horse-battery-staple-".repeat(3)[..63].to_string();
+    let share = WifiShare::new(
+        "HomeNet-5G-Upstairs-Office-Wing",
+        "psk",
+        false,
+        Some(passphrase),
+    )
+    .unwrap();
+    app.wifi_share = Some(share);
+    assert_snapshot("share_popup_long_passphrase", &render(&mut app, 119, 35));
+}
+
+#[tokio::test]
+async fn share_popup_drops_the_password_line_before_the_code() {
+    let mut app = app(FIXTURE, StartupTabPolicy::ForceWifi).await;
+    let share = WifiShare::new(&";".repeat(32), "psk", false, Some(";".repeat(63))).unwrap();
+    assert!(share.lines.len() as u16 + 6 > 35);
+    app.wifi_share = Some(share);
+    let screen = render(&mut app, 119, 35);
+    assert!(!screen.contains("Password:"));
+    assert_snapshot("share_popup_dense_code", &screen);
+}
+
+#[tokio::test]
+async fn details_popup() {
+    let mut app = app(FIXTURE, StartupTabPolicy::ForceWifi).await;
+    app.toggle_wifi_details();
+    assert_snapshot("details_popup", &render(&mut app, 119, 35));
+}
+
+#[tokio::test]
+async fn error_popup() {
+    let mut app = app(FIXTURE, StartupTabPolicy::ForceEthernet).await;
+    app.last_error = Some("renew DHCP failed: not authorized to manage enp3s0".to_string());
+    assert_snapshot("error_popup", &render(&mut app, 119, 35));
+}
+
+#[tokio::test]
+async fn too_small() {
+    let mut app = app(FIXTURE, StartupTabPolicy::ForceWifi).await;
+    assert_snapshot("too_small", &render(&mut app, 118, 35));
+    assert_snapshot("too_small_height", &render(&mut app, 119, 34));
+}
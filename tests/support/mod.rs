@@ -0,0 +1,625 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufRead, BufReader},
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::{Arc, Mutex},
+};
+use tempfile::TempDir;
+use zbus::{
+    Connection, ObjectServer, fdo, interface,
+    names::OwnedUniqueName,
+    object_server::SignalEmitter,
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue},
+};
+
+pub const STATION_PATH: &str = "/net/connman/iwd/0/3";
+
+const BUS_CONFIG: &str = r#"<!DOCTYPE busconfig PUBLIC "-//freedesktop//DTD D-Bus Bus Configuration 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/busconfig.dtd">
+<busconfig>
+  <type>session</type>
+  <listen>unix:dir=DIR</listen>
+  <auth>EXTERNAL</auth>
+  <policy context="default">
+    <allow send_destination="*" eavesdrop="true"/>
+    <allow eavesdrop="true"/>
+    <allow own="*"/>
+  </policy>
+</busconfig>
+"#;
+
+pub struct PrivateBus {
+    daemon: Child,
+    pub address: String,
+    _dir: TempDir,
+}
+
+impl PrivateBus {
+    pub fn start() -> Option<Self> {
+        let dir = TempDir::new().ok()?;
+        let config = dir.path().join("bus.conf");
+        fs::write(
+            &config,
+            BUS_CONFIG.replace("DIR", &dir.path().to_string_lossy()),
+        )
+        .ok()?;
+
+        let mut daemon = Command::new("dbus-daemon")
+            .arg(format!("--config-file={}", config.display()))
+            .args(["--nofork", "--nopidfile", "--print-address=1"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take()?)
+            .read_line(&mut address)
+            .ok()?;
+
+        Some(Self {
+            daemon,
+            address: address.trim().to_string(),
+            _dir: dir,
+        })
+    }
+}
+
+impl Drop for PrivateBus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
+
+pub fn wifi_sysfs(root: &Path, ifaces: &[&str]) {
+    for iface in ifaces {
+        fs::create_dir_all(root.join("sys/class/net").join(iface).join("wireless")).unwrap();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockNetwork {
+    pub ssid: String,
+    pub security: String,
+    pub signal_dbm: i16,
+    pub in_range: bool,
+    pub known: Option<MockKnown>,
+    pub passphrase: Option<String>,
+    pub connect_error: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MockKnown {
+    pub hidden: bool,
+    pub autoconnect: bool,
+    pub last_connected: Option<&'static str>,
+}
+
+impl MockNetwork {
+    pub fn new(ssid: &str, security: &str, signal_dbm: i16) -> Self {
+        Self {
+            ssid: ssid.to_string(),
+            security: security.to_string(),
+            signal_dbm,
+            in_range: true,
+            known: None,
+            passphrase: None,
+            connect_error: None,
+        }
+    }
+
+    pub fn known(mut self, hidden: bool, autoconnect: bool) -> Self {
+        self.known = Some(MockKnown {
+            hidden,
+            autoconnect,
+            last_connected: None,
+        });
+        self
+    }
+
+    pub fn last_connected(mut self, time: &'static str) -> Self {
+        if let Some(known) = &mut self.known {
+            known.last_connected = Some(time);
+        }
+        self
+    }
+
+    pub fn out_of_range(mut self) -> Self {
+        self.in_range = false;
+        self
+    }
+
+    pub fn passphrase(mut self, passphrase: &str) -> Self {
+        self.passphrase = Some(passphrase.to_string());
+        self
+    }
+
+    pub fn failing_with(mut self, error: &'static str) -> Self {
+        self.connect_error = Some(error);
+        self
+    }
+
+    fn network_path(&self) -> OwnedObjectPath {
+        OwnedObjectPath::try_from(format!("{STATION_PATH}/{}", self.object_id())).unwrap()
+    }
+
+    fn known_path(&self) -> OwnedObjectPath {
+        OwnedObjectPath::try_from(format!("/net/connman/iwd/{}", self.object_id())).unwrap()
+    }
+
+    fn object_id(&self) -> String {
+        let hex: String = self.ssid.bytes().map(|b| format!("{b:02x}")).collect();
+        format!("{hex}_{}", self.security)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MockState {
+    pub networks: Vec<MockNetwork>,
+    pub hidden_aps: Vec<(String, i16, String)>,
+    pub connected: Option<String>,
+    pub accept_agents: bool,
+    pub agent: Option<(OwnedUniqueName, OwnedObjectPath)>,
+    pub scans: Vec<String>,
+}
+
+impl MockState {
+    fn network(&self, ssid: &str) -> Option<&MockNetwork> {
+        self.networks.iter().find(|n| n.ssid == ssid)
+    }
+
+    fn network_mut(&mut self, ssid: &str) -> Option<&mut MockNetwork> {
+        self.networks.iter_mut().find(|n| n.ssid == ssid)
+    }
+}
+
+pub type Shared = Arc<Mutex<MockState>>;
+
+#[derive(Debug, zbus::DBusError)]
+#[zbus(prefix = "net.connman.iwd")]
+pub enum IwdError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    NoAgent(String),
+    Aborted(String),
+    Failed(String),
+    NotFound(String),
+    InProgress(String),
+    NotConnected(String),
+}
+
+fn scripted_error(name: &str) -> IwdError {
+    match name {
+        "NoAgent" => IwdError::NoAgent("No Agent registered".to_string()),
+        "Aborted" => IwdError::Aborted("Operation aborted".to_string()),
+        "NotFound" => IwdError::NotFound("Object not found".to_string()),
+        "InProgress" => IwdError::InProgress("Operation already in progress".to_string()),
+        _ => IwdError::Failed("Operation failed".to_string()),
+    }
+}
+
+pub struct MockIwd {
+    pub state: Shared,
+    connection: Connection,
+}
+
+impl MockIwd {
+    pub async fn start(address: &str, state: MockState) -> Self {
+        let networks = state.networks.clone();
+        let state = Arc::new(Mutex::new(state));
+        let connection = zbus::connection::Builder::address(address)
+            .unwrap()
+            .name("net.connman.iwd")
+            .unwrap()
+            .serve_at("/", fdo::ObjectManager)
+            .unwrap()
+            .serve_at(
+                "/net/connman/iwd",
+                AgentManager {
+                    state: Arc::clone(&state),
+                },
+            )
+            .unwrap()
+            .serve_at(STATION_PATH, Device::new("wlan0"))
+            .unwrap()
+            .serve_at(
+                STATION_PATH,
+                Station {
+                    state: Arc::clone(&state),
+                    iface: "wlan0".to_string(),
+                },
+            )
+            .unwrap()
+            .serve_at(STATION_PATH, StationDiagnostic)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let server = connection.object_server();
+        for net in networks {
+            if net.in_range {
+                server
+                    .at(
+                        net.network_path(),
+                        Network {
+                            state: Arc::clone(&state),
+                            ssid: net.ssid.clone(),
+                        },
+                    )
+                    .await
+                    .unwrap();
+            }
+            if net.known.is_some() {
+                server
+                    .at(
+                        net.known_path(),
+                        KnownNetwork {
+                            state: Arc::clone(&state),
+                            ssid: net.ssid.clone(),
+                        },
+                    )
+                    .await
+                    .unwrap();
+            }
+        }
+
+        Self { state, connection }
+    }
+
+    // Extra adapters see no networks and stay disconnected.
+    pub async fn add_adapter(&self, iface: &str) {
+        let path = adapter_path(iface);
+        let server = self.connection.object_server();
+        server.at(&path, Device::new(iface)).await.unwrap();
+        server
+            .at(
+                &path,
+                Station {
+                    state: Arc::clone(&self.state),
+                    iface: iface.to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        server.at(&path, StationDiagnostic).await.unwrap();
+    }
+
+    pub async fn remove_adapter(&self, iface: &str) {
+        let path = adapter_path(iface);
+        let server = self.connection.object_server();
+        server.remove::<StationDiagnostic, _>(&path).await.unwrap();
+        server.remove::<Station, _>(&path).await.unwrap();
+        server.remove::<Device, _>(&path).await.unwrap();
+    }
+
+    pub async fn emit_properties_changed(&self, path: &str, interface: &str) {
+        let changed: HashMap<&str, zbus::zvariant::Value<'_>> = HashMap::new();
+        self.connection
+            .emit_signal(
+                None::<()>,
+                path,
+                "org.freedesktop.DBus.Properties",
+                "PropertiesChanged",
+                &(interface, changed, Vec::<&str>::new()),
+            )
+            .await
+            .unwrap();
+    }
+
+    pub async fn stop(self) {
+        self.connection.close().await.unwrap();
+    }
+}
+
+struct AgentManager {
+    state: Shared,
+}
+
+#[interface(name = "net.connman.iwd.AgentManager")]
+impl AgentManager {
+    fn register_agent(
+        &self,
+        path: OwnedObjectPath,
+        #[zbus(header)] header: zbus::message::Header<'_>,
+    ) -> Result<(), IwdError> {
+        let mut state = self.state.lock().unwrap();
+        if !state.accept_agents {
+            return Err(IwdError::Failed("agents disabled".to_string()));
+        }
+        let sender = header
+            .sender()
+            .ok_or_else(|| IwdError::Failed("no sender".to_string()))?;
+        state.agent = Some((sender.to_owned().into(), path));
+        Ok(())
+    }
+}
+
+fn adapter_path(iface: &str) -> OwnedObjectPath {
+    OwnedObjectPath::try_from(format!("/net/connman/iwd/0/{iface}")).unwrap()
+}
+
+struct Device {
+    name: String,
+}
+
+impl Device {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+        }
+    }
+}
+
+#[interface(name = "net.connman.iwd.Device")]
+impl Device {
+    #[zbus(property)]
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    #[zbus(property)]
+    fn mode(&self) -> String {
+        "station".to_string()
+    }
+
+    #[zbus(property)]
+    fn powered(&self) -> bool {
+        true
+    }
+}
+
+struct Station {
+    state: Shared,
+    iface: String,
+}
+
+impl Station {
+    fn primary(&self) -> bool {
+        self.iface == "wlan0"
+    }
+}
+
+#[interface(name = "net.connman.iwd.Station")]
+impl Station {
+    fn scan(&self) {
+        self.state.lock().unwrap().scans.push(self.iface.clone());
+    }
+
+    fn disconnect(&self) -> Result<(), IwdError> {
+        let mut state = self.state.lock().unwrap();
+        let connected = state.connected.take_if(|_| self.primary());
+        match connected {
+            Some(_) => Ok(()),
+            None => Err(IwdError::NotConnected("Not connected".to_string())),
+        }
+    }
+
+    fn get_ordered_networks(&self) -> Vec<(OwnedObjectPath, i16)> {
+        if !self.primary() {
+            return Vec::new();
+        }
+        let state = self.state.lock().unwrap();
+        let mut out: Vec<_> = state
+            .networks
+            .iter()
+            .filter(|n| n.in_range)
+            .map(|n| (n.network_path(), n.signal_dbm))
+            .collect();
+        out.sort_by_key(|(_, signal)| -signal);
+        out
+    }
+
+    fn get_hidden_access_points(&self) -> Vec<(String, i16, String)> {
+        self.state.lock().unwrap().hidden_aps.clone()
+    }
+
+    fn connect_hidden_network(&self, _name: String) -> Result<(), IwdError> {
+        Err(scripted_error("NotFound"))
+    }
+
+    #[zbus(property)]
+    fn state(&self) -> String {
+        match self.state.lock().unwrap().connected {
+            Some(_) if self.primary() => "connected".to_string(),
+            _ => "disconnected".to_string(),
+        }
+    }
+
+    #[zbus(property)]
+    fn connected_network(&self) -> OwnedObjectPath {
+        let state = self.state.lock().unwrap();
+        if !self.primary() {
+            return OwnedObjectPath::try_from("/").unwrap();
+        }
+        state
+            .connected
+            .as_deref()
+            .and_then(|ssid| state.network(ssid))
+            .map(|n| n.network_path())
+            .unwrap_or_else(|| OwnedObjectPath::try_from("/").unwrap())
+    }
+
+    #[zbus(property)]
+    fn scanning(&self) -> bool {
+        false
+    }
+}
+
+struct StationDiagnostic;
+
+#[interface(name = "net.connman.iwd.StationDiagnostic")]
+impl StationDiagnostic {
+    fn get_diagnostics(&self) -> HashMap<String, OwnedValue> {
+        HashMap::from([
+            ("Frequency".to_string(), OwnedValue::from(5180u32)),
+            (
+                "Security".to_string(),
+                OwnedValue::try_from(zbus::zvariant::Value::from("WPA2-Personal")).unwrap(),
+            ),
+        ])
+    }
+}
+
+struct Network {
+    state: Shared,
+    ssid: String,
+}
+
+#[interface(name = "net.connman.iwd.Network")]
+impl Network {
+    async fn connect(
+        &self,
+        #[zbus(connection)] connection: &Connection,
+        #[zbus(object_server)] server: &ObjectServer,
+    ) -> Result<(), IwdError> {
+        let (net, agent) = {
+            let state = self.state.lock().unwrap();
+            let net = state.network(&self.ssid).cloned().unwrap();
+            (net, state.agent.clone())
+        };
+        if let Some(error) = net.connect_error {
+            return Err(scripted_error(error));
+        }
+
+        if net.known.is_none() && net.security != "open" {
+            let Some((sender, path)) = agent else {
+                return Err(scripted_error("NoAgent"));
+            };
+            let reply = connection
+                .call_method(
+                    Some(sender.as_str()),
+                    path.as_str(),
+                    Some("net.connman.iwd.Agent"),
+                    "RequestPassphrase",
+                    &ObjectPath::from(net.network_path()),
+                )
+                .await
+                .map_err(|_| scripted_error("Aborted"))?;
+            let given: String = reply.body().deserialize()?;
+            if net.passphrase.as_deref() != Some(given.as_str()) {
+                return Err(scripted_error("Failed"));
+            }
+
+            self.state
+                .lock()
+                .unwrap()
+                .network_mut(&self.ssid)
+                .unwrap()
+                .known = Some(MockKnown {
+                hidden: false,
+                autoconnect: true,
+                last_connected: None,
+            });
+            server
+                .at(
+                    net.known_path(),
+                    KnownNetwork {
+                        state: Arc::clone(&self.state),
+                        ssid: self.ssid.clone(),
+                    },
+                )
+                .await?;
+        }
+
+        self.state.lock().unwrap().connected = Some(self.ssid.clone());
+        Ok(())
+    }
+
+    #[zbus(property)]
+    fn name(&self) -> String {
+        self.ssid.clone()
+    }
+
+    #[zbus(property, name = "Type")]
+    fn kind(&self) -> String {
+        let state = self.state.lock().unwrap();
+        state.network(&self.ssid).unwrap().security.clone()
+    }
+
+    #[zbus(property)]
+    fn connected(&self) -> bool {
+        self.state.lock().unwrap().connected.as_deref() == Some(self.ssid.as_str())
+    }
+}
+
+struct KnownNetwork {
+    state: Shared,
+    ssid: String,
+}
+
+impl KnownNetwork {
+    fn known(&self) -> MockKnown {
+        let state = self.state.lock().unwrap();
+        state
+            .network(&self.ssid)
+            .and_then(|n| n.known)
+            .unwrap_or_default()
+    }
+}
+
+#[interface(name = "net.connman.iwd.KnownNetwork")]
+impl KnownNetwork {
+    async fn forget(
+        &self,
+        #[zbus(object_server)] server: &ObjectServer,
+        #[zbus(header)] header: zbus::message::Header<'_>,
+    ) -> Result<(), IwdError> {
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.connected.as_deref() == Some(self.ssid.as_str()) {
+                state.connected = None;
+            }
+            if let Some(net) = state.network_mut(&self.ssid) {
+                net.known = None;
+            }
+        }
+        if let Some(path) = header.path() {
+            server.remove::<Self, _>(path.to_owned()).await?;
+        }
+        Ok(())
+    }
+
+    #[zbus(property)]
+    fn name(&self) -> String {
+        self.ssid.clone()
+    }
+
+    #[zbus(property, name = "Type")]
+    fn kind(&self) -> String {
+        let state = self.state.lock().unwrap();
+        state.network(&self.ssid).unwrap().security.clone()
+    }
+
+    #[zbus(property)]
+    fn hidden(&self) -> bool {
+        self.known().hidden
+    }
+
+    #[zbus(property)]
+    fn auto_connect(&self) -> bool {
+        self.known().autoconnect
+    }
+
+    #[zbus(property)]
+    fn last_connected_time(&self) -> String {
+        self.known().last_connected.unwrap_or_default().to_string()
+    }
+
+    #[zbus(property)]
+    async fn set_auto_connect(
+        &mut self,
+        value: bool,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        if let Some(net) = self.state.lock().unwrap().network_mut(&self.ssid) {
+            net.known = net.known.map(|k| MockKnown {
+                autoconnect: value,
+                ..k
+            });
+        }
+        Ok(self.auto_connect_changed(&emitter).await?)
+    }
+}
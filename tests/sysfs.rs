@@ -0,0 +1,173 @@
+use nettui::backend::{
+    iwd::IwdBackend,
+    networkd::NetworkdBackend,
+    privilege::PrivilegedRunner,
+    sysfs::Sysfs,
+    traits::{EthernetBackend, WifiBackend},
+};
+use std::{fs, path::Path, sync::Arc};
+use tempfile::TempDir;
+
+struct FakeHost {
+    dir: TempDir,
+}
+
+impl FakeHost {
+    fn new() -> Self {
+        let host = Self {
+            dir: TempDir::new().unwrap(),
+        };
+        host.write("sys/class/net/lo/operstate", "unknown");
+        host.write(
+            "proc/net/route",
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n",
+        );
+        host.write("etc/resolv.conf", "");
+        host
+    }
+
+    fn write(&self, rel: &str, contents: &str) {
+        let path = self.dir.path().join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, format!("{contents}\n")).unwrap();
+    }
+
+    fn physical(&self, name: &str, ifindex: u32) -> &Self {
+        let base = format!("sys/class/net/{name}");
+        fs::create_dir_all(self.dir.path().join(&base).join("device")).unwrap();
+        self.write(&format!("{base}/ifindex"), &ifindex.to_string());
+        self
+    }
+
+    fn attr(&self, name: &str, attr: &str, value: &str) -> &Self {
+        self.write(&format!("sys/class/net/{name}/{attr}"), value);
+        self
+    }
+
+    fn root(&self) -> &Path {
+        self.dir.path()
+    }
+
+    fn sysfs(&self) -> Sysfs {
+        Sysfs::under(self.root())
+    }
+}
+
+#[test]
+fn tells_physical_wired_from_virtual_and_wireless() {
+    let host = FakeHost::new();
+    host.physical("enp3s0", 2);
+    host.physical("wlan0", 3);
+    fs::create_dir_all(host.root().join("sys/class/net/wlan0/wireless")).unwrap();
+    host.physical("wlp2s0", 4);
+    host.write("sys/class/net/wlp2s0/phy80211", "");
+    host.attr("docker0", "ifindex", "5");
+    host.attr("veth1a2b", "ifindex", "6");
+
+    let sysfs = host.sysfs();
+    assert!(sysfs.is_physical("enp3s0"));
+    assert!(!sysfs.is_physical("docker0"));
+    assert!(!sysfs.is_physical("lo"));
+    assert!(sysfs.is_wifi("wlan0"));
+    assert!(!sysfs.is_wifi("enp3s0"));
+
+    assert_eq!(sysfs.wifi_ifaces(), vec!["wlan0", "wlp2s0"]);
+    let wired: Vec<String> = sysfs
+        .ethernet_ifaces()
+        .unwrap()
+        .into_iter()
+        .map(|i| i.name)
+        .collect();
+    assert_eq!(wired, vec!["enp3s0"]);
+}
+
+#[test]
+fn reads_carrier_speed_and_link_attributes() {
+    let host = FakeHost::new();
+    host.physical("enp3s0", 2)
+        .attr("enp3s0", "operstate", "up")
+        .attr("enp3s0", "carrier", "1")
+        .attr("enp3s0", "speed", "2500")
+        .attr("enp3s0", "mtu", "9000")
+        .attr("enp3s0", "address", "52:54:00:12:34:56")
+        .attr("enp3s0", "flags", "0x11043");
+    host.physical("enp4s0", 3)
+        .attr("enp4s0", "operstate", "down")
+        .attr("enp4s0", "carrier", "0")
+        .attr("enp4s0", "speed", "-1");
+    host.physical("enp5s0", 4);
+
+    let sysfs = host.sysfs();
+    let up = sysfs.iface("enp3s0").unwrap();
+    assert_eq!(up.ifindex, 2);
+    assert_eq!(up.operstate, "up");
+    assert_eq!(up.carrier, Some(true));
+    assert_eq!(up.speed_mbps, Some(2500));
+    assert_eq!(up.mtu, Some(9000));
+    assert_eq!(up.mac.as_deref(), Some("52:54:00:12:34:56"));
+    assert_eq!(
+        up.flags,
+        vec!["UP", "BROADCAST", "RUNNING", "MULTICAST", "LOWER_UP"]
+    );
+
+    let down = sysfs.iface("enp4s0").unwrap();
+    assert_eq!(down.carrier, Some(false));
+    assert_eq!(down.speed_mbps, None);
+    assert!(!down.is_active());
+
+    let bare = sysfs.iface("enp5s0").unwrap();
+    assert_eq!(bare.operstate, "?");
+    assert_eq!(bare.carrier, None);
+    assert_eq!(bare.mtu, None);
+}
+
+#[test]
+fn parses_gateway_and_dns_servers() {
+    let host = FakeHost::new();
+    host.physical("enp3s0", 2);
+    host.physical("enp4s0", 3);
+    host.write(
+        "proc/net/route",
+        "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+enp3s0\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0
+enp3s0\t0001A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0
+enp4s0\t0000000A\t00000000\t0001\t0\t0\t0\t000000FF\t0\t0\t0",
+    );
+    host.write(
+        "etc/resolv.conf",
+        "\
+# Generated by resolvconf
+search lan
+nameserver 192.168.1.1
+  nameserver 2001:db8::53
+options edns0",
+    );
+
+    let sysfs = host.sysfs();
+    let enp3s0 = sysfs.iface("enp3s0").unwrap();
+    assert_eq!(enp3s0.gateway_v4.as_deref(), Some("192.168.1.1"));
+    assert_eq!(enp3s0.routes.len(), 2);
+    assert_eq!(enp3s0.dns, vec!["192.168.1.1", "2001:db8::53"]);
+
+    let enp4s0 = sysfs.iface("enp4s0").unwrap();
+    assert_eq!(enp4s0.gateway_v4, None);
+    assert_eq!(enp4s0.routes[0].destination, "10.0.0.0/8");
+}
+
+#[tokio::test]
+async fn backends_read_the_injected_tree() {
+    let host = FakeHost::new();
+    host.physical("enp3s0", 2).attr("enp3s0", "carrier", "1");
+    host.attr("br0", "ifindex", "3");
+
+    let networkd = NetworkdBackend::with_sysfs(Arc::new(PrivilegedRunner::default()), host.sysfs());
+    let err = networkd.iface_details("br0").await.unwrap_err();
+    assert!(err.to_string().contains("not a physical interface"));
+    let err = networkd.iface_details("eth9").await.unwrap_err();
+    assert!(err.to_string().contains("interface not found"));
+
+    let iwd = IwdBackend::with_sysfs(host.sysfs());
+    let wifi = iwd.query_state(None).await.unwrap();
+    assert!(wifi.ifaces.is_empty());
+}